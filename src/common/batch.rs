@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::error::FinanceError;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_CONCURRENCY: usize = 16;
+const MAX_BATCH_ITEMS: usize = 1_000;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: batch_calculate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BatchItem {
+    #[schemars(description = "Caller-chosen identifier echoed back in the result (e.g., a company name)")]
+    #[serde(default)]
+    pub id: Option<String>,
+    #[schemars(description = "Name of the calculation to run (e.g., 'calculate_company_health_score')")]
+    pub function_name: String,
+    #[schemars(description = "Arguments for the calculation, exactly as they would be passed to the tool")]
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BatchCalculateParams {
+    #[schemars(description = "Calculations to run (max: 1000)")]
+    pub items: Vec<BatchItem>,
+    #[schemars(description = "Maximum number of items calculated at the same time (default: 4, max: 16)")]
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BatchItemResult {
+    #[schemars(description = "Position of the item in the request")]
+    pub index: usize,
+    #[schemars(description = "Identifier given with the item, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[schemars(description = "Calculation that was run")]
+    pub function_name: String,
+    #[schemars(description = "Calculation result, when the item succeeded")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[schemars(description = "Error returned by the calculation, when the item failed")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<FinanceError>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BatchCalculateResponse {
+    #[schemars(description = "Number of items in the batch")]
+    pub total_items: usize,
+    #[schemars(description = "Number of items that returned a result")]
+    pub succeeded: usize,
+    #[schemars(description = "Number of items that returned an error")]
+    pub failed: usize,
+    #[schemars(description = "Number of items calculated at the same time")]
+    pub concurrency: usize,
+    #[schemars(description = "One entry per item, in request order")]
+    pub results: Vec<BatchItemResult>,
+}
+
+// =================== BATCH ===================
+
+/// Run every item with `run` on up to `max_concurrency` worker threads.
+///
+/// A failing item is reported in its own entry and does not stop the others; only an
+/// invalid batch (no items, too many items, bad concurrency) fails the whole call.
+pub fn batch_calculate_internal<F>(params: BatchCalculateParams, run: F) -> Result<BatchCalculateResponse, FinanceError>
+where
+    F: Fn(&str, serde_json::Value) -> Result<serde_json::Value, FinanceError> + Sync,
+{
+    if params.items.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one batch item is required").with_field("items"));
+    }
+    if params.items.len() > MAX_BATCH_ITEMS {
+        return Err(FinanceError::out_of_range(
+            "items",
+            Some(1.0),
+            Some(MAX_BATCH_ITEMS as f64),
+            format!("A batch can hold at most {} items, got {}", MAX_BATCH_ITEMS, params.items.len()),
+        ));
+    }
+
+    let concurrency = params.max_concurrency.unwrap_or(DEFAULT_CONCURRENCY);
+    if concurrency == 0 || concurrency > MAX_CONCURRENCY {
+        return Err(FinanceError::out_of_range(
+            "max_concurrency",
+            Some(1.0),
+            Some(MAX_CONCURRENCY as f64),
+            format!("max_concurrency must be between 1 and {}", MAX_CONCURRENCY),
+        ));
+    }
+
+    let total_items = params.items.len();
+    let workers = concurrency.min(total_items);
+    let next = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<Result<serde_json::Value, FinanceError>>>> =
+        Mutex::new((0..total_items).map(|_| None).collect());
+
+    // Workers pull the next unclaimed item until the batch is exhausted
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = params.items.get(index) else {
+                        break;
+                    };
+                    let outcome = run(&item.function_name, item.arguments.clone());
+                    outcomes.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(outcome);
+                }
+            });
+        }
+    });
+
+    let outcomes = outcomes.into_inner().unwrap_or_else(|e| e.into_inner());
+    let results: Vec<BatchItemResult> = params
+        .items
+        .into_iter()
+        .zip(outcomes)
+        .enumerate()
+        .map(|(index, (item, outcome))| {
+            let outcome = outcome.unwrap_or_else(|| Err(FinanceError::internal("Batch item was not calculated")));
+            let (result, error) = match outcome {
+                Ok(value) => (Some(value), None),
+                Err(e) => (None, Some(e)),
+            };
+            BatchItemResult { index, id: item.id, function_name: item.function_name, result, error }
+        })
+        .collect();
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    Ok(BatchCalculateResponse {
+        total_items,
+        succeeded: total_items - failed,
+        failed,
+        concurrency: workers,
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::calculation::run_calculation_internal;
+    use crate::common::error::ErrorCode;
+
+    #[test]
+    fn test_batch_calculate_rejects_invalid_batches() {
+        let params = BatchCalculateParams { items: vec![], max_concurrency: None };
+        let error = batch_calculate_internal(params, run_calculation_internal).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InsufficientData);
+        let item = BatchItem { id: None, function_name: "calculate_organic_growth".to_string(), arguments: serde_json::json!({}) };
+        let params = BatchCalculateParams { items: vec![item], max_concurrency: Some(0) };
+        let error = batch_calculate_internal(params, run_calculation_internal).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("max_concurrency"));
+        assert_eq!(error.allowed_range.unwrap().max, Some(16.0));
+    }
+}
@@ -0,0 +1,382 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+
+use super::calculation::Calculation;
+use super::error::FinanceError;
+use super::money::round_ratio;
+use super::parsing::{deserialize_bool_flexible_opt, parse_f64_from_string, sanitize_for_error_message};
+
+const MIN_PEERS: usize = 3;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: benchmark_against_peers
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BenchmarkMetric {
+    #[schemars(description = "Metric name, matching the peer metric keys (e.g., 'ebitda_margin')")]
+    pub name: String,
+    #[schemars(description = "Target company value")]
+    pub value: f64,
+    #[schemars(description = "Whether a higher value ranks better (default: true; false for e.g. leverage or DSO)")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt")]
+    pub higher_is_better: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PeerCompany {
+    #[schemars(description = "Peer company name")]
+    pub name: String,
+    #[schemars(description = "Peer metric values keyed by metric name; missing metrics are skipped")]
+    pub metrics: HashMap<String, f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PeerBenchmarkParams {
+    #[schemars(description = "Target company name, excluded from the peer set if present")]
+    #[serde(default)]
+    pub company_name: Option<String>,
+    #[schemars(description = "Target company metrics to rank")]
+    pub metrics: Vec<BenchmarkMetric>,
+    #[schemars(description = "Peer set; falls back to the PEER_DATASET_FILE CSV when omitted")]
+    #[serde(default)]
+    pub peers: Option<Vec<PeerCompany>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct MetricBenchmark {
+    #[schemars(description = "Metric name")]
+    pub metric: String,
+    #[schemars(description = "Target company value")]
+    pub value: f64,
+    #[schemars(description = "Whether a higher value ranks better")]
+    pub higher_is_better: bool,
+    #[schemars(description = "Number of peers reporting the metric")]
+    pub peer_count: usize,
+    #[schemars(description = "Peer mean")]
+    pub peer_mean: f64,
+    #[schemars(description = "Peer median")]
+    pub peer_median: f64,
+    #[schemars(description = "Peer sample standard deviation")]
+    pub peer_std_dev: f64,
+    #[schemars(description = "Lowest peer value")]
+    pub peer_min: f64,
+    #[schemars(description = "Highest peer value")]
+    pub peer_max: f64,
+    #[schemars(description = "Share of peers the target beats, ties counting half (0-100, direction-adjusted)")]
+    pub percentile_rank: f64,
+    #[schemars(description = "(value - peer mean) / peer std dev, sign flipped when lower is better (None when peers do not vary)")]
+    pub z_score: Option<f64>,
+    #[schemars(description = "Quartile placement: Top quartile, Second quartile, Third quartile, Bottom quartile")]
+    pub quartile: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PeerBenchmarkResponse {
+    #[schemars(description = "Target company name, if given")]
+    pub company_name: Option<String>,
+    #[schemars(description = "Number of peers in the set")]
+    pub peer_count: usize,
+    #[schemars(description = "Where the peer set came from: request or file")]
+    pub peer_source: String,
+    #[schemars(description = "Benchmark per metric")]
+    pub metrics: Vec<MetricBenchmark>,
+    #[schemars(description = "Average direction-adjusted percentile rank across metrics")]
+    pub average_percentile: f64,
+    #[schemars(description = "Relative position: Peer Leader, Above Median, Below Median, Peer Laggard")]
+    pub relative_position: String,
+    #[schemars(description = "Metrics in the top quartile")]
+    pub strengths: Vec<String>,
+    #[schemars(description = "Metrics in the bottom quartile")]
+    pub weaknesses: Vec<String>,
+    #[schemars(description = "Warnings about thin or incomplete peer data")]
+    pub benchmark_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of the peer comparison")]
+    pub interpretation: String,
+}
+
+// =================== PEER DATASET FILE ===================
+
+/// Parse a peer dataset CSV: a `company,<metric>,<metric>...` header, then one row per peer.
+/// Empty cells leave the metric missing for that peer.
+fn parse_peer_dataset_csv(contents: &str) -> Result<Vec<PeerCompany>, String> {
+    let mut lines = contents.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    let Some((_, header)) = lines.next() else {
+        return Err("Dataset is empty".to_string());
+    };
+    let columns: Vec<&str> = header.split(',').map(|c| c.trim()).collect();
+    if columns.len() < 2 {
+        return Err("Header must name the company column and at least one metric".to_string());
+    }
+
+    let mut peers = Vec::new();
+    for (line_no, line) in lines {
+        let cells: Vec<&str> = line.split(',').map(|c| c.trim()).collect();
+        if cells.len() != columns.len() {
+            return Err(format!("Line {}: expected {} columns, found {}", line_no + 1, columns.len(), cells.len()));
+        }
+        let mut metrics = HashMap::new();
+        for (column, cell) in columns.iter().zip(cells.iter()).skip(1) {
+            if cell.is_empty() {
+                continue;
+            }
+            let value = parse_f64_from_string(cell).map_err(|_| {
+                format!("Line {}: invalid {} '{}'", line_no + 1, column, sanitize_for_error_message(cell))
+            })?;
+            metrics.insert(column.to_string(), value);
+        }
+        peers.push(PeerCompany { name: cells[0].to_string(), metrics });
+    }
+    Ok(peers)
+}
+
+fn load_peer_dataset_file() -> Result<Option<Vec<PeerCompany>>, String> {
+    let Ok(path) = env::var("PEER_DATASET_FILE") else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read PEER_DATASET_FILE '{}': {}", path, e))?;
+    parse_peer_dataset_csv(&contents)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse PEER_DATASET_FILE '{}': {}", path, e))
+}
+
+// =================== CALCULATION ===================
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n.is_multiple_of(2) {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    } else {
+        sorted[n / 2]
+    }
+}
+
+fn quartile(percentile: f64) -> &'static str {
+    match percentile {
+        p if p >= 75.0 => "Top quartile",
+        p if p >= 50.0 => "Second quartile",
+        p if p >= 25.0 => "Third quartile",
+        _ => "Bottom quartile",
+    }
+}
+
+/// Rank a company's metrics against a peer set
+pub fn benchmark_against_peers_internal(params: &PeerBenchmarkParams) -> Result<PeerBenchmarkResponse, FinanceError> {
+    if params.metrics.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one metric is required").with_field("metrics"));
+    }
+    let (peers, peer_source) = match params.peers.as_ref() {
+        Some(peers) => (peers.clone(), "request"),
+        None => match load_peer_dataset_file().map_err(FinanceError::upstream_unavailable)? {
+            Some(peers) => (peers, "file"),
+            None => {
+                return Err(
+                    FinanceError::insufficient_data("No peer set: provide peers or set PEER_DATASET_FILE").with_field("peers")
+                )
+            }
+        },
+    };
+
+    // The target may appear in a shared dataset; never benchmark it against itself
+    let target = params.company_name.as_deref().map(|n| n.trim().to_lowercase());
+    let peers: Vec<PeerCompany> = peers
+        .into_iter()
+        .filter(|p| target.as_deref() != Some(p.name.trim().to_lowercase().as_str()))
+        .collect();
+    if peers.is_empty() {
+        return Err(FinanceError::insufficient_data("The peer set is empty").with_field("peers"));
+    }
+
+    let mut benchmarks = Vec::with_capacity(params.metrics.len());
+    let mut benchmark_issues = Vec::new();
+    for metric in params.metrics.iter() {
+        if !metric.value.is_finite() {
+            return Err(FinanceError::invalid_input(format!(
+                "Invalid value for metric '{}'",
+                sanitize_for_error_message(&metric.name)
+            ))
+            .with_field(&format!("metrics.{}.value", metric.name)));
+        }
+        let mut values: Vec<f64> = peers
+            .iter()
+            .filter_map(|p| p.metrics.get(metric.name.trim()).copied())
+            .filter(|v| v.is_finite())
+            .collect();
+        if values.is_empty() {
+            return Err(FinanceError::insufficient_data(format!(
+                "No peer reports metric '{}'",
+                sanitize_for_error_message(&metric.name)
+            ))
+            .with_field("peers"));
+        }
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let n = values.len();
+        let higher_is_better = metric.higher_is_better.unwrap_or(true);
+        let mean = values.iter().sum::<f64>() / n as f64;
+        let std_dev = if n > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        let below = values.iter().filter(|v| **v < metric.value).count() as f64;
+        let above = values.iter().filter(|v| **v > metric.value).count() as f64;
+        let ties = n as f64 - below - above;
+        let beaten = if higher_is_better { below } else { above };
+        let percentile_rank = (beaten + 0.5 * ties) / n as f64 * 100.0;
+        let direction = if higher_is_better { 1.0 } else { -1.0 };
+        let z_score = (std_dev > 0.0).then(|| round_ratio(direction * (metric.value - mean) / std_dev, 2));
+
+        if n < MIN_PEERS {
+            benchmark_issues.push(format!(
+                "Only {} peer(s) report '{}'; percentile rank is not meaningful",
+                n, metric.name
+            ));
+        } else if n < peers.len() {
+            benchmark_issues.push(format!("{} of {} peers do not report '{}'", peers.len() - n, peers.len(), metric.name));
+        }
+        if z_score.is_some_and(|z| z.abs() > 3.0) {
+            benchmark_issues.push(format!("'{}' is more than 3 standard deviations from the peer mean", metric.name));
+        }
+
+        benchmarks.push(MetricBenchmark {
+            metric: metric.name.clone(),
+            value: metric.value,
+            higher_is_better,
+            peer_count: n,
+            peer_mean: round_ratio(mean, 4),
+            peer_median: round_ratio(median(&values), 4),
+            peer_std_dev: round_ratio(std_dev, 4),
+            peer_min: values[0],
+            peer_max: values[n - 1],
+            percentile_rank: round_ratio(percentile_rank, 2),
+            z_score,
+            quartile: quartile(percentile_rank).to_string(),
+        });
+    }
+
+    let average_percentile = benchmarks.iter().map(|b| b.percentile_rank).sum::<f64>() / benchmarks.len() as f64;
+    let relative_position = match average_percentile {
+        p if p >= 75.0 => "Peer Leader",
+        p if p >= 50.0 => "Above Median",
+        p if p >= 25.0 => "Below Median",
+        _ => "Peer Laggard",
+    };
+    let strengths: Vec<String> = benchmarks
+        .iter()
+        .filter(|b| b.quartile == "Top quartile")
+        .map(|b| b.metric.clone())
+        .collect();
+    let weaknesses: Vec<String> = benchmarks
+        .iter()
+        .filter(|b| b.quartile == "Bottom quartile")
+        .map(|b| b.metric.clone())
+        .collect();
+
+    let mut interpretation = format!(
+        "{} ranks at the {:.0}th percentile on average across {} metric(s) against {} peers ({}).",
+        params.company_name.as_deref().unwrap_or("The company"),
+        average_percentile,
+        benchmarks.len(),
+        peers.len(),
+        relative_position
+    );
+    if !strengths.is_empty() {
+        interpretation.push_str(&format!(" Top quartile: {}.", strengths.join(", ")));
+    }
+    if !weaknesses.is_empty() {
+        interpretation.push_str(&format!(" Bottom quartile: {}.", weaknesses.join(", ")));
+    }
+
+    Ok(PeerBenchmarkResponse {
+        company_name: params.company_name.clone(),
+        peer_count: peers.len(),
+        peer_source: peer_source.to_string(),
+        metrics: benchmarks,
+        average_percentile: round_ratio(average_percentile, 2),
+        relative_position: relative_position.to_string(),
+        strengths,
+        weaknesses,
+        benchmark_issues,
+        interpretation,
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Percentile ranking of company metrics against a peer group (`benchmark_against_peers`)
+pub struct PeerBenchmark;
+
+impl Calculation for PeerBenchmark {
+    const NAME: &'static str = "benchmark_against_peers";
+    const DESCRIPTION: &'static str = "Rank a company's metrics against a peer group so ratings can be relative instead of fixed thresholds. Takes the target company's metrics (with higher_is_better per metric) and a peer set inline or from the PEER_DATASET_FILE CSV. Returns per metric the percentile rank, z-score, quartile placement and peer mean, median, standard deviation and range, plus the average percentile, relative position (Peer Leader, Above Median, Below Median, Peer Laggard), strengths, weaknesses and data warnings. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "average_percentile";
+    const BAND_FIELD: Option<&'static str> = Some("relative_position");
+
+    type Params = PeerBenchmarkParams;
+    type Response = PeerBenchmarkResponse;
+
+    fn calculate(params: PeerBenchmarkParams) -> Result<PeerBenchmarkResponse, FinanceError> {
+        benchmark_against_peers_internal(&params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_against_peers() {
+        let params: PeerBenchmarkParams = serde_json::from_value(serde_json::json!({
+            "company_name": "Acme",
+            "metrics": [
+                {"name": "ebitda_margin", "value": 0.22},
+                {"name": "net_leverage", "value": 3.0, "higher_is_better": false}
+            ],
+            "peers": [
+                {"name": "Acme", "metrics": {"ebitda_margin": 0.22, "net_leverage": 3.0}},
+                {"name": "Peer A", "metrics": {"ebitda_margin": 0.10, "net_leverage": 2.0}},
+                {"name": "Peer B", "metrics": {"ebitda_margin": 0.15, "net_leverage": 3.0}},
+                {"name": "Peer C", "metrics": {"ebitda_margin": 0.20, "net_leverage": 4.0}},
+                {"name": "Peer D", "metrics": {"ebitda_margin": 0.25, "net_leverage": 5.0}}
+            ]
+        }))
+        .unwrap();
+
+        let response = PeerBenchmark::calculate(params).unwrap();
+
+        // Acme itself is dropped from the peer set
+        assert_eq!(response.peer_count, 4);
+
+        // Beats 3 of 4 peers on margin
+        let margin = &response.metrics[0];
+        assert_eq!(margin.percentile_rank, 75.0);
+        assert_eq!(margin.quartile, "Top quartile");
+        assert_eq!(margin.peer_median, 0.175);
+
+        // Lower leverage is better: beats 4.0x and 5.0x, ties 3.0x -> (2 + 0.5) / 4
+        let leverage = &response.metrics[1];
+        assert_eq!(leverage.percentile_rank, 62.5);
+        assert_eq!(leverage.quartile, "Second quartile");
+        assert_eq!(leverage.z_score, Some(0.39));
+
+        assert_eq!(response.average_percentile, 68.75);
+        assert_eq!(response.relative_position, "Above Median");
+        assert_eq!(response.strengths, vec!["ebitda_margin".to_string()]);
+    }
+
+    #[test]
+    fn test_benchmark_against_peers_missing_metric() {
+        let params: PeerBenchmarkParams = serde_json::from_value(serde_json::json!({
+            "metrics": [{"name": "roic", "value": 0.12}],
+            "peers": [{"name": "Peer A", "metrics": {"ebitda_margin": 0.10}}]
+        }))
+        .unwrap();
+
+        let error = PeerBenchmark::calculate(params).unwrap_err();
+        assert!(error.message.contains("No peer reports metric 'roic'"));
+    }
+}
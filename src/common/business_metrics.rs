@@ -0,0 +1,1417 @@
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+use super::calculation::{converter, field, field_parser, inflation, money, moneys, Calculation};
+use super::currency::{CurrencyConversion, CurrencyOptions, Period};
+use super::error::FinanceError;
+use super::extraction::{InputField, InputUnit};
+use super::inflation::{InflationAdjustment, InflationOptions};
+use super::money::{allocate_percentages, decimal_to_f64, MoneyRounding};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_vec, NumberUnit};
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_company_health_score
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CompanyHealthScoreParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Year-over-year revenue growth rate as a ratio (e.g., 0.09, '9%' or '900bps')")]
+    pub revenue_growth: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Service Level Agreement compliance rate as a ratio (e.g., 0.985 or '98.5%')")]
+    pub sla_compliance: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Customer satisfaction score on 0-100 scale")]
+    pub customer_satisfaction: String,
+    #[schemars(description = "Locale of number formatting (e.g., 'en', 'es', 'de-DE'), so '9,5%' is read with a decimal comma. Auto-detected when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CompanyHealthScoreResponse {
+    #[schemars(description = "Composite health score 0-100")]
+    pub overall_score: f64,
+    #[schemars(description = "Individual dimension scores before weighting")]
+    pub components: HashMap<String, f64>,
+    #[schemars(description = "Point contribution of each dimension to final score")]
+    pub weighted_contributions: HashMap<String, f64>,
+    #[schemars(description = "Risk level: LOW, MEDIUM, HIGH, or CRITICAL")]
+    pub risk_level: String,
+    #[schemars(description = "Human-readable assessment of health status")]
+    pub interpretation: String,
+    #[schemars(description = "How values written as percentages, basis points or with suffixes were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 9 for a 9% growth rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Function: calculate_revenue_quality_score
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RevenueQualityScoreParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Dollar amount of revenue growing above 15% year-over-year")]
+    pub high_growth_revenue: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Dollar amount of revenue growing 0-15% year-over-year")]
+    pub stable_revenue: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Dollar amount of revenue with negative year-over-year growth")]
+    pub declining_revenue: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Total company revenue for normalization")]
+    pub total_revenue: String,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RevenueQualityScoreResponse {
+    #[schemars(description = "Composite quality score 0.0-1.0 scale where 1.0 is perfect")]
+    pub quality_score: f64,
+    #[schemars(description = "Percentage of total_revenue by growth category, to two decimals")]
+    pub distribution: HashMap<String, f64>,
+    #[schemars(description = "Letter grade A through F based on quality score")]
+    pub grade: String,
+    #[schemars(description = "Actionable strategic guidance based on score")]
+    pub recommendation: String,
+    #[schemars(description = "Industry benchmark for comparison")]
+    pub target_score: f64,
+    #[schemars(description = "Distance from benchmark, negative means exceeding target")]
+    pub gap_to_target: f64,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// Function: calculate_hhi_and_diversification
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct HHIParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64_vec")]
+    #[schemars(description = "Revenue values for each business segment (numbers or currency-tagged amounts, e.g., 'EUR 12.5')")]
+    pub revenues: Vec<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct HHIResponse {
+    #[schemars(description = "Herfindahl-Hirschman Index value 0.0-1.0")]
+    pub hhi: f64,
+    #[schemars(description = "Inverse of HHI, where higher means more diversified")]
+    pub diversification_score: f64,
+    #[schemars(description = "Effective number of equal-sized segments")]
+    pub effective_n: f64,
+    #[schemars(description = "Risk level: LOW, MEDIUM, or HIGH")]
+    pub risk_level: String,
+    #[schemars(description = "Risk interpretation in plain language")]
+    pub assessment: String,
+    #[schemars(description = "Individual segment shares as decimals")]
+    pub market_shares: Vec<f64>,
+    #[schemars(description = "Highest individual segment share")]
+    pub largest_share: f64,
+    #[schemars(description = "Specific warnings about concentration risks")]
+    pub concentration_issues: Vec<String>,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// Function: calculate_operating_leverage
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OperatingLeverageParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Year-over-year revenue growth rate as a ratio (e.g., 0.09, '9%' or '900bps')")]
+    pub revenue_growth_rate: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Year-over-year operating cost growth rate as a ratio (e.g., 0.06, '6%' or '600bps')")]
+    pub cost_growth_rate: String,
+    #[schemars(description = "Locale of number formatting (e.g., 'en', 'es', 'de-DE'), so '9,5%' is read with a decimal comma. Auto-detected when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OperatingLeverageResponse {
+    #[schemars(description = "Operating leverage ratio (revenue growth / cost growth)")]
+    pub operating_leverage: f64,
+    #[schemars(description = "Revenue growth rate as percentage")]
+    pub revenue_growth_pct: f64,
+    #[schemars(description = "Cost growth rate as percentage")]
+    pub cost_growth_pct: f64,
+    #[schemars(description = "Margin expansion in basis points")]
+    pub margin_expansion_bps: f64,
+    #[schemars(description = "Efficiency rating: Excellent, Good, Adequate, or Poor")]
+    pub efficiency_rating: String,
+    #[schemars(description = "Plain language interpretation of the leverage")]
+    pub interpretation: String,
+    #[schemars(description = "How values written as percentages, basis points or with suffixes were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 9 for a 9% growth rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Function: calculate_portfolio_momentum
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PortfolioSegmentData {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Segment revenue in millions (number or currency-tagged amount, e.g., 'EUR 12.5')")]
+    pub revenue: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Year-over-year growth rate as a ratio (e.g., 0.20, '20%' or '2000bps')")]
+    pub growth_rate: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PortfolioMomentumParams {
+    #[schemars(description = "Dictionary of segment names to revenue and growth rate data")]
+    pub segments: HashMap<String, PortfolioSegmentData>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+    #[serde(flatten)]
+    pub inflation: InflationOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SegmentMomentumContribution {
+    #[schemars(description = "Segment revenue", with = "String")]
+    pub revenue: Decimal,
+    #[schemars(description = "Segment revenue as percentage of total; segment percentages sum to exactly 100")]
+    pub revenue_pct: f64,
+    #[schemars(description = "Segment growth rate as percentage")]
+    pub growth_rate: f64,
+    #[schemars(description = "Contribution to overall momentum as percentage")]
+    pub contribution_to_momentum: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PortfolioMomentumResponse {
+    #[schemars(description = "Portfolio momentum as decimal")]
+    pub portfolio_momentum: f64,
+    #[schemars(description = "Portfolio momentum as percentage")]
+    pub portfolio_momentum_pct: f64,
+    #[schemars(description = "Total revenue across all segments", with = "String")]
+    pub total_revenue: Decimal,
+    #[schemars(description = "Individual segment contributions to momentum")]
+    pub segment_contributions: HashMap<String, SegmentMomentumContribution>,
+    #[schemars(description = "Name of segment contributing most to momentum")]
+    pub top_contributor: String,
+    #[schemars(description = "Momentum rating: Strong, Moderate, Weak, or Declining")]
+    pub momentum_rating: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 20 for a 20% growth rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[schemars(description = "Real portfolio momentum after deflating by inflation, when adjust_for_inflation is set")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflation_adjustment: Option<InflationAdjustment>,
+}
+
+// Function: calculate_gini_coefficient
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct GiniCoefficientParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64_vec")]
+    #[schemars(description = "List of revenue values by segment in any order (numbers or currency-tagged amounts, e.g., 'EUR 12.5')")]
+    pub revenues: Vec<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct GiniCoefficientResponse {
+    #[schemars(description = "Gini coefficient (0-1 scale, higher = more concentrated)")]
+    pub gini_coefficient: f64,
+    #[schemars(description = "Diversification score (1 - Gini, higher = more diversified)")]
+    pub diversification_score: f64,
+    #[schemars(description = "Concentration level: Low, Moderate, or High")]
+    pub concentration_level: String,
+    #[schemars(description = "Largest segment share as percentage")]
+    pub largest_segment_share: f64,
+    #[schemars(description = "Smallest segment share as percentage")]
+    pub smallest_segment_share: f64,
+    #[schemars(description = "Effective number of equal-sized segments")]
+    pub effective_segments: f64,
+    #[schemars(description = "Revenue values sorted in ascending order", with = "Vec<f64>")]
+    pub sorted_revenues: Vec<Decimal>,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// Function 11: calculate_organic_growth
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OrganicGrowthParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Revenue from prior period")]
+    pub revenue_prior: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Revenue from current period")]
+    pub revenue_current: String,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+    #[serde(flatten)]
+    pub inflation: InflationOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OrganicGrowthResponse {
+    #[schemars(description = "Organic growth rate as decimal")]
+    pub organic_growth_rate: f64,
+    #[schemars(description = "Organic growth rate as percentage")]
+    pub organic_growth_pct: f64,
+    #[schemars(description = "Absolute dollar growth", with = "String")]
+    pub absolute_growth: Decimal,
+    #[schemars(description = "Prior period revenue", with = "String")]
+    pub revenue_prior: Decimal,
+    #[schemars(description = "Current period revenue", with = "String")]
+    pub revenue_current: Decimal,
+    #[schemars(description = "Growth rating: Exceptional, Strong, Moderate, Weak, or Declining")]
+    pub growth_rating: String,
+    #[schemars(description = "Annualized CAGR as percentage")]
+    pub annualized_cagr: f64,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Real growth after deflating by inflation, when adjust_for_inflation is set")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflation_adjustment: Option<InflationAdjustment>,
+}
+
+/// Growth rating shared by the nominal and inflation-adjusted growth calculations
+pub(crate) fn growth_rating(growth_rate: f64) -> &'static str {
+    if growth_rate > 0.15 {
+        "Exceptional"
+    } else if growth_rate > 0.10 {
+        "Strong"
+    } else if growth_rate > 0.05 {
+        "Moderate"
+    } else if growth_rate > 0.0 {
+        "Weak"
+    } else {
+        "Declining"
+    }
+}
+
+/// A-F grade for a 0.0-1.0 quality score, shared by revenue and earnings quality
+pub(crate) fn quality_grade(quality_score: f64) -> &'static str {
+    if quality_score >= 0.80 {
+        "A"
+    } else if quality_score >= 0.65 {
+        "B"
+    } else if quality_score >= 0.50 {
+        "C"
+    } else if quality_score >= 0.35 {
+        "D"
+    } else {
+        "F"
+    }
+}
+
+// =================== CALCULATIONS ===================
+
+/// Calculate company health score using only directly extractable metrics
+pub fn calculate_company_health_score_internal(
+    revenue_growth: f64,
+    sla_compliance: f64,
+    customer_satisfaction: f64,
+) -> Result<CompanyHealthScoreResponse, FinanceError> {
+    // Validation
+    if !(0.0..=1.0).contains(&sla_compliance) {
+        return Err(FinanceError::out_of_range(
+            "sla_compliance",
+            Some(0.0),
+            Some(1.0),
+            "SLA compliance must be between 0.0 and 1.0",
+        ));
+    }
+    if !(0.0..=100.0).contains(&customer_satisfaction) {
+        return Err(FinanceError::out_of_range(
+            "customer_satisfaction",
+            Some(0.0),
+            Some(100.0),
+            "Customer satisfaction must be between 0.0 and 100.0",
+        ));
+    }
+
+    // Convert to 0-100 scale
+    // Revenue Growth: 0% growth = 0 points, 15%+ growth = 100 points
+    let revenue_score = ((revenue_growth / 0.15) * 100.0).clamp(0.0, 100.0);
+    
+    // Service Level Agreement Compliance: Direct percentage conversion
+    let sla_score = sla_compliance * 100.0;
+    
+    // Customer Satisfaction: Already 0-100, use as-is
+    let satisfaction_score = customer_satisfaction;
+
+    let mut components = HashMap::new();
+    components.insert("revenue".to_string(), revenue_score);
+    components.insert("sla".to_string(), sla_score);
+    components.insert("satisfaction".to_string(), satisfaction_score);
+
+    // Apply weights (redistributed from original 5 metrics to 3)
+    // Original: revenue=30%, sla=25%, innovation=20%, satisfaction=15%, pipeline=10%
+    // Adjusted: revenue=40%, sla=35%, satisfaction=25% (proportionally redistributed)
+    let weights = [
+        ("revenue", 0.40),
+        ("sla", 0.35),
+        ("satisfaction", 0.25),
+    ];
+
+    let mut weighted_contributions = HashMap::new();
+    let mut overall_score = 0.0;
+
+    for (name, weight) in weights.iter() {
+        let contribution = components[*name] * weight;
+        weighted_contributions.insert(name.to_string(), contribution);
+        overall_score += contribution;
+    }
+
+    // Classify risk
+    let (risk_level, interpretation) = if overall_score >= 80.0 {
+        ("LOW", "Company health is excellent across measured dimensions.")
+    } else if overall_score >= 65.0 {
+        ("MEDIUM", "Company health is good but some areas need attention for optimal performance.")
+    } else if overall_score >= 50.0 {
+        ("HIGH", "Company faces significant challenges requiring strategic intervention.")
+    } else {
+        ("CRITICAL", "Company health is critical with severe issues across key performance indicators.")
+    };
+
+    Ok(CompanyHealthScoreResponse {
+        overall_score,
+        components,
+        weighted_contributions,
+        risk_level: risk_level.to_string(),
+        interpretation: interpretation.to_string(),
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+/// Calculate revenue quality score
+pub fn calculate_revenue_quality_score_internal(
+    high_growth_revenue: Decimal,
+    stable_revenue: Decimal,
+    declining_revenue: Decimal,
+    total_revenue: Decimal,
+) -> Result<RevenueQualityScoreResponse, FinanceError> {
+    // Validation
+    if high_growth_revenue.is_sign_negative()
+        || stable_revenue.is_sign_negative()
+        || declining_revenue.is_sign_negative()
+        || total_revenue <= Decimal::ZERO
+    {
+        return Err(FinanceError::out_of_range(
+            "total_revenue",
+            Some(0.0),
+            None,
+            "All revenue amounts must be non-negative and total must be positive",
+        ));
+    }
+
+    let sum = high_growth_revenue + stable_revenue + declining_revenue;
+    if (sum - total_revenue).abs() > Decimal::new(1, 2) * total_revenue {
+        return Err(FinanceError::sum_mismatch("Revenue categories must sum to total revenue").with_field("total_revenue"));
+    }
+
+    // Calculate distribution
+    let high_growth_pct = decimal_to_f64(high_growth_revenue / total_revenue);
+    let stable_pct = decimal_to_f64(stable_revenue / total_revenue);
+    let declining_pct = decimal_to_f64(declining_revenue / total_revenue);
+
+    // Category shares of total_revenue; when the categories reconcile exactly the breakdown is
+    // allocated to add up to exactly 100%, otherwise each share is rounded on its own
+    let categories = [high_growth_revenue, stable_revenue, declining_revenue];
+    let shares = if sum == total_revenue {
+        allocate_percentages(&categories, total_revenue, 2)
+    } else {
+        categories.iter().map(|c| (c / total_revenue * Decimal::ONE_HUNDRED).round_dp(2)).collect()
+    };
+    let mut distribution = HashMap::new();
+    distribution.insert("high_growth".to_string(), decimal_to_f64(shares[0]));
+    distribution.insert("stable".to_string(), decimal_to_f64(shares[1]));
+    distribution.insert("declining".to_string(), decimal_to_f64(shares[2]));
+
+    // Calculate quality score with weights
+    let quality_score = (high_growth_pct * 1.0) + (stable_pct * 0.7) + (declining_pct * 0.0);
+
+    // Assign grade
+    let grade = quality_grade(quality_score);
+
+    // Generate recommendation
+    let recommendation = match grade {
+        "A" => "Excellent revenue quality. Continue investing in high-growth segments and maintain momentum.",
+        "B" => "Good revenue quality with room for improvement. Focus on accelerating growth in stable segments.",
+        "C" => "Moderate revenue quality. Strategic pivot needed to increase high-growth revenue proportion.",
+        "D" => "Poor revenue quality. Urgent action required to address declining revenue and stimulate growth.",
+        _ => "Critical revenue quality issues. Immediate restructuring needed to reverse declining trends.",
+    };
+
+    let target_score = 0.75;
+    let gap_to_target = quality_score - target_score;
+
+    Ok(RevenueQualityScoreResponse {
+        quality_score,
+        distribution,
+        grade: grade.to_string(),
+        recommendation: recommendation.to_string(),
+        target_score,
+        gap_to_target,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+/// Calculate HHI and diversification checked [√]
+pub fn calculate_hhi_and_diversification_internal(revenues: Vec<Decimal>) -> Result<HHIResponse, FinanceError> {
+    if revenues.len() < 2 {
+        return Err(FinanceError::insufficient_data("Must contain at least 2 segments").with_field("revenues"));
+    }
+
+    for (i, rev) in revenues.iter().enumerate() {
+        if rev.is_sign_negative() {
+            return Err(FinanceError::out_of_range(
+                &format!("revenues[{}]", i),
+                Some(0.0),
+                None,
+                format!("Revenue at index {} cannot be negative", i),
+            ));
+        }
+    }
+
+    let total: Decimal = revenues.iter().sum();
+    if total <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("total_revenue", Some(0.0), None, "Total revenue must be positive"));
+    }
+
+    // Calculate market shares
+    let market_shares: Vec<f64> = revenues.iter().map(|r| decimal_to_f64(r / total)).collect();
+    
+    // Calculate HHI
+    let hhi: f64 = market_shares.iter().map(|s| s * s).sum();
+    
+    let diversification_score = 1.0 - hhi;
+    let effective_n = 1.0 / hhi;
+    let largest_share = market_shares.iter().cloned().fold(0.0, f64::max);
+
+    // Determine risk level
+    let risk_level = if hhi < 0.15 {
+        "LOW"
+    } else if hhi <= 0.25 {
+        "MEDIUM"
+    } else {
+        "HIGH"
+    };
+
+    let assessment = format!(
+        "Revenue concentration is {} with HHI of {:.3}. The portfolio behaves like {:.1} equal-sized segments.",
+        risk_level.to_lowercase(), hhi, effective_n
+    );
+
+    // Identify concentration issues
+    let mut concentration_issues = Vec::new();
+    if largest_share > 0.50 {
+        concentration_issues.push(format!("Single segment dominance: {:.1}% of revenue", largest_share * 100.0));
+    }
+    if hhi > 0.35 {
+        concentration_issues.push("HHI exceeds 0.35 indicating severe concentration".to_string());
+    }
+    if effective_n < 3.0 {
+        concentration_issues.push(format!("Effective segment count ({:.1}) is below recommended minimum of 3", effective_n));
+    }
+
+    Ok(HHIResponse {
+        hhi,
+        diversification_score,
+        effective_n,
+        risk_level: risk_level.to_string(),
+        assessment,
+        market_shares,
+        largest_share,
+        concentration_issues,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+/// Calculate operating leverage ratio checked [√]
+pub fn calculate_operating_leverage_internal(
+    revenue_growth_rate: f64,
+    cost_growth_rate: f64,
+) -> Result<OperatingLeverageResponse, FinanceError> {
+    // Validation
+    if cost_growth_rate == 0.0 {
+        return Err(FinanceError::invalid_input("Cost growth rate cannot be zero").with_field("cost_growth_rate"));
+    }
+
+    let operating_leverage = revenue_growth_rate / cost_growth_rate;
+    let margin_expansion_bps = (revenue_growth_rate - cost_growth_rate) * 10000.0;
+
+    let efficiency_rating = if operating_leverage >= 1.5 {
+        "Excellent"
+    } else if operating_leverage >= 1.2 {
+        "Good"
+    } else if operating_leverage >= 1.0 {
+        "Adequate"
+    } else {
+        "Poor"
+    };
+
+    let interpretation = format!("Revenue growing {:.1}x faster than costs", operating_leverage);
+
+    Ok(OperatingLeverageResponse {
+        operating_leverage: (operating_leverage * 100.0).round() / 100.0,
+        revenue_growth_pct: (revenue_growth_rate * 1000.0).round() / 10.0,
+        cost_growth_pct: (cost_growth_rate * 1000.0).round() / 10.0,
+        margin_expansion_bps: margin_expansion_bps.round(),
+        efficiency_rating: efficiency_rating.to_string(),
+        interpretation,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+/// Calculate portfolio momentum index checked [√]
+pub fn calculate_portfolio_momentum_internal(
+    segments: HashMap<String, (Decimal, f64)>,
+    rounding: MoneyRounding,
+) -> Result<PortfolioMomentumResponse, FinanceError> {
+    if segments.is_empty() {
+        return Err(FinanceError::insufficient_data("Segments cannot be empty").with_field("segments"));
+    }
+
+    let total_revenue: Decimal = segments.values().map(|(revenue, _)| revenue).sum();
+
+    if total_revenue.is_zero() {
+        return Err(FinanceError::invalid_input("Total revenue cannot be zero").with_field("segments"));
+    }
+
+    let mut names: Vec<&String> = segments.keys().collect();
+    names.sort();
+    let revenues: Vec<Decimal> = names.iter().map(|name| segments[*name].0).collect();
+    let revenue_pcts: HashMap<&String, Decimal> =
+        names.iter().copied().zip(allocate_percentages(&revenues, total_revenue, 1)).collect();
+
+    let mut momentum = 0.0;
+    let mut segment_contributions = HashMap::new();
+    let mut max_contribution = 0.0;
+    let mut top_contributor = String::new();
+
+    for name in names.iter().copied() {
+        let (revenue, growth_rate) = segments[name];
+        let weight = decimal_to_f64(revenue / total_revenue);
+        let contribution = weight * growth_rate;
+        momentum += contribution;
+
+        let contrib_pct = contribution * 100.0;
+        if contrib_pct > max_contribution {
+            max_contribution = contrib_pct;
+            top_contributor = name.clone();
+        }
+
+        segment_contributions.insert(
+            name.clone(),
+            SegmentMomentumContribution {
+                revenue: rounding.round(revenue),
+                revenue_pct: decimal_to_f64(revenue_pcts[name]),
+                growth_rate: (growth_rate * 1000.0).round() / 10.0,
+                contribution_to_momentum: (contrib_pct * 100.0).round() / 100.0,
+            },
+        );
+    }
+
+    let momentum_rating = if momentum > 0.10 {
+        "Strong"
+    } else if momentum > 0.05 {
+        "Moderate"
+    } else if momentum > 0.0 {
+        "Weak"
+    } else {
+        "Declining"
+    };
+
+    Ok(PortfolioMomentumResponse {
+        portfolio_momentum: (momentum * 10000.0).round() / 10000.0,
+        portfolio_momentum_pct: (momentum * 10000.0).round() / 100.0,
+        total_revenue: rounding.round(total_revenue),
+        segment_contributions,
+        top_contributor,
+        momentum_rating: momentum_rating.to_string(),
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+        inflation_adjustment: None,
+    })
+}
+
+/// Calculate Gini coefficient for revenue concentration checked [√]
+pub fn calculate_gini_coefficient_internal(
+    revenues: Vec<Decimal>,
+    rounding: MoneyRounding,
+) -> Result<GiniCoefficientResponse, FinanceError> {
+    if revenues.is_empty() {
+        return Err(FinanceError::insufficient_data("Revenue list cannot be empty").with_field("revenues"));
+    }
+
+    for rev in revenues.iter() {
+        if rev.is_sign_negative() {
+            return Err(FinanceError::out_of_range("revenues", Some(0.0), None, "Revenues cannot be negative"));
+        }
+    }
+
+    let total_revenue: Decimal = revenues.iter().sum();
+    if total_revenue.is_zero() {
+        return Err(FinanceError::invalid_input("Total revenue cannot be zero").with_field("revenues"));
+    }
+
+    let mut sorted_revenues = revenues;
+    sorted_revenues.sort();
+
+    let n = sorted_revenues.len() as f64;
+    let cumsum: Decimal = sorted_revenues
+        .iter()
+        .enumerate()
+        .map(|(i, rev)| Decimal::from(i + 1) * rev)
+        .sum();
+
+    let gini = decimal_to_f64(Decimal::TWO * cumsum / total_revenue) / n - (n + 1.0) / n;
+    let diversification_score = 1.0 - gini;
+
+    let largest_share = decimal_to_f64(sorted_revenues[sorted_revenues.len() - 1] / total_revenue) * 100.0;
+    let smallest_share = decimal_to_f64(sorted_revenues[0] / total_revenue) * 100.0;
+
+    let effective_segments = if gini > 0.0 {
+        1.0 / (gini + 0.0001)
+    } else {
+        n
+    };
+
+    let concentration_level = if gini < 0.25 {
+        "Low"
+    } else if gini < 0.40 {
+        "Moderate"
+    } else {
+        "High"
+    };
+
+    Ok(GiniCoefficientResponse {
+        gini_coefficient: (gini * 1000.0).round() / 1000.0,
+        diversification_score: (diversification_score * 1000.0).round() / 1000.0,
+        concentration_level: concentration_level.to_string(),
+        largest_segment_share: (largest_share * 10.0).round() / 10.0,
+        smallest_segment_share: (smallest_share * 10.0).round() / 10.0,
+        effective_segments: (effective_segments * 100.0).round() / 100.0,
+        sorted_revenues: sorted_revenues.iter().map(|r| rounding.round(*r)).collect(),
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+/// Calculate organic growth rate checked [√]
+pub fn calculate_organic_growth_internal(
+    revenue_prior: Decimal,
+    revenue_current: Decimal,
+    rounding: MoneyRounding,
+) -> Result<OrganicGrowthResponse, FinanceError> {
+    if revenue_prior <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range(
+            "revenue_prior",
+            Some(0.0),
+            None,
+            "Prior period revenue must be positive",
+        ));
+    }
+
+    let absolute_growth = revenue_current - revenue_prior;
+    let growth_rate = decimal_to_f64(absolute_growth / revenue_prior);
+
+    let growth_rating = growth_rating(growth_rate);
+
+    Ok(OrganicGrowthResponse {
+        organic_growth_rate: (growth_rate * 10000.0).round() / 10000.0,
+        organic_growth_pct: (growth_rate * 10000.0).round() / 100.0,
+        absolute_growth: rounding.round(absolute_growth),
+        revenue_prior: rounding.round(revenue_prior),
+        revenue_current: rounding.round(revenue_current),
+        growth_rating: growth_rating.to_string(),
+        annualized_cagr: (growth_rate * 10000.0).round() / 100.0,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        inflation_adjustment: None,
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Company health score from revenue growth, SLA compliance and customer satisfaction (`calculate_company_health_score`)
+pub struct CompanyHealthScore;
+
+impl Calculation for CompanyHealthScore {
+    const NAME: &'static str = "calculate_company_health_score";
+    const DESCRIPTION: &'static str = "Calculate comprehensive company health score (0-100) by combining three weighted dimensions: revenue growth (40%), Service Level Agreement compliance (35%), and customer satisfaction (25%). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from. Returns overall score, individual components, weighted contributions, risk level classification (LOW/MEDIUM/HIGH/CRITICAL), and interpretation.";
+    const PRIMARY_METRIC: &'static str = "overall_score";
+    const BAND_FIELD: Option<&'static str> = Some("risk_level");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("revenue_growth", InputUnit::Ratio, &["revenue growth", "growth in revenue", "revenue grew", "sales growth"]),
+        InputField::required("sla_compliance", InputUnit::Ratio, &["sla compliance", "service level agreement compliance", "sla attainment", "sla"]),
+        InputField::required("customer_satisfaction", InputUnit::Score, &["customer satisfaction", "csat", "satisfaction score"]),
+    ];
+
+    type Params = CompanyHealthScoreParams;
+    type Response = CompanyHealthScoreResponse;
+
+    fn calculate(params: CompanyHealthScoreParams) -> Result<CompanyHealthScoreResponse, FinanceError> {
+        let mut units = field_parser(params.locale.as_deref())?;
+        let mut result = calculate_company_health_score_internal(
+            field(&mut units, &params.revenue_growth, "revenue_growth", NumberUnit::Ratio)?,
+            field(&mut units, &params.sla_compliance, "sla_compliance", NumberUnit::Ratio)?,
+            field(&mut units, &params.customer_satisfaction, "customer_satisfaction", NumberUnit::Percentage)?,
+        )?;
+        result.parsing_notes = units.take_notes();
+        result.warnings = units.take_warnings();
+        Ok(result)
+    }
+}
+
+/// Revenue quality score from high-growth, stable and declining revenue (`calculate_revenue_quality_score`)
+pub struct RevenueQualityScore;
+
+impl Calculation for RevenueQualityScore {
+    const NAME: &'static str = "calculate_revenue_quality_score";
+    const DESCRIPTION: &'static str = "Evaluate revenue quality and sustainability by categorizing revenue into high-growth (>15% YoY), stable (0-15% YoY), and declining (<0% YoY) segments. Applies quality weights (1.0, 0.7, 0.0) to calculate composite quality score (0.0-1.0). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from. Returns quality score, distribution breakdown, letter grade (A-F), strategic recommendation, and gap to industry benchmark (0.75).";
+    const PRIMARY_METRIC: &'static str = "quality_score";
+    const BAND_FIELD: Option<&'static str> = Some("grade");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("high_growth_revenue", InputUnit::Amount, &["high-growth revenue", "high growth revenue"]),
+        InputField::required("stable_revenue", InputUnit::Amount, &["stable revenue"]),
+        InputField::required("declining_revenue", InputUnit::Amount, &["declining revenue"]),
+        InputField::required("total_revenue", InputUnit::Amount, &["total revenue"]),
+    ];
+
+    type Params = RevenueQualityScoreParams;
+    type Response = RevenueQualityScoreResponse;
+
+    fn calculate(params: RevenueQualityScoreParams) -> Result<RevenueQualityScoreResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = calculate_revenue_quality_score_internal(
+            money(&mut fx, &params.high_growth_revenue, "high_growth_revenue", Period::Current)?,
+            money(&mut fx, &params.stable_revenue, "stable_revenue", Period::Current)?,
+            money(&mut fx, &params.declining_revenue, "declining_revenue", Period::Current)?,
+            money(&mut fx, &params.total_revenue, "total_revenue", Period::Current)?,
+        )?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+/// Herfindahl-Hirschman revenue concentration and diversification (`calculate_hhi_and_diversification`)
+pub struct HHI;
+
+impl Calculation for HHI {
+    const NAME: &'static str = "calculate_hhi_and_diversification";
+    const DESCRIPTION: &'static str = "Compute Herfindahl-Hirschman Index (HHI) to measure revenue concentration risk across business segments. HHI is sum of squared market shares (0.0-1.0). Returns HHI, diversification score (1-HHI), effective number of segments (1/HHI), risk classification (LOW <0.15, MEDIUM 0.15-0.25, HIGH >0.25), market shares, largest share, and concentration warnings. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "hhi";
+    const BAND_FIELD: Option<&'static str> = Some("risk_level");
+
+    type Params = HHIParams;
+    type Response = HHIResponse;
+
+    fn calculate(params: HHIParams) -> Result<HHIResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let revenues = moneys(&mut fx, &params.revenues, "revenues", Period::Current)?;
+        let mut result = calculate_hhi_and_diversification_internal(revenues)?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+/// Operating leverage from revenue and cost growth (`calculate_operating_leverage`)
+pub struct OperatingLeverage;
+
+impl Calculation for OperatingLeverage {
+    const NAME: &'static str = "calculate_operating_leverage";
+    const DESCRIPTION: &'static str = "Calculate operating leverage ratio measuring relationship between revenue growth and cost growth to assess operational scalability. Ratio > 1.0 indicates positive operating leverage (revenue growing faster than costs). Returns operating leverage ratio, growth rates, margin expansion in basis points, efficiency rating (Excellent/Good/Adequate/Poor), and interpretation. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "operating_leverage";
+    const BAND_FIELD: Option<&'static str> = Some("efficiency_rating");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("revenue_growth_rate", InputUnit::Ratio, &["revenue growth", "growth in revenue", "revenue grew", "sales growth"]),
+        InputField::required("cost_growth_rate", InputUnit::Ratio, &["operating cost growth", "cost growth", "growth in costs", "costs grew", "opex growth"]),
+    ];
+
+    type Params = OperatingLeverageParams;
+    type Response = OperatingLeverageResponse;
+
+    fn calculate(params: OperatingLeverageParams) -> Result<OperatingLeverageResponse, FinanceError> {
+        let mut units = field_parser(params.locale.as_deref())?;
+        let mut result = calculate_operating_leverage_internal(
+            field(&mut units, &params.revenue_growth_rate, "revenue_growth_rate", NumberUnit::Ratio)?,
+            field(&mut units, &params.cost_growth_rate, "cost_growth_rate", NumberUnit::Ratio)?,
+        )?;
+        result.parsing_notes = units.take_notes();
+        result.warnings = units.take_warnings();
+        Ok(result)
+    }
+}
+
+/// Revenue-weighted portfolio momentum across segments (`calculate_portfolio_momentum`)
+pub struct PortfolioMomentum;
+
+impl Calculation for PortfolioMomentum {
+    const NAME: &'static str = "calculate_portfolio_momentum";
+    const DESCRIPTION: &'static str = "Calculate revenue-weighted portfolio momentum index measuring aggregate growth trajectory across business segments. Computes weighted average growth rate where each segment's contribution is proportional to its revenue share. Returns portfolio momentum (decimal and percentage), total revenue, per-segment contributions, top contributor, and momentum rating (Strong >10%, Moderate 5-10%, Weak 0-5%, Declining <0%). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "portfolio_momentum";
+    const BAND_FIELD: Option<&'static str> = Some("momentum_rating");
+
+    type Params = PortfolioMomentumParams;
+    type Response = PortfolioMomentumResponse;
+
+    fn calculate(params: PortfolioMomentumParams) -> Result<PortfolioMomentumResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut segments = HashMap::new();
+        for (name, data) in params.segments.iter() {
+            let revenue = money(&mut fx, &data.revenue, &format!("segments.{}.revenue", name), Period::Current)?;
+            let growth_rate = field(&mut units, &data.growth_rate, &format!("segments.{}.growth_rate", name), NumberUnit::Ratio)?;
+            segments.insert(name.clone(), (revenue, growth_rate));
+        }
+        let mut result = calculate_portfolio_momentum_internal(segments, fx.rounding())?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        result.inflation_adjustment = inflation(result.portfolio_momentum, &params.inflation)?;
+        Ok(result)
+    }
+}
+
+/// Gini coefficient of revenue distribution across segments (`calculate_gini_coefficient`)
+pub struct GiniCoefficient;
+
+impl Calculation for GiniCoefficient {
+    const NAME: &'static str = "calculate_gini_coefficient";
+    const DESCRIPTION: &'static str = "Calculate Gini coefficient measuring revenue distribution inequality across segments for concentration risk assessment. Gini ranges 0-1 (0=perfect equality, 1=complete inequality). Returns Gini coefficient, diversification score (1-Gini), concentration level (Low <0.25, Moderate 0.25-0.40, High >0.40), largest/smallest segment shares, effective number of segments, and sorted revenues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "gini_coefficient";
+    const BAND_FIELD: Option<&'static str> = Some("concentration_level");
+
+    type Params = GiniCoefficientParams;
+    type Response = GiniCoefficientResponse;
+
+    fn calculate(params: GiniCoefficientParams) -> Result<GiniCoefficientResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let revenues = moneys(&mut fx, &params.revenues, "revenues", Period::Current)?;
+        let mut result = calculate_gini_coefficient_internal(revenues, fx.rounding())?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+/// Year-over-year organic revenue growth (`calculate_organic_growth`)
+pub struct OrganicGrowth;
+
+impl Calculation for OrganicGrowth {
+    const NAME: &'static str = "calculate_organic_growth";
+    const DESCRIPTION: &'static str = "Calculate year-over-year organic revenue growth excluding acquisitions, divestitures, and other inorganic factors. This is the purest measure of underlying business performance. Returns organic growth rate (decimal and percentage), absolute dollar growth, prior/current revenue values, growth rating (Exceptional >15%, Strong 10-15%, Moderate 5-10%, Weak 0-5%, Declining <0%), and annualized CAGR. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "organic_growth_rate";
+    const BAND_FIELD: Option<&'static str> = Some("growth_rating");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("revenue_prior", InputUnit::Amount, &["prior year revenue", "prior period revenue", "previous year revenue", "last year revenue", "prior revenue"]),
+        InputField::required("revenue_current", InputUnit::Amount, &["current year revenue", "current period revenue", "current revenue", "this year revenue"]),
+    ];
+
+    type Params = OrganicGrowthParams;
+    type Response = OrganicGrowthResponse;
+
+    fn calculate(params: OrganicGrowthParams) -> Result<OrganicGrowthResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = calculate_organic_growth_internal(
+            money(&mut fx, &params.revenue_prior, "revenue_prior", Period::Prior)?,
+            money(&mut fx, &params.revenue_current, "revenue_current", Period::Current)?,
+            fx.rounding(),
+        )?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        result.inflation_adjustment = inflation(result.organic_growth_rate, &params.inflation)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_company_health_score() {
+        let params = CompanyHealthScoreParams {
+            revenue_growth: "0.09".to_string(),
+            sla_compliance: "0.985".to_string(),
+            customer_satisfaction: "89.0".to_string(),
+            locale: None,
+        };
+        
+        let response = CompanyHealthScore::calculate(params).unwrap();
+        
+        // With 3 metrics: revenue=40%, sla=35%, satisfaction=25%
+        // revenue_score = (0.09 / 0.15) * 100 = 60
+        // sla_score = 0.985 * 100 = 98.5
+        // satisfaction_score = 89
+        // Overall = 60*0.40 + 98.5*0.35 + 89*0.25 = 24 + 34.475 + 22.25 = 80.725
+        assert!(response.overall_score > 80.0 && response.overall_score < 81.0);
+        assert_eq!(response.risk_level, "LOW");
+        
+        // Verify component scores
+        assert!((response.components["revenue"] - 60.0).abs() < 0.1);
+        assert!((response.components["sla"] - 98.5).abs() < 0.1);
+        assert!((response.components["satisfaction"] - 89.0).abs() < 0.1);
+        
+        // Verify weighted contributions
+        assert!((response.weighted_contributions["revenue"] - 24.0).abs() < 0.1);
+        assert!((response.weighted_contributions["sla"] - 34.475).abs() < 0.1);
+        assert!((response.weighted_contributions["satisfaction"] - 22.25).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_company_health_score_percent_inputs() {
+        let params = CompanyHealthScoreParams {
+            revenue_growth: "9%".to_string(),
+            sla_compliance: "9850 bps".to_string(),
+            customer_satisfaction: "0.89".to_string(),
+            locale: None,
+        };
+
+        let response = CompanyHealthScore::calculate(params).unwrap();
+
+        // "9%" is the 0.09 ratio (60 points), not 9.0 capped at 100
+        assert!((response.components["revenue"] - 60.0).abs() < 1e-9);
+        assert!((response.components["sla"] - 98.5).abs() < 1e-9);
+        assert_eq!(response.parsing_notes[0], "revenue_growth: interpreted '9%' as 0.09");
+        assert_eq!(
+            response.warnings,
+            vec!["customer_satisfaction: '0.89' was read on a 0-100 scale; write '89' if it is a ratio".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_calculate_revenue_quality_score() {
+        let params = RevenueQualityScoreParams {
+            high_growth_revenue: "15.0".to_string(),
+            stable_revenue: "25.0".to_string(),
+            declining_revenue: "10.0".to_string(),
+            total_revenue: "50.0".to_string(),
+            currency: Default::default(),
+        };
+        
+        let response = RevenueQualityScore::calculate(params).unwrap();
+        
+        assert!(response.quality_score >= 0.0 && response.quality_score <= 1.0);
+        assert!(!response.grade.is_empty());
+    }
+
+    #[test]
+    fn test_revenue_quality_distribution_is_share_of_total_revenue() {
+        let params = |high: &str, stable: &str, declining: &str, total: &str| RevenueQualityScoreParams {
+            high_growth_revenue: high.to_string(),
+            stable_revenue: stable.to_string(),
+            declining_revenue: declining.to_string(),
+            total_revenue: total.to_string(),
+            currency: Default::default(),
+        };
+
+        // Categories that reconcile exactly are allocated to add up to 100
+        let response = RevenueQualityScore::calculate(params("1", "1", "1", "3")).unwrap();
+        assert_eq!(response.distribution["high_growth"], 33.34);
+        assert_eq!(response.distribution["stable"], 33.33);
+
+        // Within the 1% tolerance the shares stay relative to total_revenue
+        let response = RevenueQualityScore::calculate(params("50", "30", "20.5", "100")).unwrap();
+        assert_eq!(response.distribution["high_growth"], 50.0);
+        assert_eq!(response.distribution["declining"], 20.5);
+    }
+
+    #[test]
+    fn test_calculate_hhi_and_diversification() {
+        let params = HHIParams {
+            revenues: vec!["15.0".to_string(), "25.0".to_string(), "5.0".to_string(), "8.0".to_string()],
+            currency: Default::default(),
+        };
+        
+        let response = HHI::calculate(params).unwrap();
+        
+        assert!(response.hhi >= 0.0 && response.hhi <= 1.0);
+        assert!(response.effective_n >= 1.0);
+    }
+
+    #[test]
+    fn test_calculate_operating_leverage() {
+        let params = OperatingLeverageParams {
+            revenue_growth_rate: "0.09".to_string(),
+            cost_growth_rate: "0.06".to_string(),
+            locale: None,
+        };
+        
+        let response = OperatingLeverage::calculate(params).unwrap();
+        
+        assert_eq!(response.operating_leverage, 1.5);
+        assert_eq!(response.revenue_growth_pct, 9.0);
+        assert_eq!(response.cost_growth_pct, 6.0);
+        assert_eq!(response.margin_expansion_bps, 300.0);
+        assert_eq!(response.efficiency_rating, "Excellent");
+        assert!(!response.interpretation.is_empty());
+    }
+
+    #[test]
+    fn test_calculate_operating_leverage_zero_cost_growth() {
+        let params = OperatingLeverageParams {
+            revenue_growth_rate: "0.09".to_string(),
+            cost_growth_rate: "0.0".to_string(),
+            locale: None,
+        };
+        
+        let error = OperatingLeverage::calculate(params).unwrap_err();
+        assert!(error.message.contains("Cost growth rate cannot be zero"));
+    }
+
+    #[test]
+    fn test_calculate_portfolio_momentum() {
+        let mut segments = HashMap::new();
+        segments.insert("subscription".to_string(), PortfolioSegmentData {
+            revenue: "15.0".to_string(),
+            growth_rate: "0.20".to_string(),
+        });
+        segments.insert("enterprise".to_string(), PortfolioSegmentData {
+            revenue: "25.0".to_string(),
+            growth_rate: "0.14".to_string(),
+        });
+        segments.insert("upsell".to_string(), PortfolioSegmentData {
+            revenue: "5.0".to_string(),
+            growth_rate: "0.19".to_string(),
+        });
+        segments.insert("legacy".to_string(), PortfolioSegmentData {
+            revenue: "8.0".to_string(),
+            growth_rate: "-0.20".to_string(),
+        });
+        
+        let params = PortfolioMomentumParams {
+            segments,
+            currency: Default::default(),
+            inflation: Default::default(),
+        };
+        
+        let response = PortfolioMomentum::calculate(params).unwrap();
+        
+        assert!(response.portfolio_momentum > 0.0);
+        assert_eq!(response.total_revenue, Decimal::new(53, 0));
+        assert_eq!(response.momentum_rating, "Strong");
+        assert!(!response.top_contributor.is_empty());
+        assert_eq!(response.segment_contributions.len(), 4);
+    }
+
+    #[test]
+    fn test_calculate_portfolio_momentum_growth_rate_units() {
+        let params: PortfolioMomentumParams = serde_json::from_value(serde_json::json!({
+            "segments": {
+                "core": {"revenue": 30, "growth_rate": "20%"},
+                "new": {"revenue": 10, "growth_rate": 40}
+            }
+        }))
+        .unwrap();
+
+        let response = PortfolioMomentum::calculate(params).unwrap();
+
+        assert_eq!(response.segment_contributions["core"].growth_rate, 20.0);
+        assert!(response.parsing_notes.iter().any(|n| n.starts_with("segments.core.growth_rate")));
+        assert_eq!(response.warnings.len(), 1);
+        assert!(response.warnings[0].contains("segments.new.growth_rate"));
+    }
+
+    #[test]
+    fn test_calculate_gini_coefficient() {
+        let params = GiniCoefficientParams {
+            revenues: vec!["15.0".to_string(), "25.0".to_string(), "5.0".to_string(), "8.0".to_string()],
+            currency: Default::default(),
+        };
+        
+        let response = GiniCoefficient::calculate(params).unwrap();
+        
+        assert!(response.gini_coefficient >= 0.0);
+        assert!(response.gini_coefficient <= 1.0);
+        // Use approximate comparison for floating point
+        assert!((response.diversification_score - (1.0 - response.gini_coefficient)).abs() < 0.001);
+        assert!(response.largest_segment_share > response.smallest_segment_share);
+        assert_eq!(response.sorted_revenues.len(), 4);
+        assert!(response.sorted_revenues[0] <= response.sorted_revenues[3]);
+    }
+
+    #[test]
+    fn test_calculate_gini_coefficient_empty_list() {
+        let params = GiniCoefficientParams {
+            revenues: vec![],
+            currency: Default::default(),
+        };
+        
+        let error = GiniCoefficient::calculate(params).unwrap_err();
+        assert!(error.message.contains("Revenue list cannot be empty"));
+    }
+
+    #[test]
+    fn test_calculate_organic_growth() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "48.7".to_string(),
+            revenue_current: "53.0".to_string(),
+            currency: Default::default(),
+            inflation: Default::default(),
+        };
+        
+        let response = OrganicGrowth::calculate(params).unwrap();
+        
+        assert!(response.organic_growth_rate > 0.0);
+        assert_eq!(response.revenue_prior, Decimal::new(487, 1));
+        assert_eq!(response.revenue_current, Decimal::new(53, 0));
+        assert_eq!(response.absolute_growth, Decimal::new(43, 1));
+        // Growth rate is 8.83%, which falls in Moderate range (5-10%)
+        assert_eq!(response.growth_rating, "Moderate");
+        assert_eq!(response.organic_growth_pct, response.annualized_cagr);
+    }
+
+    #[test]
+    fn test_calculate_organic_growth_negative_prior() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "0".to_string(),
+            revenue_current: "53.0".to_string(),
+            currency: Default::default(),
+            inflation: Default::default(),
+        };
+        
+        let error = OrganicGrowth::calculate(params).unwrap_err();
+        assert!(error.message.contains("Prior period revenue must be positive"));
+    }
+
+    #[test]
+    fn test_calculate_organic_growth_declining() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "53.0".to_string(),
+            revenue_current: "48.0".to_string(),
+            currency: Default::default(),
+            inflation: Default::default(),
+        };
+        
+        let response = OrganicGrowth::calculate(params).unwrap();
+        
+        assert!(response.organic_growth_rate < 0.0);
+        assert_eq!(response.growth_rating, "Declining");
+    }
+
+    #[test]
+    fn test_mixed_currencies_without_rates_rejected() {
+        let params = HHIParams {
+            revenues: vec!["$15.0".to_string(), "EUR 25.0".to_string()],
+            currency: Default::default(),
+        };
+
+        let error = HHI::calculate(params).unwrap_err();
+        assert!(error.message.contains("Amounts mix USD and EUR"));
+    }
+
+    #[test]
+    fn test_amount_parse_error_keeps_the_reason() {
+        let params = HHIParams { revenues: vec!["$15%".to_string()], currency: Default::default() };
+
+        let error = HHI::calculate(params).unwrap_err();
+        assert!(error.message.contains("Cannot parse '$15%' as an amount"));
+        assert!(error.message.contains("is a percentage, not an amount"));
+    }
+
+    #[test]
+    fn test_tagged_and_untagged_amounts_need_reporting_currency() {
+        for revenues in [["$15.0", "25.0"], ["25.0", "$15.0"]] {
+            let params = HHIParams { revenues: revenues.map(String::from).to_vec(), currency: Default::default() };
+            let error = HHI::calculate(params).unwrap_err();
+            assert!(error.message.contains("mix currency-tagged and untagged"), "{}", error.message);
+        }
+
+        let params = HHIParams {
+            revenues: vec!["$15.0".to_string(), "25.0".to_string()],
+            currency: CurrencyOptions { reporting_currency: Some("USD".to_string()), ..Default::default() },
+        };
+        assert!(HHI::calculate(params).is_ok());
+    }
+
+    #[test]
+    fn test_hhi_converts_to_reporting_currency() {
+        let params = HHIParams {
+            revenues: vec!["$50".to_string(), "€40".to_string(), "10".to_string()],
+            currency: CurrencyOptions {
+                reporting_currency: Some("usd".to_string()),
+                fx_rates: Some(HashMap::from([("EUR".to_string(), 1.25)])),
+                ..Default::default()
+            },
+        };
+
+        let response = HHI::calculate(params).unwrap();
+
+        // 50 + 40 x 1.25 + 10 = 110 USD; USD and converted EUR lines both hold 50
+        assert!((response.largest_share - 50.0 / 110.0).abs() < 1e-9);
+        let conversion = response.currency_conversion.unwrap();
+        assert_eq!(conversion.reporting_currency, "USD");
+        assert_eq!(conversion.rates_applied["EUR"], 1.25);
+        assert_eq!(conversion.notes.len(), 1);
+    }
+
+    #[test]
+    fn test_organic_growth_constant_currency() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "EUR 100".to_string(),
+            revenue_current: "EUR 110".to_string(),
+            currency: CurrencyOptions {
+                reporting_currency: Some("USD".to_string()),
+                fx_rates: Some(HashMap::from([("EUR".to_string(), 1.20)])),
+                prior_fx_rates: Some(HashMap::from([("EUR".to_string(), 1.00)])),
+                constant_currency: Some(true),
+                ..Default::default()
+            },
+            inflation: Default::default(),
+        };
+
+        let response = OrganicGrowth::calculate(params).unwrap();
+
+        // Both periods at the prior rate: FX tailwind excluded, 10% constant-currency growth
+        assert!((response.organic_growth_pct - 10.0).abs() < 0.01);
+        let conversion = response.currency_conversion.unwrap();
+        assert!(conversion.constant_currency);
+        assert!(conversion.rates_applied.is_empty());
+        assert_eq!(conversion.prior_rates_applied["EUR"], 1.00);
+    }
+
+    #[test]
+    fn test_organic_growth_inflation_adjusted() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "100".to_string(),
+            revenue_current: "130".to_string(),
+            currency: Default::default(),
+            inflation: InflationOptions {
+                adjust_for_inflation: Some(true),
+                inflation_rate: Some("0.25".to_string()),
+                ..Default::default()
+            },
+        };
+
+        let response = OrganicGrowth::calculate(params).unwrap();
+
+        // 30% nominal growth with 25% inflation: 1.30 / 1.25 - 1 = 4% real
+        assert_eq!(response.organic_growth_pct, 30.0);
+        let adjustment = response.inflation_adjustment.unwrap();
+        assert_eq!(adjustment.real_growth_pct, 4.0);
+        assert_eq!(adjustment.index_source, "inflation_rate");
+    }
+
+    #[test]
+    fn test_decimal_money_sums_and_rounding_options() {
+        let params: PortfolioMomentumParams = serde_json::from_value(serde_json::json!({
+            "segments": {
+                "a": {"revenue": "0.10", "growth_rate": 0.1},
+                "b": {"revenue": 0.2, "growth_rate": 0.1},
+                "c": {"revenue": "0.10", "growth_rate": 0.1}
+            }
+        }))
+        .unwrap();
+
+        let response = PortfolioMomentum::calculate(params).unwrap();
+
+        // 0.1 + 0.2 + 0.1 is exactly 0.4, serialized as an exact decimal string, and the shares add up to exactly 100%
+        assert_eq!(serde_json::to_value(&response).unwrap()["total_revenue"], serde_json::json!("0.40"));
+        assert_eq!(response.total_revenue, Decimal::new(4, 1));
+        let total_pct: f64 = response.segment_contributions.values().map(|s| s.revenue_pct).sum();
+        assert_eq!(total_pct, 100.0);
+
+        let params: OrganicGrowthParams = serde_json::from_value(serde_json::json!({
+            "revenue_prior": "100.125",
+            "revenue_current": "110.135",
+            "rounding_mode": "half_up"
+        }))
+        .unwrap();
+        let response = OrganicGrowth::calculate(params).unwrap();
+        assert_eq!(response.revenue_prior, Decimal::new(10013, 2));
+        assert_eq!(response.absolute_growth, Decimal::new(1001, 2));
+
+        let params: OrganicGrowthParams = serde_json::from_value(serde_json::json!({
+            "revenue_prior": "100.125",
+            "revenue_current": "110.135"
+        }))
+        .unwrap();
+        let response = OrganicGrowth::calculate(params).unwrap();
+        // Half-even by default: 100.125 -> 100.12
+        assert_eq!(response.revenue_prior, Decimal::new(10012, 2));
+    }
+
+    #[test]
+    fn test_locale_hint_in_request() {
+        let params: OrganicGrowthParams = serde_json::from_value(serde_json::json!({
+            "revenue_prior": "1.000",
+            "revenue_current": "EUR 1.100,50",
+            "reporting_currency": "EUR",
+            "locale": "de"
+        }))
+        .unwrap();
+
+        let response = OrganicGrowth::calculate(params).unwrap();
+
+        // "1.000" is one thousand under the German hint, not 1.0
+        assert_eq!(response.revenue_prior, Decimal::new(1000, 0));
+        assert_eq!(response.revenue_current, Decimal::new(110050, 2));
+        assert!(response.parsing_notes.is_empty());
+
+        let params: OrganicGrowthParams = serde_json::from_value(serde_json::json!({
+            "revenue_prior": "100",
+            "revenue_current": "110",
+            "locale": "klingon"
+        }))
+        .unwrap();
+        assert!(OrganicGrowth::calculate(params).is_err());
+    }
+
+    #[test]
+    fn test_parsing_notes_in_response() {
+        let params = OrganicGrowthParams {
+            revenue_prior: "$1.1B".to_string(),
+            revenue_current: "1,210 mm".to_string(),
+            currency: CurrencyOptions { reporting_currency: Some("USD".to_string()), ..Default::default() },
+            inflation: Default::default(),
+        };
+
+        let response = OrganicGrowth::calculate(params).unwrap();
+
+        assert_eq!(response.revenue_prior, Decimal::new(1_100_000_000, 0));
+        assert!((response.organic_growth_pct - 10.0).abs() < 1e-9);
+        assert_eq!(
+            response.parsing_notes,
+            vec![
+                "revenue_prior: interpreted '$1.1B' as 1100000000".to_string(),
+                "revenue_current: interpreted '1,210 mm' as 1210000000".to_string(),
+            ]
+        );
+    }
+}
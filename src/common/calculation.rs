@@ -0,0 +1,231 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use rust_decimal::Decimal;
+
+use super::benchmark::PeerBenchmark;
+use super::business_metrics::{
+    CompanyHealthScore, GiniCoefficient, HHI, OperatingLeverage, OrganicGrowth, PortfolioMomentum,
+    RevenueQualityScore,
+};
+use super::cap_table::CapTable;
+use super::covenant::CovenantCompliance;
+use super::credit_rating::CreditRating;
+use super::currency::{CurrencyConverter, CurrencyOptions, Period};
+use super::customer::CustomerConcentration;
+use super::dividend::{DividendDiscount, DividendSustainability};
+use super::earnings_quality::EarningsQuality;
+use super::error::{ErrorCode, FinanceError};
+use super::extraction::InputField;
+use super::inflation::{apply_inflation_adjustment, InflationAdjustedGrowth, InflationAdjustment, InflationOptions};
+use super::lbo::Lbo;
+use super::merger::AccretionDilution;
+use super::parsing::{FieldParser, NumberUnit};
+use super::unit_economics::UnitEconomics;
+use super::variance::BudgetVariance;
+
+// =================== CALCULATION TRAIT ===================
+
+/// A calculation with typed parameters in and a typed response out.
+///
+/// Implementors listed in [`for_each_calculation`] become MCP tools, are accepted as
+/// `function_name` by [`run_calculation_internal`] (and so by simulation and scenario
+/// analysis), and get the same request metrics, error mapping and serialization.
+pub trait Calculation: 'static {
+    /// Tool name, also accepted as `function_name` by the meta tools
+    const NAME: &'static str;
+    /// Tool description shown to MCP clients
+    const DESCRIPTION: &'static str;
+    /// Headline numeric output, tracked by simulation and scenario analysis
+    const PRIMARY_METRIC: &'static str;
+    /// Risk or rating band field of the response, when it has one
+    const BAND_FIELD: Option<&'static str>;
+    /// Arguments the pipeline tool can read from document text; empty when they must be given
+    const INPUT_FIELDS: &'static [InputField] = &[];
+
+    type Params: DeserializeOwned + schemars::JsonSchema + Send + 'static;
+    type Response: Serialize + schemars::JsonSchema + 'static;
+
+    /// Parse the parameters and run the calculation
+    fn calculate(params: Self::Params) -> Result<Self::Response, FinanceError>;
+}
+
+/// Receives every calculation type in turn, see [`for_each_calculation`]
+pub trait CalculationVisitor {
+    fn visit<C: Calculation>(&mut self);
+}
+
+/// Visit every calculation. A new calculation needs its [`Calculation`] impl and a line here.
+pub fn for_each_calculation<V: CalculationVisitor>(visitor: &mut V) {
+    visitor.visit::<CompanyHealthScore>();
+    visitor.visit::<RevenueQualityScore>();
+    visitor.visit::<HHI>();
+    visitor.visit::<OperatingLeverage>();
+    visitor.visit::<PortfolioMomentum>();
+    visitor.visit::<GiniCoefficient>();
+    visitor.visit::<OrganicGrowth>();
+    visitor.visit::<BudgetVariance>();
+    visitor.visit::<InflationAdjustedGrowth>();
+    visitor.visit::<Lbo>();
+    visitor.visit::<AccretionDilution>();
+    visitor.visit::<CapTable>();
+    visitor.visit::<CovenantCompliance>();
+    visitor.visit::<PeerBenchmark>();
+    visitor.visit::<CustomerConcentration>();
+    visitor.visit::<CreditRating>();
+    visitor.visit::<UnitEconomics>();
+    visitor.visit::<EarningsQuality>();
+    visitor.visit::<DividendDiscount>();
+    visitor.visit::<DividendSustainability>();
+}
+
+// =================== PARSING HELPERS ===================
+
+pub(crate) fn converter(options: &CurrencyOptions) -> Result<CurrencyConverter, FinanceError> {
+    CurrencyConverter::from_options(options)
+        .map_err(|e| FinanceError::invalid_input(format!("Invalid currency options: {}", e)))
+}
+
+/// The converter reads the FX_RATES_FILE on first use, so a broken file surfaces while amounts
+/// are parsed; report it as UPSTREAM_UNAVAILABLE rather than as invalid input
+pub(crate) fn fx_failure(fx: &CurrencyConverter, error: FinanceError) -> FinanceError {
+    if fx.rates_file_failed() {
+        FinanceError { code: ErrorCode::UpstreamUnavailable, ..error }
+    } else {
+        error
+    }
+}
+
+pub(crate) fn field_parser(locale: Option<&str>) -> Result<FieldParser, FinanceError> {
+    FieldParser::new(locale).map_err(|e| FinanceError::invalid_input(format!("Invalid locale: {}", e)))
+}
+
+pub(crate) fn field(units: &mut FieldParser, value: &str, name: &str, unit: NumberUnit) -> Result<f64, FinanceError> {
+    units.parse(value, name, unit).map_err(|e| FinanceError::invalid_field(name, e))
+}
+
+/// Optional ratio field, `default` when omitted
+pub(crate) fn rate_or(units: &mut FieldParser, value: Option<&str>, name: &str, default: f64) -> Result<f64, FinanceError> {
+    value.map_or(Ok(default), |value| field(units, value, name, NumberUnit::Ratio))
+}
+
+pub(crate) fn money(fx: &mut CurrencyConverter, value: &str, name: &str, period: Period) -> Result<Decimal, FinanceError> {
+    fx.parse_money(value, name, period).map_err(|e| fx_failure(fx, FinanceError::invalid_field(name, e)))
+}
+
+pub(crate) fn moneys(fx: &mut CurrencyConverter, values: &[String], name: &str, period: Period) -> Result<Vec<Decimal>, FinanceError> {
+    fx.parse_moneys(values, name, period).map_err(|e| fx_failure(fx, FinanceError::invalid_field(name, e)))
+}
+
+pub(crate) fn inflation(nominal_growth: f64, options: &InflationOptions) -> Result<Option<InflationAdjustment>, FinanceError> {
+    apply_inflation_adjustment(nominal_growth, options).map_err(|e| e.context("Invalid inflation options"))
+}
+
+// =================== CALCULATION DISPATCH ===================
+
+/// Names of every calculation, in [`for_each_calculation`] order
+pub fn calculation_names() -> Vec<&'static str> {
+    struct Names(Vec<&'static str>);
+    impl CalculationVisitor for Names {
+        fn visit<C: Calculation>(&mut self) {
+            self.0.push(C::NAME);
+        }
+    }
+
+    let mut names = Names(Vec::new());
+    for_each_calculation(&mut names);
+    names.0
+}
+
+/// Headline numeric output and rating band field for each calculation
+pub fn calculation_output_fields(function_name: &str) -> Result<(&'static str, Option<&'static str>), String> {
+    struct Fields<'a>(&'a str, Option<(&'static str, Option<&'static str>)>);
+    impl CalculationVisitor for Fields<'_> {
+        fn visit<C: Calculation>(&mut self) {
+            if C::NAME == self.0 {
+                self.1 = Some((C::PRIMARY_METRIC, C::BAND_FIELD));
+            }
+        }
+    }
+
+    let mut fields = Fields(function_name, None);
+    for_each_calculation(&mut fields);
+    fields.1.ok_or_else(|| unknown_calculation_error(function_name))
+}
+
+/// Arguments of a calculation that can be read from document text
+pub fn calculation_input_fields(function_name: &str) -> Result<&'static [InputField], String> {
+    struct Fields<'a>(&'a str, Option<&'static [InputField]>);
+    impl CalculationVisitor for Fields<'_> {
+        fn visit<C: Calculation>(&mut self) {
+            if C::NAME == self.0 {
+                self.1 = Some(C::INPUT_FIELDS);
+            }
+        }
+    }
+
+    let mut fields = Fields(function_name, None);
+    for_each_calculation(&mut fields);
+    fields.1.ok_or_else(|| unknown_calculation_error(function_name))
+}
+
+fn unknown_calculation_error(function_name: &str) -> String {
+    format!(
+        "Unknown calculation: '{}'. Valid calculations are: {}",
+        function_name,
+        calculation_names().join(", ")
+    )
+}
+
+/// Run one calculation from its JSON tool arguments and return the JSON response
+pub fn run_calculation_as<C: Calculation>(arguments: serde_json::Value) -> Result<serde_json::Value, FinanceError> {
+    let params: C::Params = serde_json::from_value(arguments)
+        .map_err(|e| FinanceError::invalid_input(format!("Invalid arguments: {}", e)))?;
+    let result = C::calculate(params)?;
+    serde_json::to_value(result).map_err(|e| FinanceError::internal(format!("Serialization error: {}", e)))
+}
+
+/// Run any calculation by name from its JSON tool arguments and return the JSON response
+pub fn run_calculation_internal(
+    function_name: &str,
+    arguments: serde_json::Value,
+) -> Result<serde_json::Value, FinanceError> {
+    struct Run<'a> {
+        function_name: &'a str,
+        arguments: Option<serde_json::Value>,
+        result: Option<Result<serde_json::Value, FinanceError>>,
+    }
+    impl CalculationVisitor for Run<'_> {
+        fn visit<C: Calculation>(&mut self) {
+            if C::NAME == self.function_name
+                && let Some(arguments) = self.arguments.take()
+            {
+                self.result = Some(run_calculation_as::<C>(arguments));
+            }
+        }
+    }
+
+    let mut run = Run { function_name, arguments: Some(arguments), result: None };
+    for_each_calculation(&mut run);
+    run.result.unwrap_or_else(|| {
+        Err(FinanceError::invalid_input(unknown_calculation_error(function_name)).with_field("function_name"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_calculation_by_name() {
+        let result = run_calculation_internal(
+            "calculate_organic_growth",
+            serde_json::json!({ "revenue_prior": "100", "revenue_current": "112" }),
+        )
+        .unwrap();
+        assert_eq!(result["organic_growth_pct"], serde_json::json!(12.0));
+
+        let error = run_calculation_internal("calculate_nothing", serde_json::json!({})).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("function_name"));
+        assert!(error.message.contains(&calculation_names().join(", ")));
+    }
+}
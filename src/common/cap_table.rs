@@ -0,0 +1,989 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::calculation::{converter, field, field_parser, fx_failure, money, rate_or, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{decimal_from_f64, decimal_to_f64, round_ratio, scale_money, MoneyRounding};
+use super::parsing::{deserialize_bool_flexible_opt, deserialize_flexible_f64, deserialize_flexible_f64_opt, FieldParser, NumberUnit};
+
+const OPTION_POOL_HOLDER: &str = "Option pool";
+const OPTION_POOL_CLASS: &str = "Options (unallocated)";
+const MAX_PRICE_ITERATIONS: usize = 200;
+/// Relative change in the round price at which the fixed-point iteration stops
+const PRICE_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 12);
+/// Minimum gain for a series to convert to common, so rounding noise never flips a decision
+const CONVERSION_GAIN_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 6);
+/// Relative tolerance (1%) for investor amounts against the round investment
+const AMOUNT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_cap_table
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CapTableHolder {
+    #[schemars(description = "Shareholder name (e.g., 'Founder A', 'Angel investors')")]
+    pub name: String,
+    #[schemars(description = "Shares held (fully diluted, including granted options)")]
+    pub shares: f64,
+    #[schemars(description = "Share class label (default: 'Common'); existing holdings are treated as common in the waterfall")]
+    #[serde(default)]
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RoundInvestor {
+    #[schemars(description = "Investor name")]
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Amount invested in the round")]
+    pub amount: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ConvertibleInstrument {
+    #[schemars(description = "Holder of the SAFE or note")]
+    pub name: String,
+    #[schemars(description = "Instrument type: safe or note")]
+    pub instrument_type: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Amount invested")]
+    pub principal: String,
+    #[schemars(description = "Valuation cap, applied to the pre-round fully diluted share count")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub valuation_cap: Option<String>,
+    #[schemars(description = "Discount to the round price as a ratio (e.g., 0.20 or '20%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub discount: Option<String>,
+    #[schemars(description = "Simple annual interest rate as a ratio (notes only, e.g., 0.06 or '6%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub interest_rate: Option<String>,
+    #[schemars(description = "Years of accrued interest at conversion (notes only)")]
+    #[serde(default)]
+    pub years_outstanding: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct FundingRound {
+    #[schemars(description = "Round name, also the name of the preferred series (e.g., 'Seed', 'Series A')")]
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Pre-money valuation, including the option pool top-up and converting SAFEs/notes")]
+    pub pre_money_valuation: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "New money invested in the round")]
+    pub investment: String,
+    #[schemars(description = "Investors and amounts, summing to the investment (default: one holder named '<round> investors')")]
+    #[serde(default)]
+    pub investors: Option<Vec<RoundInvestor>>,
+    #[schemars(description = "Unallocated option pool target as a ratio of post-money fully diluted shares (e.g., 0.10 or '10%'), topped up pre-money")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub option_pool_target_pct: Option<String>,
+    #[schemars(description = "SAFEs and convertible notes converting into this round")]
+    #[serde(default)]
+    pub convertibles: Option<Vec<ConvertibleInstrument>>,
+    #[schemars(description = "Liquidation preference as a multiple of the amount invested (default: 1.0)")]
+    #[serde(default)]
+    pub liquidation_preference: Option<f64>,
+    #[schemars(description = "Whether the series participates alongside common after its preference (default: false)")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt")]
+    pub participating: Option<bool>,
+    #[schemars(description = "Cap on total participating returns as a multiple of the amount invested")]
+    #[serde(default)]
+    pub participation_cap: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CapTableParams {
+    #[schemars(description = "Existing cap table before the first round")]
+    pub shareholders: Vec<CapTableHolder>,
+    #[schemars(description = "Existing unallocated option pool shares (default: 0)")]
+    #[serde(default)]
+    pub option_pool_shares: Option<f64>,
+    #[schemars(description = "Funding rounds in chronological order")]
+    pub rounds: Vec<FundingRound>,
+    #[schemars(description = "Exit equity value for the liquidation waterfall (omit to skip the waterfall)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub exit_value: Option<String>,
+    #[schemars(description = "Preference seniority: standard (latest round paid first) or pari_passu (default: standard)")]
+    #[serde(default)]
+    pub preference_stacking: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OwnershipEntry {
+    #[schemars(description = "Holder name")]
+    pub holder: String,
+    #[schemars(description = "Share class or series")]
+    pub class: String,
+    #[schemars(description = "Shares held")]
+    pub shares: f64,
+    #[schemars(description = "Fully diluted ownership as percentage")]
+    pub ownership_pct: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ConversionResult {
+    #[schemars(description = "Holder of the SAFE or note")]
+    pub name: String,
+    #[schemars(description = "Instrument type: safe or note")]
+    pub instrument_type: String,
+    #[schemars(description = "Principal plus accrued interest converted", with = "String")]
+    pub amount_converted: Decimal,
+    #[schemars(description = "Price per share paid on conversion", with = "String")]
+    pub conversion_price: Decimal,
+    #[schemars(description = "Shares issued on conversion")]
+    pub shares_issued: f64,
+    #[schemars(description = "Which term set the price: cap, discount, or round price")]
+    pub basis: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RoundResult {
+    #[schemars(description = "Round name")]
+    pub name: String,
+    #[schemars(description = "Pre-money valuation", with = "String")]
+    pub pre_money_valuation: Decimal,
+    #[schemars(description = "New money invested", with = "String")]
+    pub investment: Decimal,
+    #[schemars(description = "Post-money valuation (pre-money + investment)", with = "String")]
+    pub post_money_valuation: Decimal,
+    #[schemars(description = "Round price per share", with = "String")]
+    pub price_per_share: Decimal,
+    #[schemars(description = "Shares issued to new money investors")]
+    pub new_investor_shares: f64,
+    #[schemars(description = "Shares added to the option pool before the round")]
+    pub option_pool_top_up: f64,
+    #[schemars(description = "SAFEs and notes converted in the round")]
+    pub conversions: Vec<ConversionResult>,
+    #[schemars(description = "Fully diluted shares after the round")]
+    pub fully_diluted_shares: f64,
+    #[schemars(description = "Fully diluted ownership after the round, largest first")]
+    pub ownership: Vec<OwnershipEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct WaterfallSeries {
+    #[schemars(description = "Preferred series (round name)")]
+    pub series: String,
+    #[schemars(description = "Amount invested in the series, including converted SAFEs/notes", with = "String")]
+    pub invested: Decimal,
+    #[schemars(description = "Preference type: non-participating, participating, or capped participating")]
+    pub preference_type: String,
+    #[schemars(description = "Outcome chosen for the holders: Preference, Participating, or Converted")]
+    pub decision: String,
+    #[schemars(description = "Liquidation preference paid", with = "String")]
+    pub preference_paid: Decimal,
+    #[schemars(description = "Share of the residual received alongside common", with = "String")]
+    pub participation_paid: Decimal,
+    #[schemars(description = "Total proceeds to the series", with = "String")]
+    pub total_payout: Decimal,
+    #[schemars(description = "Total proceeds as a multiple of the amount invested")]
+    pub multiple_of_invested: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct HolderPayout {
+    #[schemars(description = "Holder name")]
+    pub holder: String,
+    #[schemars(description = "Total exit proceeds across all classes held", with = "String")]
+    pub payout: Decimal,
+    #[schemars(description = "Share of the exit value as percentage")]
+    pub pct_of_exit: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct LiquidationWaterfall {
+    #[schemars(description = "Exit equity value distributed", with = "String")]
+    pub exit_value: Decimal,
+    #[schemars(description = "Preference seniority applied: standard or pari_passu")]
+    pub stacking: String,
+    #[schemars(description = "Payout per preferred series")]
+    pub series: Vec<WaterfallSeries>,
+    #[schemars(description = "Total proceeds to existing (common) holders", with = "String")]
+    pub common_payout: Decimal,
+    #[schemars(description = "Proceeds per holder, largest first; the unallocated pool receives nothing")]
+    pub holder_payouts: Vec<HolderPayout>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CapTableResponse {
+    #[schemars(description = "Round-by-round pricing, conversions and ownership")]
+    pub rounds: Vec<RoundResult>,
+    #[schemars(description = "Fully diluted ownership after the last round")]
+    pub final_ownership: Vec<OwnershipEntry>,
+    #[schemars(description = "Fully diluted shares after the last round")]
+    pub fully_diluted_shares: f64,
+    #[schemars(description = "Combined ownership of the original shareholders after the last round as percentage")]
+    pub existing_holders_ownership_pct: f64,
+    #[schemars(description = "Liquidation waterfall at exit_value, when given")]
+    pub waterfall: Option<LiquidationWaterfall>,
+    #[schemars(description = "Plain language summary of dilution and exit proceeds")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 20 for a 20% discount)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn too_large(field: &str) -> FinanceError {
+    FinanceError::invalid_field(field, "amount exceeds the supported magnitude of 1e18")
+}
+
+/// A block of shares held by one holder in one class
+struct Holding {
+    holder: String,
+    class: String,
+    shares: Decimal,
+    /// Index of the preferred series, None for common and the unallocated pool
+    series: Option<usize>,
+    /// Amount paid for the shares, the base of the liquidation preference
+    invested: Decimal,
+    original: bool,
+}
+
+struct SeriesTerms {
+    name: String,
+    multiple: Decimal,
+    participating: bool,
+    cap: Option<Decimal>,
+}
+
+/// Preference and participation proceeds per holding
+type Payouts = (Vec<Decimal>, Vec<Decimal>);
+
+struct Conversion {
+    name: String,
+    instrument_type: String,
+    amount: Decimal,
+    cap_price: Option<Decimal>,
+    discount: Decimal,
+}
+
+fn fully_diluted(holdings: &[Holding]) -> Decimal {
+    holdings.iter().map(|h| h.shares).sum()
+}
+
+fn pool_index(holdings: &[Holding]) -> Option<usize> {
+    holdings.iter().position(|h| h.holder == OPTION_POOL_HOLDER && h.class == OPTION_POOL_CLASS)
+}
+
+/// Ownership table aggregated by holder and class, largest first
+fn ownership(holdings: &[Holding]) -> Vec<OwnershipEntry> {
+    let total = decimal_to_f64(fully_diluted(holdings));
+    let mut blocks: Vec<(&str, &str, Decimal)> = Vec::new();
+    for h in holdings.iter().filter(|h| h.shares > Decimal::ZERO) {
+        match blocks.iter_mut().find(|b| b.0 == h.holder && b.1 == h.class) {
+            Some(block) => block.2 += h.shares,
+            None => blocks.push((&h.holder, &h.class, h.shares)),
+        }
+    }
+    blocks.sort_by_key(|b| std::cmp::Reverse(b.2));
+    blocks
+        .into_iter()
+        .map(|(holder, class, shares)| OwnershipEntry {
+            holder: holder.to_string(),
+            class: class.to_string(),
+            shares: round_ratio(decimal_to_f64(shares), 2),
+            ownership_pct: round_ratio(decimal_to_f64(shares) / total * 100.0, 2),
+        })
+        .collect()
+}
+
+fn conversion_price(conversion: &Conversion, round_price: Decimal) -> (Decimal, &'static str) {
+    let discounted = round_price * (Decimal::ONE - conversion.discount);
+    match conversion.cap_price {
+        Some(cap_price) if cap_price < discounted => (cap_price, "cap"),
+        _ if conversion.discount > Decimal::ZERO => (discounted, "discount"),
+        _ => (round_price, "round price"),
+    }
+}
+
+/// Solve the round price when pool top-up and conversions are both part of the pre-money.
+///
+/// price = pre_money / (existing + top_up + conversion shares), where the top-up sizes the pool to
+/// its target share of post-money shares and conversion shares depend on the price. The mapping is
+/// a contraction for realistic terms, so fixed-point iteration converges quickly.
+fn solve_round_price(
+    round: &str,
+    pre_money: Decimal,
+    investment: Decimal,
+    existing_shares: Decimal,
+    existing_pool: Decimal,
+    pool_target: Option<Decimal>,
+    conversions: &[Conversion],
+) -> Result<(Decimal, Decimal, Vec<Decimal>), FinanceError> {
+    let field = format!("rounds.{}", round);
+    let no_price = || {
+        FinanceError::invalid_input(format!("Round '{}': no positive share price satisfies the terms", round)).with_field(&field)
+    };
+    let mut price = pre_money.checked_div(existing_shares).ok_or_else(no_price)?;
+    for _ in 0..MAX_PRICE_ITERATIONS {
+        let conversion_shares = conversions
+            .iter()
+            .map(|c| c.amount.checked_div(conversion_price(c, price).0))
+            .collect::<Option<Vec<Decimal>>>()
+            .ok_or_else(no_price)?;
+        let converted: Decimal = conversion_shares.iter().sum();
+        let investor_shares = investment.checked_div(price).ok_or_else(no_price)?;
+        let top_up = match pool_target {
+            Some(target) => {
+                let pre_top_up = existing_shares + converted + investor_shares;
+                // pool_after = target x (pre_top_up + top_up) with pool_after = existing_pool + top_up
+                (target * pre_top_up - existing_pool)
+                    .checked_div(Decimal::ONE - target)
+                    .ok_or_else(no_price)?
+                    .max(Decimal::ZERO)
+            }
+            None => Decimal::ZERO,
+        };
+
+        let next = pre_money.checked_div(existing_shares + top_up + converted).ok_or_else(no_price)?;
+        if next <= Decimal::ZERO {
+            return Err(no_price());
+        }
+        if (next - price).abs() <= PRICE_TOLERANCE * price {
+            return Ok((next, top_up, conversion_shares));
+        }
+        price = next;
+    }
+    Err(FinanceError::invalid_input(format!(
+        "Round '{}': share price did not converge; check the option pool target and discounts",
+        round
+    ))
+    .with_field(&field))
+}
+
+fn parse_convertible(
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+    round: &str,
+    instrument: &ConvertibleInstrument,
+    existing_shares: Decimal,
+) -> Result<Conversion, FinanceError> {
+    let label = format!("Round '{}' convertible '{}'", round, instrument.name);
+    let path = |field: &str| format!("rounds.{}.convertibles.{}.{}", round, instrument.name, field);
+    let out_of_range = |field: &str, min: f64, max: Option<f64>, problem: &str| {
+        FinanceError::out_of_range(&path(field), Some(min), max, format!("{}: {} {}", label, field, problem))
+    };
+    let decimal = |value: f64, field: &str| decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(&path(field), e));
+    let instrument_type = instrument.instrument_type.trim().to_lowercase();
+    if instrument_type != "safe" && instrument_type != "note" {
+        return Err(FinanceError::invalid_field(&path("instrument_type"), format!("{}: must be safe or note", label)));
+    }
+    let interest_rate = rate_or(units, instrument.interest_rate.as_deref(), &path("interest_rate"), 0.0)?;
+    if instrument_type == "safe" && interest_rate != 0.0 {
+        return Err(out_of_range("interest_rate", 0.0, Some(0.0), "must be 0 because SAFEs do not accrue interest"));
+    }
+
+    let principal = money(fx, &instrument.principal, &path("principal"), Period::Current)?;
+    if principal <= Decimal::ZERO {
+        return Err(out_of_range("principal", 0.0, None, "must be positive"));
+    }
+    let years = instrument.years_outstanding.unwrap_or(0.0);
+    if interest_rate * years < 0.0 {
+        return Err(out_of_range("interest_rate", 0.0, None, "cannot be negative"));
+    }
+    let interest = decimal(interest_rate, "interest_rate")?
+        .checked_mul(decimal(years, "years_outstanding")?)
+        .ok_or_else(|| too_large(&path("years_outstanding")))?;
+    let amount = scale_money(principal, Decimal::ONE + interest)
+        .map_err(|e| FinanceError::invalid_field(&path("interest_rate"), e))?;
+
+    let discount = rate_or(units, instrument.discount.as_deref(), &path("discount"), 0.0)?;
+    if !(0.0..1.0).contains(&discount) {
+        return Err(out_of_range("discount", 0.0, Some(1.0), "must be between 0 and 1"));
+    }
+
+    let cap_price = match instrument.valuation_cap.as_deref() {
+        Some(cap) => {
+            let cap = money(fx, cap, &path("valuation_cap"), Period::Current)?;
+            if cap <= Decimal::ZERO {
+                return Err(out_of_range("valuation_cap", 0.0, None, "must be positive"));
+            }
+            Some(cap.checked_div(existing_shares).ok_or_else(|| too_large(&path("valuation_cap")))?)
+        }
+        None => None,
+    };
+
+    Ok(Conversion {
+        name: instrument.name.clone(),
+        instrument_type,
+        amount,
+        cap_price,
+        discount: decimal(discount, "discount")?,
+    })
+}
+
+/// Per-holding preference and participation proceeds for a given set of converted series.
+///
+/// Claims are at most 1e18 each (checked when the round is parsed), and every split multiplies by
+/// a share of at most one, so the decimal arithmetic cannot overflow.
+fn distribute(
+    exit_value: Decimal,
+    holdings: &[Holding],
+    terms: &[SeriesTerms],
+    converted: &[bool],
+    pari_passu: bool,
+) -> Payouts {
+    let mut preference = vec![Decimal::ZERO; holdings.len()];
+    let mut participation = vec![Decimal::ZERO; holdings.len()];
+    let mut remaining = exit_value;
+
+    let claims: Vec<Decimal> = holdings
+        .iter()
+        .map(|h| match h.series {
+            Some(s) if !converted[s] => h.invested * terms[s].multiple,
+            _ => Decimal::ZERO,
+        })
+        .collect();
+
+    // Preference stack: one class per series from the latest round down, or everything pari passu
+    let groups: Vec<Vec<usize>> = if pari_passu {
+        vec![(0..terms.len()).collect()]
+    } else {
+        (0..terms.len()).rev().map(|s| vec![s]).collect()
+    };
+    for group in groups.iter() {
+        let members: Vec<usize> = (0..holdings.len())
+            .filter(|&i| holdings[i].series.is_some_and(|s| group.contains(&s)))
+            .collect();
+        let demand: Decimal = members.iter().map(|&i| claims[i]).sum();
+        if demand <= Decimal::ZERO {
+            continue;
+        }
+        let paid = demand.min(remaining);
+        for &i in members.iter() {
+            preference[i] = paid * (claims[i] / demand);
+        }
+        remaining -= paid;
+    }
+
+    // Residual to common, converted series and participating series, honouring participation caps
+    let participates = |h: &Holding| match h.series {
+        None => h.holder != OPTION_POOL_HOLDER || h.class != OPTION_POOL_CLASS,
+        Some(s) => converted[s] || terms[s].participating,
+    };
+    let mut active: Vec<bool> = holdings.iter().map(participates).collect();
+    loop {
+        let shares: Decimal = (0..holdings.len()).filter(|&i| active[i]).map(|i| holdings[i].shares).sum();
+        if shares <= Decimal::ZERO || remaining <= Decimal::ZERO {
+            break;
+        }
+
+        let mut capped_any = false;
+        for (s, series) in terms.iter().enumerate() {
+            let Some(cap) = series.cap.filter(|_| series.participating && !converted[s]) else {
+                continue;
+            };
+            let members: Vec<usize> = (0..holdings.len())
+                .filter(|&i| active[i] && holdings[i].series == Some(s))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let invested: Decimal = members.iter().map(|&i| holdings[i].invested).sum();
+            let received: Decimal = members.iter().map(|&i| preference[i]).sum();
+            let series_shares: Decimal = members.iter().map(|&i| holdings[i].shares).sum();
+            let room = (cap * invested - received).max(Decimal::ZERO);
+            if remaining * (series_shares / shares) > room {
+                for &i in members.iter() {
+                    participation[i] = room * (holdings[i].shares / series_shares);
+                    active[i] = false;
+                }
+                remaining -= room;
+                capped_any = true;
+            }
+        }
+
+        if !capped_any {
+            for i in (0..holdings.len()).filter(|&i| active[i]) {
+                participation[i] = remaining * (holdings[i].shares / shares);
+            }
+            break;
+        }
+    }
+
+    (preference, participation)
+}
+
+fn series_total(holdings: &[Holding], payouts: &Payouts, series: usize) -> Decimal {
+    (0..holdings.len())
+        .filter(|&i| holdings[i].series == Some(series))
+        .map(|i| payouts.0[i] + payouts.1[i])
+        .sum()
+}
+
+/// Distribute the exit value, letting each series convert to common when that pays more
+fn liquidation_waterfall(
+    exit_value: Decimal,
+    holdings: &[Holding],
+    terms: &[SeriesTerms],
+    pari_passu: bool,
+    rounding: MoneyRounding,
+) -> LiquidationWaterfall {
+    let mut converted = vec![false; terms.len()];
+    let mut payouts = distribute(exit_value, holdings, terms, &converted, pari_passu);
+
+    // Convert one series at a time, the one gaining most first, until no series gains by converting
+    loop {
+        let mut best: Option<(usize, Decimal, Payouts)> = None;
+        for s in (0..terms.len()).filter(|&s| !converted[s]) {
+            if terms[s].participating && terms[s].cap.is_none() {
+                continue;
+            }
+            let mut trial = converted.clone();
+            trial[s] = true;
+            let trial_payouts = distribute(exit_value, holdings, terms, &trial, pari_passu);
+            let gain = series_total(holdings, &trial_payouts, s) - series_total(holdings, &payouts, s);
+            if gain > CONVERSION_GAIN_TOLERANCE && best.as_ref().is_none_or(|b| gain > b.1) {
+                best = Some((s, gain, trial_payouts));
+            }
+        }
+        match best {
+            Some((s, _, trial_payouts)) => {
+                converted[s] = true;
+                payouts = trial_payouts;
+            }
+            None => break,
+        }
+    }
+
+    let series = terms
+        .iter()
+        .enumerate()
+        .map(|(s, t)| {
+            let members: Vec<usize> = (0..holdings.len()).filter(|&i| holdings[i].series == Some(s)).collect();
+            let invested: Decimal = members.iter().map(|&i| holdings[i].invested).sum();
+            let preference_paid: Decimal = members.iter().map(|&i| payouts.0[i]).sum();
+            let participation_paid: Decimal = members.iter().map(|&i| payouts.1[i]).sum();
+            let total = preference_paid + participation_paid;
+            let preference_type = match (t.participating, t.cap) {
+                (false, _) => "non-participating",
+                (true, None) => "participating",
+                (true, Some(_)) => "capped participating",
+            };
+            let decision = if converted[s] {
+                "Converted"
+            } else if t.participating {
+                "Participating"
+            } else {
+                "Preference"
+            };
+            WaterfallSeries {
+                series: t.name.clone(),
+                invested: rounding.round(invested),
+                preference_type: preference_type.to_string(),
+                decision: decision.to_string(),
+                preference_paid: rounding.round(preference_paid),
+                participation_paid: rounding.round(participation_paid),
+                total_payout: rounding.round(total),
+                multiple_of_invested: (invested > Decimal::ZERO)
+                    .then(|| round_ratio(decimal_to_f64(total) / decimal_to_f64(invested), 2)),
+            }
+        })
+        .collect();
+
+    let common_payout: Decimal = (0..holdings.len())
+        .filter(|&i| holdings[i].series.is_none())
+        .map(|i| payouts.0[i] + payouts.1[i])
+        .sum();
+
+    let mut payouts_by_holder: Vec<(&str, Decimal)> = Vec::new();
+    for (i, h) in holdings.iter().enumerate() {
+        if h.holder == OPTION_POOL_HOLDER && h.class == OPTION_POOL_CLASS {
+            continue;
+        }
+        let payout = payouts.0[i] + payouts.1[i];
+        match payouts_by_holder.iter_mut().find(|p| p.0 == h.holder) {
+            Some(entry) => entry.1 += payout,
+            None => payouts_by_holder.push((&h.holder, payout)),
+        }
+    }
+    payouts_by_holder.sort_by_key(|p| std::cmp::Reverse(p.1));
+    let holder_payouts = payouts_by_holder
+        .into_iter()
+        .map(|(holder, payout)| HolderPayout {
+            holder: holder.to_string(),
+            payout: rounding.round(payout),
+            pct_of_exit: if exit_value > Decimal::ZERO {
+                round_ratio(decimal_to_f64(payout) / decimal_to_f64(exit_value) * 100.0, 2)
+            } else {
+                0.0
+            },
+        })
+        .collect();
+
+    LiquidationWaterfall {
+        exit_value: rounding.round(exit_value),
+        stacking: if pari_passu { "pari_passu" } else { "standard" }.to_string(),
+        series,
+        common_payout: rounding.round(common_payout),
+        holder_payouts,
+    }
+}
+
+/// Roll a cap table through funding rounds and optionally run the exit waterfall
+pub fn calculate_cap_table_internal(
+    params: &CapTableParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<CapTableResponse, FinanceError> {
+    if params.shareholders.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one existing shareholder is required").with_field("shareholders"));
+    }
+    let pari_passu = match params.preference_stacking.as_deref().map(|s| s.trim().to_lowercase()) {
+        None => false,
+        Some(s) if s == "standard" => false,
+        Some(s) if s == "pari_passu" => true,
+        Some(other) => {
+            return Err(FinanceError::invalid_field(
+                "preference_stacking",
+                format!("'{}' must be standard or pari_passu", other),
+            ))
+        }
+    };
+    let decimal = |value: f64, name: &str| decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(name, e));
+
+    let mut holdings: Vec<Holding> = Vec::new();
+    for holder in params.shareholders.iter() {
+        let name = format!("shareholders.{}.shares", holder.name);
+        if holder.shares <= 0.0 {
+            return Err(FinanceError::out_of_range(
+                &name,
+                Some(0.0),
+                None,
+                format!("Shareholder '{}': shares must be positive", holder.name),
+            ));
+        }
+        holdings.push(Holding {
+            holder: holder.name.clone(),
+            class: holder.class.clone().unwrap_or_else(|| "Common".to_string()),
+            shares: decimal(holder.shares, &name)?,
+            series: None,
+            invested: Decimal::ZERO,
+            original: true,
+        });
+    }
+    let existing_pool = params.option_pool_shares.unwrap_or(0.0);
+    if existing_pool < 0.0 {
+        return Err(FinanceError::out_of_range("option_pool_shares", Some(0.0), None, "option_pool_shares cannot be negative"));
+    }
+    holdings.push(Holding {
+        holder: OPTION_POOL_HOLDER.to_string(),
+        class: OPTION_POOL_CLASS.to_string(),
+        shares: decimal(existing_pool, "option_pool_shares")?,
+        series: None,
+        invested: Decimal::ZERO,
+        original: false,
+    });
+
+    let rounding = fx.rounding();
+    let mut terms: Vec<SeriesTerms> = Vec::with_capacity(params.rounds.len());
+    let mut rounds = Vec::with_capacity(params.rounds.len());
+    for round in params.rounds.iter() {
+        let path = |field: &str| format!("rounds.{}.{}", round.name, field);
+        let pre_money = money(fx, &round.pre_money_valuation, &path("pre_money_valuation"), Period::Current)?;
+        let investment = money(fx, &round.investment, &path("investment"), Period::Current)?;
+        let out_of_range = |field: &str, min: f64, max: Option<f64>, problem: &str| {
+            FinanceError::out_of_range(&path(field), Some(min), max, format!("Round '{}': {} {}", round.name, field, problem))
+        };
+        if pre_money <= Decimal::ZERO {
+            return Err(out_of_range("pre_money_valuation", 0.0, None, "must be positive"));
+        }
+        if investment < Decimal::ZERO {
+            return Err(out_of_range("investment", 0.0, None, "cannot be negative"));
+        }
+        let pool_target = round
+            .option_pool_target_pct
+            .as_deref()
+            .map(|v| field(units, v, &path("option_pool_target_pct"), NumberUnit::Ratio))
+            .transpose()?;
+        if pool_target.is_some_and(|t| !(0.0..1.0).contains(&t)) {
+            return Err(out_of_range("option_pool_target_pct", 0.0, Some(1.0), "must be between 0 and 1"));
+        }
+        let pool_target = pool_target.map(|t| decimal(t, &path("option_pool_target_pct"))).transpose()?;
+        let multiple = round.liquidation_preference.unwrap_or(1.0);
+        if multiple < 0.0 {
+            return Err(out_of_range("liquidation_preference", 0.0, None, "cannot be negative"));
+        }
+        if round.participation_cap.is_some_and(|c| c <= 0.0) {
+            return Err(out_of_range("participation_cap", 0.0, None, "must be positive"));
+        }
+        let multiple = decimal(multiple, &path("liquidation_preference"))?;
+        let cap = round.participation_cap.map(|c| decimal(c, &path("participation_cap"))).transpose()?;
+
+        let investors: Vec<(String, Decimal)> = match round.investors.as_ref() {
+            Some(list) if !list.is_empty() => {
+                let mut parsed = Vec::with_capacity(list.len());
+                for investor in list.iter() {
+                    let amount = money(fx, &investor.amount, &path(&format!("investors.{}.amount", investor.name)), Period::Current)?;
+                    parsed.push((investor.name.clone(), amount));
+                }
+                let total: Decimal = parsed.iter().map(|(_, a)| a).sum();
+                if (total - investment).abs() > AMOUNT_TOLERANCE * investment.abs().max(Decimal::ONE) {
+                    return Err(FinanceError::sum_mismatch(format!(
+                        "Round '{}': investor amounts sum to {} but investment is {}",
+                        round.name,
+                        rounding.round(total),
+                        rounding.round(investment)
+                    ))
+                    .with_field(&path("investors")));
+                }
+                parsed
+            }
+            _ => vec![(format!("{} investors", round.name), investment)],
+        };
+
+        let existing_shares = fully_diluted(&holdings);
+        let conversions = round
+            .convertibles
+            .iter()
+            .flatten()
+            .map(|c| parse_convertible(fx, units, &round.name, c, existing_shares))
+            .collect::<Result<Vec<_>, FinanceError>>()?;
+
+        // Preference claims and participation caps must stay within the supported magnitude
+        let series_invested = investors.iter().map(|(_, a)| *a).chain(conversions.iter().map(|c| c.amount)).sum();
+        let scale = |factor: Decimal, field: &str| scale_money(series_invested, factor).map_err(|e| FinanceError::invalid_field(&path(field), e));
+        scale(multiple, "liquidation_preference")?;
+        if let Some(cap) = cap {
+            scale(cap, "participation_cap")?;
+        }
+
+        let pool = pool_index(&holdings).map_or(Decimal::ZERO, |i| holdings[i].shares);
+        let (price, top_up, conversion_shares) = solve_round_price(
+            &round.name,
+            pre_money,
+            investment,
+            existing_shares,
+            pool,
+            pool_target,
+            &conversions,
+        )?;
+
+        let series = terms.len();
+        terms.push(SeriesTerms {
+            name: round.name.clone(),
+            multiple,
+            participating: round.participating.unwrap_or(false),
+            cap,
+        });
+
+        if let Some(i) = pool_index(&holdings) {
+            holdings[i].shares += top_up;
+        }
+        let mut conversion_results = Vec::with_capacity(conversions.len());
+        for (conversion, shares) in conversions.iter().zip(conversion_shares.iter()) {
+            let (conversion_price, basis) = conversion_price(conversion, price);
+            holdings.push(Holding {
+                holder: conversion.name.clone(),
+                class: round.name.clone(),
+                shares: *shares,
+                series: Some(series),
+                invested: conversion.amount,
+                original: false,
+            });
+            conversion_results.push(ConversionResult {
+                name: conversion.name.clone(),
+                instrument_type: conversion.instrument_type.clone(),
+                amount_converted: rounding.round(conversion.amount),
+                conversion_price: rounding.round_price(conversion_price),
+                shares_issued: round_ratio(decimal_to_f64(*shares), 2),
+                basis: basis.to_string(),
+            });
+        }
+        let mut new_investor_shares = Decimal::ZERO;
+        for (name, invested) in investors.into_iter() {
+            let shares = invested.checked_div(price).ok_or_else(|| too_large(&path("investors")))?;
+            new_investor_shares += shares;
+            holdings.push(Holding { holder: name, class: round.name.clone(), shares, series: Some(series), invested, original: false });
+        }
+
+        rounds.push(RoundResult {
+            name: round.name.clone(),
+            pre_money_valuation: rounding.round(pre_money),
+            investment: rounding.round(investment),
+            post_money_valuation: rounding.round(pre_money + investment),
+            price_per_share: rounding.round_price(price),
+            new_investor_shares: round_ratio(decimal_to_f64(new_investor_shares), 2),
+            option_pool_top_up: round_ratio(decimal_to_f64(top_up), 2),
+            conversions: conversion_results,
+            fully_diluted_shares: round_ratio(decimal_to_f64(fully_diluted(&holdings)), 2),
+            ownership: ownership(&holdings),
+        });
+    }
+
+    let total_shares = fully_diluted(&holdings);
+    let original_shares: Decimal = holdings.iter().filter(|h| h.original).map(|h| h.shares).sum();
+    let existing_holders_ownership = decimal_to_f64(original_shares) / decimal_to_f64(total_shares);
+
+    let waterfall = match params.exit_value.as_deref() {
+        Some(value) => {
+            let exit_value = money(fx, value, "exit_value", Period::Current)?;
+            if exit_value < Decimal::ZERO {
+                return Err(FinanceError::out_of_range("exit_value", Some(0.0), None, "Exit value cannot be negative"));
+            }
+            Some(liquidation_waterfall(exit_value, &holdings, &terms, pari_passu, rounding))
+        }
+        None => None,
+    };
+
+    let mut interpretation = format!(
+        "After {} round(s) the original shareholders hold {:.1}% of {:.0} fully diluted shares.",
+        rounds.len(),
+        existing_holders_ownership * 100.0,
+        total_shares
+    );
+    if let Some(w) = waterfall.as_ref() {
+        let common_share = if w.exit_value > Decimal::ZERO {
+            decimal_to_f64(w.common_payout) / decimal_to_f64(w.exit_value) * 100.0
+        } else {
+            0.0
+        };
+        interpretation.push_str(&format!(
+            " At an exit of {:.0} they receive {:.0} ({:.1}% of proceeds) after liquidation preferences.",
+            w.exit_value, w.common_payout, common_share
+        ));
+    }
+
+    Ok(CapTableResponse {
+        rounds,
+        final_ownership: ownership(&holdings),
+        fully_diluted_shares: round_ratio(decimal_to_f64(total_shares), 2),
+        existing_holders_ownership_pct: round_ratio(existing_holders_ownership * 100.0, 2),
+        waterfall,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Cap table roll-forward through funding rounds and exit waterfall (`calculate_cap_table`)
+pub struct CapTable;
+
+impl Calculation for CapTable {
+    const NAME: &'static str = "calculate_cap_table";
+    const DESCRIPTION: &'static str = "Roll a startup cap table through funding rounds and model exit proceeds. Takes existing shareholders and option pool, then rounds in order with pre-money valuation, investment, investors, option pool top-up target, converting SAFEs/convertible notes (valuation cap, discount, interest) and liquidation preference terms (multiple, participating, participation cap); optionally an exit value and preference stacking (standard or pari_passu). Returns per-round price per share, conversion shares, pool top-up and fully diluted ownership, plus a liquidation waterfall showing preference, participation or conversion per series and payout per holder. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "existing_holders_ownership_pct";
+    const BAND_FIELD: Option<&'static str> = None;
+
+    type Params = CapTableParams;
+    type Response = CapTableResponse;
+
+    fn calculate(params: CapTableParams) -> Result<CapTableResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = calculate_cap_table_internal(&params, &mut fx, &mut units).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_cap_table_safe_conversion() {
+        let params: CapTableParams = serde_json::from_value(serde_json::json!({
+            "shareholders": [{"name": "Founders", "shares": 10000000}],
+            "rounds": [{
+                "name": "Series A",
+                "pre_money_valuation": "10000000",
+                "investment": 5000000,
+                "convertibles": [{"name": "Seed SAFE", "instrument_type": "safe", "principal": 1000000, "valuation_cap": 5000000, "discount": 0.2}]
+            }]
+        }))
+        .unwrap();
+
+        let response = CapTable::calculate(params).unwrap();
+
+        // Cap price 5M / 10M = 0.50 beats the discount; 2M SAFE shares sit in the pre-money
+        // Round price 10M / 12M = 0.8333; investors receive 6M shares of 18M
+        let round = &response.rounds[0];
+        assert_eq!(round.conversions[0].basis, "cap");
+        assert_eq!(round.conversions[0].shares_issued, 2000000.0);
+        assert_eq!(round.price_per_share, Decimal::new(8333, 4));
+        assert_eq!(round.new_investor_shares, 6000000.0);
+        assert_eq!(round.post_money_valuation, Decimal::from(15000000));
+        assert_eq!(response.fully_diluted_shares, 18000000.0);
+        assert_eq!(response.existing_holders_ownership_pct, 55.56);
+        assert!(response.waterfall.is_none());
+    }
+
+    #[test]
+    fn test_calculate_cap_table_option_pool_top_up() {
+        let params: CapTableParams = serde_json::from_value(serde_json::json!({
+            "shareholders": [{"name": "Founders", "shares": 9000000}],
+            "rounds": [{"name": "Seed", "pre_money_valuation": 8000000, "investment": 2000000, "option_pool_target_pct": "10%"}]
+        }))
+        .unwrap();
+
+        let response = CapTable::calculate(params).unwrap();
+
+        // Investors 20%, pool 10% post-money, both at the founders' expense: founders keep 70%
+        let pool = response.final_ownership.iter().find(|e| e.holder == "Option pool").unwrap();
+        assert_eq!(pool.ownership_pct, 10.0);
+        assert_eq!(response.existing_holders_ownership_pct, 70.0);
+        assert_eq!(response.rounds[0].price_per_share, Decimal::new(7778, 4));
+        assert!(response.parsing_notes.iter().any(|n| n.starts_with("rounds.Seed.option_pool_target_pct")));
+    }
+
+    #[test]
+    fn test_calculate_cap_table_discount_given_in_percent_points() {
+        let params: CapTableParams = serde_json::from_value(serde_json::json!({
+            "shareholders": [{"name": "Founders", "shares": 10000000}],
+            "rounds": [{
+                "name": "Series A",
+                "pre_money_valuation": 10000000,
+                "investment": 5000000,
+                "convertibles": [{"name": "Seed SAFE", "instrument_type": "safe", "principal": 1000000, "discount": 20}]
+            }]
+        }))
+        .unwrap();
+
+        let error = CapTable::calculate(params).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("rounds.Series A.convertibles.Seed SAFE.discount"));
+    }
+
+    #[test]
+    fn test_calculate_cap_table_liquidation_waterfall() {
+        let run = |exit_value: f64, participating: bool| {
+            serde_json::from_value::<CapTableParams>(serde_json::json!({
+                "shareholders": [{"name": "Founders", "shares": 1000000}],
+                "rounds": [{"name": "Series A", "pre_money_valuation": 4000000, "investment": 1000000, "participating": participating}],
+                "exit_value": exit_value
+            }))
+            .unwrap()
+        };
+
+        // 1x non-participating owning 20%: preference at a 3M exit, converts at 10M
+        for (exit_value, participating, decision, series_payout, common_payout) in [
+            (3000000.0, false, "Preference", 1000000, 2000000),
+            (10000000.0, false, "Converted", 2000000, 8000000),
+            (3000000.0, true, "Participating", 1400000, 1600000),
+        ] {
+            let response = CapTable::calculate(run(exit_value, participating)).unwrap();
+            let waterfall = response.waterfall.unwrap();
+            assert_eq!(waterfall.series[0].decision, decision);
+            assert_eq!(waterfall.series[0].total_payout, Decimal::from(series_payout));
+            assert_eq!(waterfall.common_payout, Decimal::from(common_payout));
+        }
+    }
+}
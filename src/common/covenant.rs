@@ -0,0 +1,523 @@
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::calculation::{converter, fx_failure, money, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{decimal_from_f64, decimal_to_f64, round_ratio};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_opt};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIGHT_HEADROOM_PCT: f64 = 10.0;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: check_covenant_compliance
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantStep {
+    #[schemars(description = "Date the new threshold applies from (YYYY-MM-DD)")]
+    pub effective_date: String,
+    #[schemars(description = "Threshold from that date: ratio for coverage/leverage, amount for liquidity")]
+    pub threshold: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantDefinition {
+    #[schemars(description = "Covenant type: max_net_leverage, min_interest_coverage, min_fixed_charge_coverage or min_liquidity")]
+    pub covenant_type: String,
+    #[schemars(description = "Initial threshold: ratio (e.g., 4.5 for 4.5x) or, for min_liquidity, an amount in the reporting currency")]
+    pub threshold: f64,
+    #[schemars(description = "Scheduled threshold changes; the latest step on or before test_date applies")]
+    #[serde(default)]
+    pub step_downs: Option<Vec<CovenantStep>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantComplianceParams {
+    #[schemars(description = "Covenant test date (YYYY-MM-DD)")]
+    pub test_date: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Covenant EBITDA for the test period (usually LTM)")]
+    pub ebitda: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Total debt at the test date")]
+    pub total_debt: String,
+    #[schemars(description = "Cash and equivalents netted against debt and counted as liquidity (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cash: Option<String>,
+    #[schemars(description = "Cash interest expense for the test period")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub interest_expense: Option<String>,
+    #[schemars(description = "Scheduled principal repayments for the test period, a fixed charge (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub scheduled_principal: Option<String>,
+    #[schemars(description = "Capital expenditure deducted from EBITDA for fixed-charge coverage (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub capex: Option<String>,
+    #[schemars(description = "Cash taxes deducted from EBITDA for fixed-charge coverage (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cash_taxes: Option<String>,
+    #[schemars(description = "Undrawn committed revolver counted as liquidity (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub undrawn_revolver: Option<String>,
+    #[schemars(description = "Covenants to test")]
+    pub covenants: Vec<CovenantDefinition>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantMetrics {
+    #[schemars(description = "Total debt less cash", with = "String")]
+    pub net_debt: Decimal,
+    #[schemars(description = "Net debt / EBITDA (None when EBITDA is not positive)")]
+    pub net_leverage: Option<f64>,
+    #[schemars(description = "EBITDA / interest expense")]
+    pub interest_coverage: Option<f64>,
+    #[schemars(description = "(EBITDA - capex - cash taxes) / (interest + scheduled principal)")]
+    pub fixed_charge_coverage: Option<f64>,
+    #[schemars(description = "Cash plus undrawn revolver", with = "String")]
+    pub liquidity: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantResult {
+    #[schemars(description = "Covenant type")]
+    pub covenant_type: String,
+    #[schemars(description = "Threshold applying at the test date")]
+    pub threshold: f64,
+    #[schemars(description = "Step-down date the threshold applies from, if not the initial threshold")]
+    pub threshold_effective_date: Option<String>,
+    #[schemars(description = "Actual value at the test date (None when undefined, e.g. leverage on non-positive EBITDA)")]
+    pub actual: Option<f64>,
+    #[schemars(description = "Compliance status: Pass or Breach")]
+    pub status: String,
+    #[schemars(description = "Distance to the threshold in the covenant's units; negative when breached, None when the actual is undefined")]
+    pub headroom: Option<f64>,
+    #[schemars(description = "Headroom as percentage of the threshold")]
+    pub headroom_pct: f64,
+    #[schemars(description = "EBITDA decline before a breach (None for liquidity)", with = "Option<String>")]
+    pub ebitda_cushion: Option<Decimal>,
+    #[schemars(description = "EBITDA cushion as percentage of EBITDA")]
+    pub ebitda_cushion_pct: Option<f64>,
+    #[schemars(description = "Next scheduled threshold after the test date")]
+    pub next_step: Option<CovenantStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CovenantComplianceResponse {
+    #[schemars(description = "Covenant test date")]
+    pub test_date: String,
+    #[schemars(description = "Credit metrics computed from the inputs")]
+    pub metrics: CovenantMetrics,
+    #[schemars(description = "Result per covenant")]
+    pub covenants: Vec<CovenantResult>,
+    #[schemars(description = "Overall status: Compliant or Breach")]
+    pub overall_status: String,
+    #[schemars(description = "Number of covenants breached")]
+    pub covenants_breached: usize,
+    #[schemars(description = "Covenant with the least headroom")]
+    pub tightest_covenant: String,
+    #[schemars(description = "Headroom percentage of the tightest covenant")]
+    pub tightest_headroom_pct: f64,
+    #[schemars(description = "Smallest EBITDA decline that triggers any breach", with = "Option<String>")]
+    pub min_ebitda_cushion: Option<Decimal>,
+    #[schemars(description = "Warnings about breaches, thin headroom and upcoming step-downs")]
+    pub covenant_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of compliance")]
+    pub interpretation: String,
+    #[schemars(description = "Covenant metrics that could not be computed from the inputs")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn parse_date(value: &str, name: &str) -> Result<NaiveDate, FinanceError> {
+    NaiveDate::parse_from_str(value.trim(), DATE_FORMAT)
+        .map_err(|_| FinanceError::invalid_field(name, format!("'{}' is not a YYYY-MM-DD date", value)))
+}
+
+/// Threshold in force at the test date and the next scheduled step, if any
+fn applicable_threshold(
+    index: usize,
+    covenant: &CovenantDefinition,
+    test_date: NaiveDate,
+) -> Result<(f64, Option<String>, Option<CovenantStep>), FinanceError> {
+    let mut steps = Vec::new();
+    for (i, step) in covenant.step_downs.iter().flatten().enumerate() {
+        let date = parse_date(&step.effective_date, &format!("covenants.{}.step_downs.{}.effective_date", index, i))?;
+        steps.push((date, step));
+    }
+    steps.sort_by_key(|(date, _)| *date);
+
+    let mut threshold = covenant.threshold;
+    let mut effective = None;
+    let mut next = None;
+    for (date, step) in steps.iter() {
+        if *date <= test_date {
+            threshold = step.threshold;
+            effective = Some(date.format(DATE_FORMAT).to_string());
+        } else {
+            next = Some(CovenantStep { effective_date: date.format(DATE_FORMAT).to_string(), threshold: step.threshold });
+            break;
+        }
+    }
+    Ok((threshold, effective, next))
+}
+
+/// Test credit metrics against covenant thresholds with step-downs
+pub fn check_covenant_compliance_internal(
+    params: &CovenantComplianceParams,
+    fx: &mut CurrencyConverter,
+) -> Result<CovenantComplianceResponse, FinanceError> {
+    if params.covenants.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one covenant is required").with_field("covenants"));
+    }
+    let test_date = parse_date(&params.test_date, "test_date")?;
+
+    let rounding = fx.rounding();
+    let ebitda = money(fx, &params.ebitda, "ebitda", Period::Current)?;
+    let total_debt = money(fx, &params.total_debt, "total_debt", Period::Current)?;
+    let mut optional = |value: Option<&str>, name: &str| value.map(|v| money(fx, v, name, Period::Current)).transpose();
+    let cash = optional(params.cash.as_deref(), "cash")?.unwrap_or_default();
+    let interest = optional(params.interest_expense.as_deref(), "interest_expense")?;
+    let principal = optional(params.scheduled_principal.as_deref(), "scheduled_principal")?.unwrap_or_default();
+    let capex = optional(params.capex.as_deref(), "capex")?.unwrap_or_default();
+    let cash_taxes = optional(params.cash_taxes.as_deref(), "cash_taxes")?.unwrap_or_default();
+    let undrawn = optional(params.undrawn_revolver.as_deref(), "undrawn_revolver")?.unwrap_or_default();
+
+    let non_negative = [
+        ("total_debt", Some(total_debt)),
+        ("cash", Some(cash)),
+        ("scheduled_principal", Some(principal)),
+        ("undrawn_revolver", Some(undrawn)),
+        ("interest_expense", interest),
+    ];
+    if let Some((name, _)) = non_negative.iter().find(|(_, value)| value.is_some_and(|v| v < Decimal::ZERO)) {
+        return Err(FinanceError::out_of_range(name, Some(0.0), None, format!("{} cannot be negative", name)));
+    }
+
+    let net_debt = total_debt - cash;
+    let liquidity = cash + undrawn;
+    let net_leverage = (ebitda > Decimal::ZERO).then(|| decimal_to_f64(net_debt / ebitda));
+    let interest_coverage = interest.filter(|i| *i > Decimal::ZERO).map(|i| decimal_to_f64(ebitda / i));
+    let fixed_charges = interest.unwrap_or_default() + principal;
+    let fixed_charge_coverage =
+        (fixed_charges > Decimal::ZERO).then(|| decimal_to_f64((ebitda - capex - cash_taxes) / fixed_charges));
+
+    let mut results = Vec::with_capacity(params.covenants.len());
+    let mut warnings = Vec::new();
+    for (index, covenant) in params.covenants.iter().enumerate() {
+        let kind = covenant.covenant_type.trim().to_lowercase();
+        let (threshold, effective, next_step) = applicable_threshold(index, covenant, test_date)?;
+        if threshold < 0.0 || next_step.as_ref().is_some_and(|s| s.threshold < 0.0) {
+            return Err(FinanceError::out_of_range(
+                &format!("covenants.{}.threshold", index),
+                Some(0.0),
+                None,
+                format!("Covenant {}: thresholds cannot be negative", kind),
+            ));
+        }
+
+        // Breakeven EBITDA is an amount: the threshold multiple times the fixed costs it covers
+        let threshold_path = format!("covenants.{}.threshold", index);
+        let times = |amount: Decimal| {
+            decimal_from_f64(threshold)
+                .ok()
+                .and_then(|t| t.checked_mul(amount))
+                .ok_or_else(|| FinanceError::invalid_field(&threshold_path, "threshold x amount exceeds the supported magnitude"))
+        };
+
+        // (actual, maximum covenant?, EBITDA at which the covenant is exactly met)
+        let (actual, is_max, breakeven_ebitda) = match kind.as_str() {
+            "max_net_leverage" => {
+                if threshold <= 0.0 {
+                    return Err(FinanceError::out_of_range(
+                        &format!("covenants.{}.threshold", index),
+                        Some(0.0),
+                        None,
+                        "Covenant max_net_leverage: threshold must be positive",
+                    ));
+                }
+                // Non-positive EBITDA against positive net debt is an unconditional breach
+                let actual = match net_leverage {
+                    Some(leverage) => leverage,
+                    None if net_debt > Decimal::ZERO => f64::INFINITY,
+                    None => 0.0,
+                };
+                let breakeven = decimal_from_f64(threshold)
+                    .map_err(|e| FinanceError::invalid_field(&threshold_path, e))
+                    .map(|t| (net_debt / t).max(Decimal::ZERO))?;
+                (actual, true, Some(breakeven))
+            }
+            "min_interest_coverage" => {
+                let interest = interest
+                    .filter(|i| *i > Decimal::ZERO)
+                    .ok_or_else(|| {
+                        FinanceError::insufficient_data("A positive interest_expense is required for min_interest_coverage")
+                            .with_field("interest_expense")
+                    })?;
+                (decimal_to_f64(ebitda / interest), false, Some(times(interest)?))
+            }
+            "min_fixed_charge_coverage" => {
+                let coverage = fixed_charge_coverage
+                    .ok_or_else(|| {
+                        FinanceError::insufficient_data(
+                            "interest_expense or scheduled_principal is required for min_fixed_charge_coverage",
+                        )
+                        .with_field("interest_expense")
+                    })?;
+                (coverage, false, Some(times(fixed_charges)? + capex + cash_taxes))
+            }
+            "min_liquidity" => (decimal_to_f64(liquidity), false, None),
+            other => {
+                return Err(FinanceError::invalid_input(format!(
+                    "Invalid covenant_type '{}': must be max_net_leverage, min_interest_coverage, min_fixed_charge_coverage or min_liquidity",
+                    other
+                ))
+                .with_field(&format!("covenants.{}.covenant_type", index)))
+            }
+        };
+
+        let headroom = if is_max { threshold - actual } else { actual - threshold };
+        let passes = headroom >= 0.0;
+        let headroom_pct = if threshold > 0.0 { headroom / threshold * 100.0 } else { 0.0 };
+        let ebitda_cushion = breakeven_ebitda.map(|b| ebitda - b);
+        let ebitda_cushion_pct =
+            ebitda_cushion.filter(|_| ebitda > Decimal::ZERO).map(|c| round_ratio(decimal_to_f64(c / ebitda) * 100.0, 2));
+
+        if !actual.is_finite() {
+            warnings.push(format!(
+                "{} is undefined: net debt is positive but EBITDA is not; actual and headroom are reported as null",
+                kind
+            ));
+        }
+
+        results.push(CovenantResult {
+            covenant_type: kind,
+            threshold,
+            threshold_effective_date: effective,
+            actual: Some(actual).filter(|a| a.is_finite()).map(|v| round_ratio(v, 2)),
+            status: if passes { "Pass" } else { "Breach" }.to_string(),
+            headroom: Some(headroom).filter(|h| h.is_finite()).map(|v| round_ratio(v, 2)),
+            headroom_pct: if headroom_pct.is_finite() { round_ratio(headroom_pct, 2) } else { -100.0 },
+            ebitda_cushion: ebitda_cushion.map(|c| rounding.round(c)),
+            ebitda_cushion_pct,
+            next_step,
+        });
+    }
+
+    let covenants_breached = results.iter().filter(|r| r.status == "Breach").count();
+    let tightest = results
+        .iter()
+        .min_by(|a, b| a.headroom_pct.partial_cmp(&b.headroom_pct).unwrap_or(std::cmp::Ordering::Equal))
+        .expect("at least one covenant");
+    let tightest_covenant = tightest.covenant_type.clone();
+    let tightest_headroom_pct = tightest.headroom_pct;
+    let min_ebitda_cushion = results
+        .iter()
+        .filter_map(|r| r.ebitda_cushion)
+        .min();
+
+    let mut covenant_issues = Vec::new();
+    for r in results.iter() {
+        if r.status == "Breach" {
+            let actual = r.actual.map_or_else(|| "undefined".to_string(), |a| format!("{:.2}", a));
+            covenant_issues.push(format!(
+                "{} breached: actual {} against threshold {:.2}",
+                r.covenant_type, actual, r.threshold
+            ));
+        } else if r.headroom_pct < TIGHT_HEADROOM_PCT {
+            covenant_issues.push(format!(
+                "{} headroom is only {:.1}% of the threshold",
+                r.covenant_type, r.headroom_pct
+            ));
+        }
+        if let (Some(step), Some(actual)) = (r.next_step.as_ref(), r.actual) {
+            let is_max = r.covenant_type == "max_net_leverage";
+            let breaches_next = if is_max { actual > step.threshold } else { actual < step.threshold };
+            if breaches_next && r.status == "Pass" {
+                covenant_issues.push(format!(
+                    "{} would breach the {:.2} threshold stepping in on {} at current levels",
+                    r.covenant_type, step.threshold, step.effective_date
+                ));
+            }
+        }
+    }
+
+    let overall_status = if covenants_breached == 0 { "Compliant" } else { "Breach" };
+    let mut interpretation = if covenants_breached == 0 {
+        format!(
+            "All {} covenants pass at {}. Tightest is {} with {:.1}% headroom.",
+            results.len(),
+            params.test_date.trim(),
+            tightest_covenant,
+            tightest_headroom_pct
+        )
+    } else {
+        format!(
+            "{} of {} covenants breached at {}. Worst is {} at {:.1}% headroom.",
+            covenants_breached,
+            results.len(),
+            params.test_date.trim(),
+            tightest_covenant,
+            tightest_headroom_pct
+        )
+    };
+    if let Some(cushion) = min_ebitda_cushion.filter(|c| *c > Decimal::ZERO && ebitda > Decimal::ZERO) {
+        interpretation.push_str(&format!(
+            " EBITDA could fall by {:.0} ({:.1}%) before the first breach.",
+            cushion,
+            decimal_to_f64(cushion / ebitda) * 100.0
+        ));
+    }
+
+    Ok(CovenantComplianceResponse {
+        test_date: test_date.format(DATE_FORMAT).to_string(),
+        metrics: CovenantMetrics {
+            net_debt: rounding.round(net_debt),
+            net_leverage: net_leverage.map(|v| round_ratio(v, 2)),
+            interest_coverage: interest_coverage.map(|v| round_ratio(v, 2)),
+            fixed_charge_coverage: fixed_charge_coverage.map(|v| round_ratio(v, 2)),
+            liquidity: rounding.round(liquidity),
+        },
+        covenants: results,
+        overall_status: overall_status.to_string(),
+        covenants_breached,
+        tightest_covenant,
+        tightest_headroom_pct,
+        min_ebitda_cushion,
+        covenant_issues,
+        interpretation,
+        warnings,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Debt covenant compliance test (`check_covenant_compliance`)
+pub struct CovenantCompliance;
+
+impl Calculation for CovenantCompliance {
+    const NAME: &'static str = "check_covenant_compliance";
+    const DESCRIPTION: &'static str = "Test financial statement data against debt covenants. Takes the test date, EBITDA, total debt, cash, interest expense, scheduled principal, capex, cash taxes and undrawn revolver, plus covenants (max_net_leverage, min_interest_coverage, min_fixed_charge_coverage, min_liquidity) each with a threshold and optional dated step-downs. Returns the computed credit metrics and, per covenant, the threshold in force, pass/fail status, headroom in absolute and % terms, the EBITDA decline before a breach and the next step-down, plus overall status, tightest covenant and covenant issues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "tightest_headroom_pct";
+    const BAND_FIELD: Option<&'static str> = Some("overall_status");
+
+    type Params = CovenantComplianceParams;
+    type Response = CovenantComplianceResponse;
+
+    fn calculate(params: CovenantComplianceParams) -> Result<CovenantComplianceResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = check_covenant_compliance_internal(&params, &mut fx).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covenant_params(liquidity_threshold: f64) -> CovenantComplianceParams {
+        serde_json::from_value(serde_json::json!({
+            "test_date": "2025-12-31",
+            "ebitda": 100,
+            "total_debt": 450,
+            "cash": 50,
+            "interest_expense": 20,
+            "scheduled_principal": 10,
+            "capex": 20,
+            "cash_taxes": 10,
+            "undrawn_revolver": 50,
+            "covenants": [
+                {"covenant_type": "max_net_leverage", "threshold": 5.0, "step_downs": [
+                    {"effective_date": "2026-06-30", "threshold": 3.75},
+                    {"effective_date": "2025-06-30", "threshold": 4.5}
+                ]},
+                {"covenant_type": "min_interest_coverage", "threshold": 3.0},
+                {"covenant_type": "min_fixed_charge_coverage", "threshold": 1.25},
+                {"covenant_type": "min_liquidity", "threshold": liquidity_threshold}
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check_covenant_compliance_step_downs() {
+        let response = CovenantCompliance::calculate(covenant_params(75.0)).unwrap();
+
+        // Net debt 400 / EBITDA 100 = 4.0x against the 4.5x step in force since 2025-06-30
+        let leverage = &response.covenants[0];
+        assert_eq!(leverage.threshold, 4.5);
+        assert_eq!(leverage.threshold_effective_date.as_deref(), Some("2025-06-30"));
+        assert_eq!(leverage.headroom, Some(0.5));
+        assert_eq!(leverage.headroom_pct, 11.11);
+        // Breach once EBITDA < 400 / 4.5 = 88.89
+        assert_eq!(leverage.ebitda_cushion, Some(Decimal::new(1111, 2)));
+        assert_eq!(leverage.next_step.as_ref().unwrap().threshold, 3.75);
+
+        // Interest cover 5.0x vs 3.0x: EBITDA can fall to 60
+        assert_eq!(response.covenants[1].ebitda_cushion, Some(Decimal::new(40, 0)));
+        // FCCR (100 - 20 - 10) / 30 = 2.33x vs 1.25x: EBITDA can fall to 37.5 + 30
+        assert_eq!(response.metrics.fixed_charge_coverage, Some(2.33));
+        assert_eq!(response.covenants[2].ebitda_cushion, Some(Decimal::new(325, 1)));
+        assert_eq!(response.covenants[3].ebitda_cushion, None);
+
+        assert_eq!(response.overall_status, "Compliant");
+        assert_eq!(response.tightest_covenant, "max_net_leverage");
+        assert_eq!(response.min_ebitda_cushion, Some(Decimal::new(1111, 2)));
+        assert!(response.covenant_issues.iter().any(|i| i.contains("2026-06-30")));
+    }
+
+    #[test]
+    fn test_check_covenant_compliance_breach() {
+        let response = CovenantCompliance::calculate(covenant_params(150.0)).unwrap();
+
+        // Liquidity 50 cash + 50 revolver = 100 against 150
+        let liquidity = &response.covenants[3];
+        assert_eq!(liquidity.status, "Breach");
+        assert_eq!(liquidity.headroom, Some(-50.0));
+        assert_eq!(liquidity.headroom_pct, -33.33);
+        assert_eq!(response.overall_status, "Breach");
+        assert_eq!(response.covenants_breached, 1);
+        assert_eq!(response.tightest_covenant, "min_liquidity");
+    }
+
+    #[test]
+    fn test_check_covenant_compliance_invalid_covenant() {
+        let mut params = covenant_params(75.0);
+        params.covenants[1].covenant_type = "max_capex".to_string();
+
+        let error = CovenantCompliance::calculate(params).unwrap_err();
+        assert!(error.message.contains("Invalid covenant_type 'max_capex'"));
+        assert_eq!(error.field.as_deref(), Some("covenants.1.covenant_type"));
+    }
+
+    #[test]
+    fn test_check_covenant_compliance_undefined_leverage() {
+        let mut params = covenant_params(75.0);
+        params.ebitda = "-10".to_string();
+        params.covenants.truncate(1);
+
+        let response = CovenantCompliance::calculate(params).unwrap();
+        let leverage = &response.covenants[0];
+        assert_eq!(leverage.status, "Breach");
+        assert_eq!(leverage.actual, None);
+        assert_eq!(leverage.headroom, None);
+        assert_eq!(response.warnings.len(), 1);
+        assert!(response.warnings[0].contains("max_net_leverage is undefined"));
+        assert!(response.covenant_issues[0].contains("actual undefined"));
+    }
+}
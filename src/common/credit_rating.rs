@@ -0,0 +1,445 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+
+use super::calculation::{converter, fx_failure, money, Calculation};
+use super::error::FinanceError;
+use super::extraction::{InputField, InputUnit};
+use super::money::{decimal_to_f64, round_ratio};
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::parsing::{deserialize_flexible_f64_opt, sanitize_for_error_message};
+
+/// Default scorecard shipped with the crate; bump the file name and version when the grid changes
+const DEFAULT_SCORECARD: &str = include_str!("scorecards/credit_rating_v1.json");
+const WEIGHT_TOLERANCE: f64 = 0.001;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: estimate_credit_rating
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ScorecardFactor {
+    #[schemars(description = "Factor name, matching an input metric (e.g., 'net_leverage')")]
+    pub name: String,
+    #[schemars(description = "What the factor measures")]
+    #[serde(default)]
+    pub description: Option<String>,
+    #[schemars(description = "Factor weight as decimal; weights sum to 1")]
+    pub weight: f64,
+    #[schemars(description = "Whether a higher value is stronger credit")]
+    pub higher_is_better: bool,
+    #[schemars(description = "Boundaries between adjacent ratings, one fewer than ratings, from strongest to weakest")]
+    pub breakpoints: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RatingDefaultProbability {
+    #[schemars(description = "Rating bucket")]
+    pub rating: String,
+    #[schemars(description = "One-year probability of default as decimal")]
+    pub pd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RatingScorecard {
+    #[schemars(description = "Scorecard version reported with every result")]
+    pub version: String,
+    #[schemars(description = "Rating buckets from strongest to weakest (e.g., AAA ... CCC)")]
+    pub ratings: Vec<String>,
+    #[schemars(description = "Lowest investment grade rating (e.g., 'BBB')")]
+    #[serde(default)]
+    pub investment_grade_floor: Option<String>,
+    #[schemars(description = "Weighted factors and their rating grid")]
+    pub factors: Vec<ScorecardFactor>,
+    #[schemars(description = "Rating to one-year default probability table")]
+    pub default_probabilities: Vec<RatingDefaultProbability>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CreditRatingParams {
+    #[schemars(description = "Net debt / EBITDA")]
+    #[serde(default)]
+    pub net_leverage: Option<f64>,
+    #[schemars(description = "EBITDA / interest expense")]
+    #[serde(default)]
+    pub interest_coverage: Option<f64>,
+    #[schemars(description = "EBITDA margin as decimal (e.g., 0.18 for 18%)")]
+    #[serde(default)]
+    pub ebitda_margin: Option<f64>,
+    #[schemars(description = "Annual revenue, the size factor (number or currency-tagged amount)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub revenue: Option<String>,
+    #[schemars(description = "Values for extra factors used by a custom scorecard, keyed by factor name")]
+    #[serde(default)]
+    pub additional_factors: Option<HashMap<String, f64>>,
+    #[schemars(description = "Custom scorecard; falls back to RATING_SCORECARD_FILE, then the built-in scorecard")]
+    #[serde(default)]
+    pub scorecard: Option<RatingScorecard>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RatingFactorResult {
+    #[schemars(description = "Factor name")]
+    pub factor: String,
+    #[schemars(description = "Input value")]
+    pub value: f64,
+    #[schemars(description = "Factor weight")]
+    pub weight: f64,
+    #[schemars(description = "Rating bucket the factor alone maps to")]
+    pub factor_rating: String,
+    #[schemars(description = "Notch score of the factor, 0 = strongest rating")]
+    pub notch_score: f64,
+    #[schemars(description = "Weight x notch score, the factor's share of the rating score")]
+    pub weighted_contribution: f64,
+    #[schemars(description = "Weighted notches the factor pulls the rating down (positive) or up (negative) versus the overall score")]
+    pub rating_drag: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CreditRatingResponse {
+    #[schemars(description = "Version of the scorecard applied")]
+    pub scorecard_version: String,
+    #[schemars(description = "Where the scorecard came from: request, file or built-in")]
+    pub scorecard_source: String,
+    #[schemars(description = "Implied rating bucket")]
+    pub implied_rating: String,
+    #[schemars(description = "Weighted notch score, 0 = strongest rating")]
+    pub rating_score: f64,
+    #[schemars(description = "Whether the implied rating is investment grade")]
+    pub investment_grade: Option<bool>,
+    #[schemars(description = "Implied one-year probability of default as decimal")]
+    pub implied_default_probability: f64,
+    #[schemars(description = "Implied one-year probability of default as percentage")]
+    pub implied_default_probability_pct: f64,
+    #[schemars(description = "Result per scorecard factor")]
+    pub factors: Vec<RatingFactorResult>,
+    #[schemars(description = "Notch contribution of each factor to the rating score")]
+    pub weighted_contributions: HashMap<String, f64>,
+    #[schemars(description = "Factor pulling the rating down the most, if any")]
+    pub limiting_factor: Option<String>,
+    #[schemars(description = "Plain language summary of the implied rating")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// =================== SCORECARD ===================
+
+fn load_scorecard_file() -> Result<Option<RatingScorecard>, String> {
+    let Ok(path) = env::var("RATING_SCORECARD_FILE") else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read RATING_SCORECARD_FILE '{}': {}", path, e))?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse RATING_SCORECARD_FILE '{}': {}", path, e))
+}
+
+/// Scorecard from the request, RATING_SCORECARD_FILE or the built-in grid, with its source label
+fn resolve_scorecard(inline: Option<&RatingScorecard>) -> Result<(RatingScorecard, &'static str), FinanceError> {
+    let (scorecard, source) = match inline {
+        Some(scorecard) => (scorecard.clone(), "request"),
+        None => match load_scorecard_file().map_err(FinanceError::upstream_unavailable)? {
+            Some(scorecard) => (scorecard, "file"),
+            None => (
+                serde_json::from_str(DEFAULT_SCORECARD)
+                    .map_err(|e| FinanceError::internal(format!("Built-in scorecard is invalid: {}", e)))?,
+                "built-in",
+            ),
+        },
+    };
+    validate_scorecard(&scorecard)?;
+    Ok((scorecard, source))
+}
+
+fn validate_scorecard(scorecard: &RatingScorecard) -> Result<(), FinanceError> {
+    if scorecard.ratings.len() < 2 {
+        return Err(FinanceError::insufficient_data("Scorecard needs at least two ratings").with_field("scorecard"));
+    }
+    if scorecard.factors.is_empty() {
+        return Err(FinanceError::insufficient_data("Scorecard needs at least one factor").with_field("scorecard"));
+    }
+    let total_weight: f64 = scorecard.factors.iter().map(|f| f.weight).sum();
+    if (total_weight - 1.0).abs() > WEIGHT_TOLERANCE {
+        return Err(FinanceError::sum_mismatch(format!(
+            "Scorecard factor weights must sum to 1.0, got {:.4}",
+            total_weight
+        ))
+        .with_field("scorecard"));
+    }
+    for factor in scorecard.factors.iter() {
+        let name = sanitize_for_error_message(&factor.name);
+        if factor.weight <= 0.0 {
+            return Err(FinanceError::out_of_range(
+                "scorecard",
+                Some(0.0),
+                None,
+                format!("Scorecard factor '{}' needs a positive weight", name),
+            ));
+        }
+        if factor.breakpoints.len() != scorecard.ratings.len() - 1 {
+            return Err(FinanceError::invalid_input(format!(
+                "Scorecard factor '{}' needs {} breakpoints, one between each pair of ratings",
+                name,
+                scorecard.ratings.len() - 1
+            ))
+            .with_field(&format!("scorecard.factors.{}.breakpoints", factor.name)));
+        }
+        let ordered = factor.breakpoints.windows(2).all(|w| {
+            if factor.higher_is_better { w[0] > w[1] } else { w[0] < w[1] }
+        });
+        if !ordered {
+            return Err(FinanceError::invalid_input(format!(
+                "Scorecard factor '{}' breakpoints must run from strongest to weakest credit",
+                name
+            ))
+            .with_field(&format!("scorecard.factors.{}.breakpoints", factor.name)));
+        }
+    }
+    let mut previous = 0.0;
+    for rating in scorecard.ratings.iter() {
+        let pd = scorecard
+            .default_probabilities
+            .iter()
+            .find(|p| &p.rating == rating)
+            .map(|p| p.pd)
+            .ok_or_else(|| {
+                FinanceError::insufficient_data(format!(
+                    "Scorecard has no default probability for '{}'",
+                    sanitize_for_error_message(rating)
+                ))
+                .with_field("scorecard.default_probabilities")
+            })?;
+        if !(0.0..=1.0).contains(&pd) || pd < previous {
+            return Err(FinanceError::out_of_range(
+                "scorecard",
+                Some(previous),
+                Some(1.0),
+                "Scorecard default probabilities must be between 0 and 1 and rise as ratings weaken",
+            ));
+        }
+        previous = pd;
+    }
+    if let Some(floor) = scorecard.investment_grade_floor.as_ref()
+        && !scorecard.ratings.contains(floor)
+    {
+        return Err(FinanceError::invalid_field(
+            "scorecard.investment_grade_floor",
+            format!("'{}' is not a scorecard rating", sanitize_for_error_message(floor)),
+        ));
+    }
+    Ok(())
+}
+
+/// Notch index of the strongest rating whose boundary the value clears
+fn factor_notch(factor: &ScorecardFactor, value: f64) -> usize {
+    factor
+        .breakpoints
+        .iter()
+        .position(|b| if factor.higher_is_better { value >= *b } else { value <= *b })
+        .unwrap_or(factor.breakpoints.len())
+}
+
+// =================== CALCULATION ===================
+
+/// Map credit ratios to an implied rating bucket and default probability
+pub fn estimate_credit_rating_internal(
+    params: &CreditRatingParams,
+    fx: &mut CurrencyConverter,
+) -> Result<CreditRatingResponse, FinanceError> {
+    let (scorecard, source) = resolve_scorecard(params.scorecard.as_ref())?;
+
+    let mut inputs: HashMap<String, f64> = params.additional_factors.clone().unwrap_or_default();
+    let standard = [
+        ("net_leverage", params.net_leverage),
+        ("interest_coverage", params.interest_coverage),
+        ("ebitda_margin", params.ebitda_margin),
+    ];
+    for (name, value) in standard.into_iter() {
+        if let Some(value) = value {
+            inputs.insert(name.to_string(), value);
+        }
+    }
+    if let Some(revenue) = params.revenue.as_deref() {
+        let revenue = money(fx, revenue, "revenue", Period::Current)?;
+        inputs.insert("revenue".to_string(), decimal_to_f64(revenue));
+    }
+
+    let mut notches = Vec::with_capacity(scorecard.factors.len());
+    for factor in scorecard.factors.iter() {
+        let value = inputs
+            .get(&factor.name)
+            .copied()
+            .ok_or_else(|| {
+                FinanceError::insufficient_data(format!(
+                    "Missing input for scorecard factor '{}'",
+                    sanitize_for_error_message(&factor.name)
+                ))
+                .with_field(&factor.name)
+            })?;
+        if !value.is_finite() {
+            return Err(FinanceError::invalid_field(&factor.name, "must be a finite number"));
+        }
+        notches.push((value, factor_notch(factor, value) as f64));
+    }
+
+    let rating_score: f64 = scorecard.factors.iter().zip(notches.iter()).map(|(f, (_, n))| f.weight * n).sum();
+    let implied_index = (rating_score.round() as usize).min(scorecard.ratings.len() - 1);
+    let implied_rating = scorecard.ratings[implied_index].clone();
+    let implied_default_probability = scorecard
+        .default_probabilities
+        .iter()
+        .find(|p| p.rating == implied_rating)
+        .map(|p| p.pd)
+        .unwrap_or(1.0);
+    let investment_grade = scorecard.investment_grade_floor.as_ref().map(|floor| {
+        let floor_index = scorecard.ratings.iter().position(|r| r == floor).unwrap_or(0);
+        implied_index <= floor_index
+    });
+
+    let mut factors = Vec::with_capacity(scorecard.factors.len());
+    let mut weighted_contributions = HashMap::new();
+    for (factor, (value, notch)) in scorecard.factors.iter().zip(notches.iter()) {
+        let contribution = factor.weight * notch;
+        weighted_contributions.insert(factor.name.clone(), round_ratio(contribution, 4));
+        factors.push(RatingFactorResult {
+            factor: factor.name.clone(),
+            value: *value,
+            weight: factor.weight,
+            factor_rating: scorecard.ratings[*notch as usize].clone(),
+            notch_score: *notch,
+            weighted_contribution: round_ratio(contribution, 4),
+            rating_drag: round_ratio(factor.weight * (notch - rating_score), 4),
+        });
+    }
+    let limiting_factor = factors
+        .iter()
+        .filter(|f| f.rating_drag > 0.0)
+        .max_by(|a, b| a.rating_drag.partial_cmp(&b.rating_drag).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|f| f.factor.clone());
+
+    let mut interpretation = format!(
+        "Implied rating {} (score {:.2}) with a one-year default probability of {:.2}%",
+        implied_rating,
+        rating_score,
+        implied_default_probability * 100.0
+    );
+    match investment_grade {
+        Some(true) => interpretation.push_str(", investment grade."),
+        Some(false) => interpretation.push_str(", sub-investment grade."),
+        None => interpretation.push('.'),
+    }
+    if let Some(limiting) = limiting_factor.as_ref()
+        && let Some(f) = factors.iter().find(|f| &f.factor == limiting)
+    {
+        interpretation.push_str(&format!(" {} ({}) pulls the rating down the most.", f.factor, f.factor_rating));
+    }
+
+    Ok(CreditRatingResponse {
+        scorecard_version: scorecard.version.clone(),
+        scorecard_source: source.to_string(),
+        implied_rating,
+        rating_score: round_ratio(rating_score, 2),
+        investment_grade,
+        implied_default_probability,
+        implied_default_probability_pct: round_ratio(implied_default_probability * 100.0, 4),
+        factors,
+        weighted_contributions,
+        limiting_factor,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Implied credit rating from a scorecard grid (`estimate_credit_rating`)
+pub struct CreditRating;
+
+impl Calculation for CreditRating {
+    const NAME: &'static str = "estimate_credit_rating";
+    const DESCRIPTION: &'static str = "Estimate an implied credit rating (AAA to CCC) from financial ratios using a versioned scorecard grid. Takes net leverage (net debt/EBITDA), interest coverage (EBITDA/interest), EBITDA margin and revenue (size), plus optional extra factors and a custom scorecard (otherwise RATING_SCORECARD_FILE or the built-in scorecard). Returns the implied rating, weighted notch score, investment grade flag, implied one-year default probability from the scorecard's rating-to-PD table, per-factor ratings and weighted contributions, and the factor pulling the rating down the most. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "rating_score";
+    const BAND_FIELD: Option<&'static str> = Some("implied_rating");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::optional("net_leverage", InputUnit::Multiple, &["net leverage", "net debt to ebitda", "net debt/ebitda"]),
+        InputField::optional("interest_coverage", InputUnit::Multiple, &["interest coverage", "ebitda to interest", "ebitda/interest"]),
+        InputField::optional("ebitda_margin", InputUnit::Ratio, &["ebitda margin"]),
+        InputField::optional("revenue", InputUnit::Amount, &["total revenue", "revenue"]),
+    ];
+
+    type Params = CreditRatingParams;
+    type Response = CreditRatingResponse;
+
+    fn calculate(params: CreditRatingParams) -> Result<CreditRatingResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = estimate_credit_rating_internal(&params, &mut fx).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_credit_rating_built_in_scorecard() {
+        let params: CreditRatingParams = serde_json::from_value(serde_json::json!({
+            "net_leverage": 2.5,
+            "interest_coverage": 9.0,
+            "ebitda_margin": 0.22,
+            "revenue": "500000000"
+        }))
+        .unwrap();
+
+        let response = CreditRating::calculate(params).unwrap();
+
+        // Notches: leverage BBB (3), coverage A (2), margin A (2), size BB (4)
+        // Score 0.35 x 3 + 0.25 x 2 + 0.20 x 2 + 0.20 x 4 = 2.75 -> BBB
+        assert_eq!(response.scorecard_version, "1.0");
+        assert_eq!(response.scorecard_source, "built-in");
+        assert_eq!(response.rating_score, 2.75);
+        assert_eq!(response.implied_rating, "BBB");
+        assert_eq!(response.investment_grade, Some(true));
+        assert_eq!(response.implied_default_probability, 0.0018);
+        assert_eq!(response.weighted_contributions["revenue"], 0.8);
+        // Size sits 1.25 notches below the score at a 20% weight
+        assert_eq!(response.limiting_factor.as_deref(), Some("revenue"));
+        assert_eq!(response.factors[3].rating_drag, 0.25);
+    }
+
+    #[test]
+    fn test_estimate_credit_rating_custom_scorecard() {
+        let scorecard = serde_json::json!({
+            "version": "custom-1",
+            "ratings": ["IG", "HY"],
+            "factors": [{"name": "debt_to_equity", "weight": 1.0, "higher_is_better": false, "breakpoints": [1.0]}],
+            "default_probabilities": [{"rating": "IG", "pd": 0.001}, {"rating": "HY", "pd": 0.05}]
+        });
+        let params: CreditRatingParams = serde_json::from_value(serde_json::json!({
+            "additional_factors": {"debt_to_equity": 1.5},
+            "scorecard": scorecard
+        }))
+        .unwrap();
+
+        let response = CreditRating::calculate(params).unwrap();
+        assert_eq!(response.scorecard_version, "custom-1");
+        assert_eq!(response.implied_rating, "HY");
+        assert_eq!(response.implied_default_probability_pct, 5.0);
+        assert_eq!(response.investment_grade, None);
+
+        let params: CreditRatingParams = serde_json::from_value(serde_json::json!({ "scorecard": scorecard })).unwrap();
+        let error = CreditRating::calculate(params).unwrap_err();
+        assert!(error.message.contains("Missing input for scorecard factor 'debt_to_equity'"));
+    }
+}
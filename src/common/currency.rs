@@ -0,0 +1,451 @@
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use super::parsing::{
+    deserialize_bool_flexible_opt, parse_decimal_with_note, parse_f64_with_note, sanitize_for_error_message,
+    NumberLocale,
+};
+use super::money::{check_money_range, decimal_from_f64, MoneyRounding, RoundingMode};
+
+/// Currency symbols and prefixes recognized in amounts, longest first
+pub(crate) const CURRENCY_SYMBOLS: [(&str, &str); 8] = [
+    ("US$", "USD"),
+    ("C$", "CAD"),
+    ("A$", "AUD"),
+    ("R$", "BRL"),
+    ("$", "USD"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+    ("¥", "JPY"),
+];
+
+/// ISO 4217 codes recognized as amount prefixes or suffixes
+pub(crate) const ISO_CURRENCY_CODES: [&str; 30] = [
+    "USD", "EUR", "GBP", "JPY", "CHF", "CAD", "AUD", "NZD", "CNY", "HKD",
+    "SGD", "SEK", "NOK", "DKK", "PLN", "CZK", "HUF", "INR", "BRL", "MXN",
+    "ZAR", "KRW", "TRY", "ILS", "AED", "SAR", "ARS", "CLP", "COP", "TWD",
+];
+
+// =================== DATA STRUCTURES ===================
+
+/// Per-request currency options, flattened into the parameters of every tool taking amounts
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CurrencyOptions {
+    #[schemars(description = "ISO currency code to report in (e.g., 'USD'). Untagged amounts are assumed to be in this currency")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporting_currency: Option<String>,
+    #[schemars(description = "Current-period FX rates as units of reporting currency per one unit of each currency (e.g., {'EUR': 1.08})")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fx_rates: Option<HashMap<String, f64>>,
+    #[schemars(description = "Prior-period FX rates, used for prior-period amounts and for constant-currency restatement")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_fx_rates: Option<HashMap<String, f64>>,
+    #[schemars(description = "Restate current-period amounts at prior-period FX rates (constant currency). Default: false")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt", skip_serializing_if = "Option::is_none")]
+    pub constant_currency: Option<bool>,
+    #[schemars(description = "Rounding for monetary outputs: half_even (default), half_up, half_down, down, up, floor or ceiling")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding_mode: Option<RoundingMode>,
+    #[schemars(description = "Decimal places for monetary outputs (default: 2, max: 10)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimal_places: Option<u32>,
+    #[schemars(description = "Locale of number formatting (e.g., 'en', 'es', 'de-DE'), so '1.234,56' is read with a decimal comma. Auto-detected from digit grouping when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CurrencyConversion {
+    #[schemars(description = "Currency all amounts were reported in")]
+    pub reporting_currency: String,
+    #[schemars(description = "Whether current-period amounts were restated at prior-period rates")]
+    pub constant_currency: bool,
+    #[schemars(description = "Current-period rates applied, per currency")]
+    pub rates_applied: HashMap<String, f64>,
+    #[schemars(description = "Prior-period rates applied, per currency")]
+    pub prior_rates_applied: HashMap<String, f64>,
+    #[schemars(description = "Where FX rates came from: request or file")]
+    pub rate_source: String,
+    #[schemars(description = "One note per converted amount")]
+    pub notes: Vec<String>,
+}
+
+/// FX rate file referenced by the FX_RATES_FILE environment variable
+#[derive(Debug, Default, Deserialize)]
+struct FxRatesFile {
+    reporting_currency: Option<String>,
+    #[serde(default)]
+    rates: HashMap<String, f64>,
+    #[serde(default)]
+    prior_rates: HashMap<String, f64>,
+}
+
+/// Amount parsed from text, with its currency tag and a note when its notation was interpreted
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedAmount<T> {
+    pub value: T,
+    pub currency: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Current,
+    Prior,
+}
+
+// =================== PARSING ===================
+
+/// ISO code at the start (`prefix`) or end of the input, if it is a known currency
+fn iso_code_affix(s: &str, prefix: bool) -> Option<String> {
+    if s.len() < 3 {
+        return None;
+    }
+    let (code, neighbour) = if prefix {
+        (s.get(..3)?, s[3..].chars().next())
+    } else {
+        (s.get(s.len() - 3..)?, s[..s.len() - 3].chars().next_back())
+    };
+    if neighbour.is_some_and(|c| c.is_alphabetic()) {
+        return None;
+    }
+    let code = code.to_ascii_uppercase();
+    ISO_CURRENCY_CODES.contains(&code.as_str()).then_some(code)
+}
+
+/// Split an amount into its number text and currency code, if tagged
+pub(crate) fn split_currency_tag(s: &str) -> Result<(String, Option<String>), String> {
+    let trimmed = s.trim();
+    let sanitized = sanitize_for_error_message(trimmed);
+
+    // Accounting negatives may wrap the currency too: "(EUR 3,200)"
+    let wrapped = trimmed.len() > 2 && trimmed.starts_with('(') && trimmed.ends_with(')');
+    let mut currency: Option<String> = None;
+    let mut rest = if wrapped { trimmed[1..trimmed.len() - 1].trim() } else { trimmed }.to_string();
+
+    let mut tag = |code: &str| -> Result<(), String> {
+        match currency.as_deref() {
+            Some(existing) if existing != code => Err(format!("Conflicting currencies in '{}'", sanitized)),
+            _ => {
+                currency = Some(code.to_string());
+                Ok(())
+            }
+        }
+    };
+
+    // ISO code prefix or suffix
+    if let Some(code) = iso_code_affix(&rest, true) {
+        tag(&code)?;
+        rest = rest[3..].to_string();
+    } else if let Some(code) = iso_code_affix(&rest, false) {
+        tag(&code)?;
+        rest = rest[..rest.len() - 3].to_string();
+    }
+
+    for (symbol, code) in CURRENCY_SYMBOLS.iter() {
+        if rest.contains(symbol) {
+            tag(code)?;
+            rest = rest.replacen(symbol, "", 1);
+            break;
+        }
+    }
+
+    if wrapped {
+        rest = format!("({})", rest.trim());
+    }
+    Ok((rest, currency))
+}
+
+/// Parse an amount that may carry a currency symbol or ISO code ("$1,200", "EUR 1200", "1200 gbp")
+/// and magnitude suffixes or accounting notation ("$1.2B", "(3,200)")
+pub(crate) fn parse_currency_amount(s: &str, locale: Option<NumberLocale>) -> Result<ParsedAmount<f64>, String> {
+    let (rest, currency) = split_currency_tag(s)?;
+    let sanitized = sanitize_for_error_message(s.trim());
+    let (value, note) = parse_f64_with_note(&rest, locale).map_err(|e| format!("Cannot parse '{}' as an amount: {}", sanitized, e))?;
+    Ok(ParsedAmount {
+        value,
+        currency,
+        note: note.map(|_| format!("interpreted '{}' as {}", sanitized, value)),
+    })
+}
+
+/// Parse a currency-tagged amount as an exact decimal
+pub(crate) fn parse_currency_money(s: &str, locale: Option<NumberLocale>) -> Result<ParsedAmount<Decimal>, String> {
+    let (rest, currency) = split_currency_tag(s)?;
+    let sanitized = sanitize_for_error_message(s.trim());
+    let (value, note) =
+        parse_decimal_with_note(&rest, locale).map_err(|e| format!("Cannot parse '{}' as an amount: {}", sanitized, e))?;
+    let value = check_money_range(value).map_err(|e| format!("'{}': {}", sanitized, e))?;
+    Ok(ParsedAmount {
+        value,
+        currency,
+        note: note.map(|_| format!("interpreted '{}' as {}", sanitized, value.normalize())),
+    })
+}
+
+// =================== CONVERSION ===================
+
+/// Converts currency-tagged amounts into one reporting currency within a single request.
+///
+/// Without an explicit reporting currency the first tagged currency becomes the reporting
+/// currency, so a request mixing currencies without rates fails instead of being summed, and
+/// a request mixing tagged and untagged amounts fails instead of guessing the untagged currency.
+/// The FX_RATES_FILE is only consulted once a tagged amount needs a reporting currency or rate.
+pub struct CurrencyConverter {
+    reporting_currency: Option<String>,
+    reporting_explicit: bool,
+    rates: Option<HashMap<String, f64>>,
+    prior_rates: Option<HashMap<String, f64>>,
+    constant_currency: bool,
+    rate_source: String,
+    file_consulted: bool,
+    rates_file_failed: bool,
+    rates_applied: HashMap<String, f64>,
+    prior_rates_applied: HashMap<String, f64>,
+    notes: Vec<String>,
+    tagged: bool,
+    untagged: bool,
+    rounding: MoneyRounding,
+    locale: Option<NumberLocale>,
+    parsing_notes: Vec<String>,
+}
+
+fn normalize_rates(rates: HashMap<String, f64>) -> Result<HashMap<String, f64>, String> {
+    rates
+        .into_iter()
+        .map(|(code, rate)| {
+            if rate.is_finite() && rate > 0.0 {
+                Ok((code.trim().to_uppercase(), rate))
+            } else {
+                Err(format!("FX rate for {} must be positive", sanitize_for_error_message(&code)))
+            }
+        })
+        .collect()
+}
+
+/// Last FX_RATES_FILE read, reused until the path or the file's modification time changes
+struct CachedRatesFile {
+    path: String,
+    modified: SystemTime,
+    file: Arc<FxRatesFile>,
+}
+
+static RATES_FILE_CACHE: Mutex<Option<CachedRatesFile>> = Mutex::new(None);
+
+/// Read the FX_RATES_FILE, if configured. Errors do not name the path, which is server configuration.
+fn load_rates_file() -> Result<Option<Arc<FxRatesFile>>, String> {
+    let Ok(path) = env::var("FX_RATES_FILE") else {
+        return Ok(None);
+    };
+
+    let read_error = |e: std::io::Error| format!("Failed to read FX_RATES_FILE: {}", e);
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).map_err(read_error)?;
+    let mut cache = RATES_FILE_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cached) = cache.as_ref().filter(|c| c.path == path && c.modified == modified) {
+        return Ok(Some(cached.file.clone()));
+    }
+
+    let contents = std::fs::read_to_string(&path).map_err(read_error)?;
+    let file: FxRatesFile = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse FX_RATES_FILE: {}", e))?;
+    let file = Arc::new(file);
+    *cache = Some(CachedRatesFile { path, modified, file: file.clone() });
+    Ok(Some(file))
+}
+
+impl CurrencyConverter {
+    /// Build a converter from request options; the FX_RATES_FILE fills in missing tables when needed
+    pub fn from_options(options: &CurrencyOptions) -> Result<Self, String> {
+        let reporting_currency = options.reporting_currency.as_ref().map(|c| c.trim().to_uppercase());
+
+        Ok(Self {
+            reporting_explicit: reporting_currency.is_some(),
+            reporting_currency,
+            rates: options.fx_rates.clone().map(normalize_rates).transpose()?,
+            prior_rates: options.prior_fx_rates.clone().map(normalize_rates).transpose()?,
+            constant_currency: options.constant_currency.unwrap_or(false),
+            rate_source: "request".to_string(),
+            file_consulted: false,
+            rates_file_failed: false,
+            rates_applied: HashMap::new(),
+            prior_rates_applied: HashMap::new(),
+            notes: Vec::new(),
+            tagged: false,
+            untagged: false,
+            rounding: MoneyRounding::new(options.rounding_mode, options.decimal_places)?,
+            locale: NumberLocale::from_option(options.locale.as_deref())?,
+            parsing_notes: Vec::new(),
+        })
+    }
+
+    /// Rounding policy for monetary outputs of this request
+    pub fn rounding(&self) -> MoneyRounding {
+        self.rounding
+    }
+
+    fn record_parsing_note(&mut self, field: &str, note: Option<String>) {
+        if let Some(note) = note {
+            self.parsing_notes.push(format!("{}: {}", field, note));
+        }
+    }
+
+    /// Notes on amounts whose suffixes or accounting notation were interpreted, in parse order
+    pub fn take_parsing_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.parsing_notes)
+    }
+
+    /// Fill the reporting currency and rate tables the request left out from the FX_RATES_FILE, once
+    fn consult_rates_file(&mut self) -> Result<(), String> {
+        if self.file_consulted
+            || (self.reporting_currency.is_some() && self.rates.is_some() && self.prior_rates.is_some())
+        {
+            return Ok(());
+        }
+        let Some(file) = load_rates_file().inspect_err(|_| self.rates_file_failed = true)? else {
+            self.file_consulted = true;
+            return Ok(());
+        };
+
+        if self.reporting_currency.is_none() {
+            self.reporting_currency = file.reporting_currency.as_ref().map(|c| c.trim().to_uppercase());
+            self.reporting_explicit = self.reporting_currency.is_some();
+        }
+        let from_request = self.rates.is_some() || self.prior_rates.is_some();
+        let mut from_file = false;
+        if self.rates.is_none() && !file.rates.is_empty() {
+            self.rates = Some(normalize_rates(file.rates.clone())?);
+            from_file = true;
+        }
+        if self.prior_rates.is_none() && !file.prior_rates.is_empty() {
+            self.prior_rates = Some(normalize_rates(file.prior_rates.clone())?);
+            from_file = true;
+        }
+        if from_file {
+            self.rate_source = if from_request { "request+file" } else { "file" }.to_string();
+        }
+        self.file_consulted = true;
+        Ok(())
+    }
+
+    /// Whether reading or parsing the FX_RATES_FILE failed, so the request could not be converted
+    pub fn rates_file_failed(&self) -> bool {
+        self.rates_file_failed
+    }
+
+    fn mixed_tagging_error() -> String {
+        "Amounts mix currency-tagged and untagged values: tag every amount or provide reporting_currency \
+         for the untagged ones"
+            .to_string()
+    }
+
+    /// Record an amount without a currency tag; it is in the reporting currency only when one was given
+    fn untagged_amount(&mut self) -> Result<(), String> {
+        self.untagged = true;
+        if self.tagged && !self.reporting_explicit {
+            return Err(Self::mixed_tagging_error());
+        }
+        Ok(())
+    }
+
+    /// Rate converting `currency` into the reporting currency, or None when it is the reporting currency
+    fn rate_for(&mut self, currency: &str, period: Period) -> Result<Option<(f64, String)>, String> {
+        self.consult_rates_file()?;
+        if self.untagged && !self.reporting_explicit {
+            return Err(Self::mixed_tagging_error());
+        }
+        self.tagged = true;
+
+        let reporting = self.reporting_currency.get_or_insert_with(|| currency.to_string()).clone();
+        if currency == reporting {
+            return Ok(None);
+        }
+
+        let use_prior = period == Period::Prior || self.constant_currency;
+        let (table, label) = if use_prior {
+            (self.prior_rates.as_ref(), "prior_fx_rates")
+        } else {
+            (self.rates.as_ref(), "fx_rates")
+        };
+        let rate = table.and_then(|t| t.get(currency)).copied().ok_or_else(|| {
+            format!(
+                "Amounts mix {} and {}: provide reporting_currency and a {} rate for {}",
+                reporting, currency, label, currency
+            )
+        })?;
+
+        if use_prior {
+            self.prior_rates_applied.insert(currency.to_string(), rate);
+        } else {
+            self.rates_applied.insert(currency.to_string(), rate);
+        }
+        Ok(Some((rate, reporting)))
+    }
+
+    /// Parse an amount as an exact decimal and convert it into the reporting currency,
+    /// returning the value and rate used. The converted amount is not rounded.
+    pub fn parse_money_with_rate(&mut self, value: &str, field: &str, period: Period) -> Result<(Decimal, f64), String> {
+        let ParsedAmount { value: amount, currency, note } = parse_currency_money(value, self.locale)?;
+        self.record_parsing_note(field, note);
+        let Some(currency) = currency else {
+            self.untagged_amount()?;
+            return Ok((amount, 1.0));
+        };
+        let Some((rate, reporting)) = self.rate_for(&currency, period)? else {
+            return Ok((amount, 1.0));
+        };
+
+        let converted = amount
+            .checked_mul(decimal_from_f64(rate)?)
+            .ok_or_else(|| "converted amount exceeds the supported magnitude of 1e18".to_string())
+            .and_then(check_money_range)?;
+        self.notes.push(format!(
+            "{}: {} {} converted at {} {}/{} = {} {}",
+            field,
+            currency,
+            self.rounding.round(amount),
+            rate,
+            reporting,
+            currency,
+            self.rounding.round(converted),
+            reporting
+        ));
+        Ok((converted, rate))
+    }
+
+    /// Parse an amount as an exact decimal and convert it into the reporting currency
+    pub fn parse_money(&mut self, value: &str, field: &str, period: Period) -> Result<Decimal, String> {
+        self.parse_money_with_rate(value, field, period).map(|(amount, _)| amount)
+    }
+
+    /// Parse and convert a list of amounts as exact decimals, naming failing elements by index
+    pub fn parse_moneys(&mut self, values: &[String], field: &str, period: Period) -> Result<Vec<Decimal>, String> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                self.parse_money(v, &format!("{}[{}]", field, i), period)
+                    .map_err(|e| format!("element {}: {}", i, e))
+            })
+            .collect()
+    }
+
+    /// Summary of conversions performed, or None when no amount carried a currency
+    pub fn into_summary(self) -> Option<CurrencyConversion> {
+        if !self.tagged {
+            return None;
+        }
+
+        Some(CurrencyConversion {
+            reporting_currency: self.reporting_currency.unwrap_or_default(),
+            constant_currency: self.constant_currency,
+            rates_applied: self.rates_applied,
+            prior_rates_applied: self.prior_rates_applied,
+            rate_source: self.rate_source,
+            notes: self.notes,
+        })
+    }
+}
@@ -0,0 +1,430 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::calculation::{converter, fx_failure, money, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{allocate_percentages, decimal_to_f64, round_ratio};
+use super::parsing::{deserialize_flexible_f64, sanitize_for_error_message};
+
+const DEFAULT_TOP_N: [usize; 3] = [1, 5, 10];
+const DEFAULT_TOP_PCTS: [f64; 2] = [0.10, 0.20];
+const DEFAULT_SINGLE_CUSTOMER_THRESHOLD: f64 = 0.10;
+const DEFAULT_CLASS_A_CUTOFF: f64 = 0.80;
+const DEFAULT_CLASS_B_CUTOFF: f64 = 0.95;
+const PARETO_SHARE: f64 = 0.80;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: analyze_customer_concentration
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CustomerRevenue {
+    #[schemars(description = "Customer name")]
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Revenue from the customer (number or currency-tagged amount)")]
+    pub revenue: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CustomerConcentrationParams {
+    #[schemars(description = "Customers with their revenue for the period")]
+    pub customers: Vec<CustomerRevenue>,
+    #[schemars(description = "Top-N customer counts to report revenue shares for (default: [1, 5, 10])")]
+    #[serde(default)]
+    pub top_n: Option<Vec<usize>>,
+    #[schemars(description = "Top shares of the customer base to report as decimals (default: [0.10, 0.20])")]
+    #[serde(default)]
+    pub top_pcts: Option<Vec<f64>>,
+    #[schemars(description = "Revenue share above which a single customer is flagged, as decimal (default: 0.10)")]
+    #[serde(default)]
+    pub single_customer_threshold: Option<f64>,
+    #[schemars(description = "Cumulative revenue share closing class A, as decimal (default: 0.80)")]
+    #[serde(default)]
+    pub class_a_cutoff: Option<f64>,
+    #[schemars(description = "Cumulative revenue share closing class B, as decimal (default: 0.95)")]
+    #[serde(default)]
+    pub class_b_cutoff: Option<f64>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CustomerShare {
+    #[schemars(description = "Rank by revenue, 1 = largest")]
+    pub rank: usize,
+    #[schemars(description = "Customer name")]
+    pub name: String,
+    #[schemars(description = "Customer revenue", with = "String")]
+    pub revenue: Decimal,
+    #[schemars(description = "Share of total revenue as percentage")]
+    pub share_pct: f64,
+    #[schemars(description = "Cumulative share of revenue up to and including this customer as percentage")]
+    pub cumulative_share_pct: f64,
+    #[schemars(description = "ABC class: A (core revenue), B, or C (long tail)")]
+    pub abc_class: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct AbcClassSummary {
+    #[schemars(description = "ABC class")]
+    pub class: String,
+    #[schemars(description = "Customers in the class")]
+    pub customer_count: usize,
+    #[schemars(description = "Share of the customer base as percentage")]
+    pub customer_pct: f64,
+    #[schemars(description = "Share of revenue as percentage")]
+    pub revenue_share_pct: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct TopCustomerShare {
+    #[schemars(description = "Number of largest customers included")]
+    pub customers: usize,
+    #[schemars(description = "Those customers as percentage of the customer base")]
+    pub customer_pct: f64,
+    #[schemars(description = "Their share of revenue as percentage")]
+    pub revenue_share_pct: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CustomerConcentrationResponse {
+    #[schemars(description = "Total revenue across customers", with = "String")]
+    pub total_revenue: Decimal,
+    #[schemars(description = "Number of customers")]
+    pub customer_count: usize,
+    #[schemars(description = "Customers ranked by revenue with cumulative share and ABC class")]
+    pub customers: Vec<CustomerShare>,
+    #[schemars(description = "Customer count and revenue share per ABC class")]
+    pub abc_summary: Vec<AbcClassSummary>,
+    #[schemars(description = "Revenue share of the top-N customers")]
+    pub top_n_shares: Vec<TopCustomerShare>,
+    #[schemars(description = "Revenue share of the top percentages of the customer base")]
+    pub top_pct_shares: Vec<TopCustomerShare>,
+    #[schemars(description = "Fewest customers reaching 80% of revenue")]
+    pub customers_to_80_pct: usize,
+    #[schemars(description = "Those customers as percentage of the customer base")]
+    pub customers_to_80_pct_share: f64,
+    #[schemars(description = "Largest customer")]
+    pub largest_customer: String,
+    #[schemars(description = "Largest customer's share of revenue as percentage")]
+    pub largest_customer_share_pct: f64,
+    #[schemars(description = "Customer-level Herfindahl-Hirschman Index 0.0-1.0")]
+    pub hhi: f64,
+    #[schemars(description = "Customers above the single-customer threshold")]
+    pub customers_above_threshold: Vec<String>,
+    #[schemars(description = "True when any single customer exceeds the threshold")]
+    pub single_customer_flag: bool,
+    #[schemars(description = "Concentration risk: Low, Moderate, High, Severe")]
+    pub concentration_risk: String,
+    #[schemars(description = "Specific warnings about concentration risks")]
+    pub concentration_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of customer concentration")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn fraction(name: &str, value: Option<f64>, default: f64) -> Result<f64, FinanceError> {
+    let value = value.unwrap_or(default);
+    if !(value > 0.0 && value <= 1.0) {
+        return Err(FinanceError::out_of_range(name, Some(0.0), Some(1.0), format!("{} must be between 0 and 1", name)));
+    }
+    Ok(value)
+}
+
+/// Rank customers by revenue and measure how concentrated revenue is among them
+pub fn analyze_customer_concentration_internal(
+    params: &CustomerConcentrationParams,
+    fx: &mut CurrencyConverter,
+) -> Result<CustomerConcentrationResponse, FinanceError> {
+    if params.customers.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one customer is required").with_field("customers"));
+    }
+    let threshold = fraction("single_customer_threshold", params.single_customer_threshold, DEFAULT_SINGLE_CUSTOMER_THRESHOLD)?;
+    let class_a_cutoff = fraction("class_a_cutoff", params.class_a_cutoff, DEFAULT_CLASS_A_CUTOFF)?;
+    let class_b_cutoff = fraction("class_b_cutoff", params.class_b_cutoff, DEFAULT_CLASS_B_CUTOFF)?;
+    if class_b_cutoff < class_a_cutoff {
+        return Err(FinanceError::out_of_range(
+            "class_b_cutoff",
+            Some(class_a_cutoff),
+            Some(1.0),
+            "class_b_cutoff must not be below class_a_cutoff",
+        ));
+    }
+
+    let rounding = fx.rounding();
+    let mut ranked: Vec<(String, Decimal)> = Vec::with_capacity(params.customers.len());
+    for customer in params.customers.iter() {
+        let name = customer.name.trim();
+        if ranked.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            return Err(FinanceError::invalid_input(format!("Duplicate customer '{}'", sanitize_for_error_message(name)))
+                .with_field(&format!("customers.{}.name", name)));
+        }
+        let revenue = money(fx, &customer.revenue, &format!("customers.{}.revenue", name), Period::Current)?;
+        if revenue.is_sign_negative() && !revenue.is_zero() {
+            return Err(FinanceError::out_of_range(
+                &format!("customers.{}.revenue", name),
+                Some(0.0),
+                None,
+                format!("Revenue for '{}' cannot be negative", sanitize_for_error_message(name)),
+            ));
+        }
+        ranked.push((name.to_string(), revenue));
+    }
+    let total: Decimal = ranked.iter().map(|(_, r)| r).sum();
+    if total <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("customers", Some(0.0), None, "Total revenue must be positive"));
+    }
+    ranked.sort_by_key(|(_, revenue)| std::cmp::Reverse(*revenue));
+
+    // Displayed shares are allocated so they sum to exactly 100%; fractions drive the classification
+    let count = ranked.len();
+    let revenues: Vec<Decimal> = ranked.iter().map(|(_, r)| *r).collect();
+    let share_pcts = allocate_percentages(&revenues, total, 2);
+    let fractions: Vec<f64> = revenues.iter().map(|r| decimal_to_f64(r / total)).collect();
+    let share_of_top = |n: usize| decimal_to_f64(revenues.iter().take(n).sum::<Decimal>() / total);
+    let top_share = |n: usize| TopCustomerShare {
+        customers: n,
+        customer_pct: round_ratio(n as f64 / count as f64 * 100.0, 2),
+        revenue_share_pct: decimal_to_f64(share_pcts.iter().take(n).sum::<Decimal>()),
+    };
+
+    // A customer belongs to the class whose cutoff had not been reached before it was added
+    let mut customers = Vec::with_capacity(count);
+    let mut cumulative = 0.0;
+    let mut cumulative_pct = Decimal::ZERO;
+    let mut customers_to_80_pct = count;
+    for (i, (name, revenue)) in ranked.iter().enumerate() {
+        let before = cumulative;
+        cumulative += fractions[i];
+        cumulative_pct += share_pcts[i];
+        let abc_class = if before < class_a_cutoff {
+            "A"
+        } else if before < class_b_cutoff {
+            "B"
+        } else {
+            "C"
+        };
+        if customers_to_80_pct == count && cumulative >= PARETO_SHARE - 1e-12 {
+            customers_to_80_pct = i + 1;
+        }
+        customers.push(CustomerShare {
+            rank: i + 1,
+            name: name.clone(),
+            revenue: rounding.round(*revenue),
+            share_pct: decimal_to_f64(share_pcts[i]),
+            cumulative_share_pct: decimal_to_f64(cumulative_pct),
+            abc_class: abc_class.to_string(),
+        });
+    }
+
+    let abc_summary = ["A", "B", "C"]
+        .iter()
+        .map(|class| {
+            let members: Vec<Decimal> = share_pcts
+                .iter()
+                .zip(customers.iter())
+                .filter(|(_, c)| c.abc_class == *class)
+                .map(|(share, _)| *share)
+                .collect();
+            AbcClassSummary {
+                class: class.to_string(),
+                customer_count: members.len(),
+                customer_pct: round_ratio(members.len() as f64 / count as f64 * 100.0, 2),
+                revenue_share_pct: decimal_to_f64(members.iter().sum()),
+            }
+        })
+        .collect();
+
+    let mut top_n: Vec<usize> = params.top_n.clone().unwrap_or_else(|| DEFAULT_TOP_N.to_vec());
+    top_n.retain(|n| *n > 0 && *n <= count);
+    top_n.sort_unstable();
+    top_n.dedup();
+    let top_n_shares = top_n.into_iter().map(top_share).collect();
+
+    let mut top_pct_shares = Vec::new();
+    for pct in params.top_pcts.clone().unwrap_or_else(|| DEFAULT_TOP_PCTS.to_vec()) {
+        if !(pct > 0.0 && pct <= 1.0) {
+            return Err(FinanceError::out_of_range(
+                "top_pcts",
+                Some(0.0),
+                Some(1.0),
+                format!("top_pcts values must be between 0 and 1, got {}", pct),
+            ));
+        }
+        top_pct_shares.push(top_share(((pct * count as f64).ceil() as usize).max(1)));
+    }
+
+    let largest_share = fractions[0];
+    let hhi: f64 = fractions.iter().map(|f| f.powi(2)).sum();
+    let customers_above_threshold: Vec<String> = ranked
+        .iter()
+        .zip(fractions.iter())
+        .filter(|(_, f)| **f > threshold)
+        .map(|((n, _), _)| n.clone())
+        .collect();
+    let single_customer_flag = !customers_above_threshold.is_empty();
+    let top5_share = share_of_top(5);
+    let top10_share = share_of_top(10);
+
+    let concentration_risk = if largest_share >= 0.25 {
+        "Severe"
+    } else if single_customer_flag || top5_share >= 0.50 {
+        "High"
+    } else if top10_share >= 0.50 {
+        "Moderate"
+    } else {
+        "Low"
+    };
+
+    // Identify concentration issues
+    let mut concentration_issues = Vec::new();
+    for ((name, _), share) in ranked.iter().zip(fractions.iter()).filter(|(_, f)| **f > threshold) {
+        concentration_issues.push(format!(
+            "Single customer exposure: {} is {:.1}% of revenue (threshold {:.1}%)",
+            name,
+            share * 100.0,
+            threshold * 100.0
+        ));
+    }
+    if count >= 5 && top5_share > 0.50 {
+        concentration_issues.push(format!("Top 5 customers account for {:.1}% of revenue", top5_share * 100.0));
+    }
+    if customers_to_80_pct as f64 / count as f64 <= 0.10 {
+        concentration_issues.push(format!(
+            "80% of revenue comes from {} of {} customers",
+            customers_to_80_pct, count
+        ));
+    }
+    if hhi > 0.25 {
+        concentration_issues.push(format!("Customer HHI of {:.2} indicates high concentration", hhi));
+    }
+
+    let interpretation = format!(
+        "{} customers; {} reach 80% of revenue ({:.1}% of the base). Largest customer {} holds {:.1}%. Concentration risk is {}.",
+        count,
+        customers_to_80_pct,
+        customers_to_80_pct as f64 / count as f64 * 100.0,
+        ranked[0].0,
+        largest_share * 100.0,
+        concentration_risk
+    );
+
+    Ok(CustomerConcentrationResponse {
+        total_revenue: rounding.round(total),
+        customer_count: count,
+        customers,
+        abc_summary,
+        top_n_shares,
+        top_pct_shares,
+        customers_to_80_pct,
+        customers_to_80_pct_share: round_ratio(customers_to_80_pct as f64 / count as f64 * 100.0, 2),
+        largest_customer: ranked[0].0.clone(),
+        largest_customer_share_pct: decimal_to_f64(share_pcts[0]),
+        hhi: round_ratio(hhi, 4),
+        customers_above_threshold,
+        single_customer_flag,
+        concentration_risk: concentration_risk.to_string(),
+        concentration_issues,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Customer revenue concentration with Pareto and ABC classification (`analyze_customer_concentration`)
+pub struct CustomerConcentration;
+
+impl Calculation for CustomerConcentration {
+    const NAME: &'static str = "analyze_customer_concentration";
+    const DESCRIPTION: &'static str = "Analyze customer revenue concentration with Pareto and ABC classification. Takes named customers with revenue, optional top-N counts, top customer-base percentages, a single-customer threshold (default 10%) and ABC cutoffs (default 80%/95% of cumulative revenue). Returns customers ranked with cumulative share and ABC class, class summary, top-N and top-% revenue shares, the number of customers needed to reach 80% of revenue, customer-level HHI, customers above the threshold, concentration risk (Low, Moderate, High, Severe) and concentration issues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "largest_customer_share_pct";
+    const BAND_FIELD: Option<&'static str> = Some("concentration_risk");
+
+    type Params = CustomerConcentrationParams;
+    type Response = CustomerConcentrationResponse;
+
+    fn calculate(params: CustomerConcentrationParams) -> Result<CustomerConcentrationResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = analyze_customer_concentration_internal(&params, &mut fx).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_customer_concentration() {
+        let revenues = [("Beta", 200), ("Alpha", 400), ("Gamma", 150), ("Delta", 100), ("Epsilon", 50),
+            ("Zeta", 40), ("Eta", 30), ("Theta", 20), ("Iota", 5), ("Kappa", 5)];
+        let customers: Vec<serde_json::Value> = revenues
+            .iter()
+            .map(|(name, revenue)| serde_json::json!({"name": name, "revenue": revenue}))
+            .collect();
+        let params: CustomerConcentrationParams =
+            serde_json::from_value(serde_json::json!({ "customers": customers })).unwrap();
+
+        let response = CustomerConcentration::calculate(params).unwrap();
+
+        // Cumulative shares 40, 60, 75, 85, 90, 94, 97, 99, 99.5, 100
+        assert_eq!(response.customers[0].name, "Alpha");
+        assert_eq!(response.customers_to_80_pct, 4);
+        assert_eq!(response.customers_to_80_pct_share, 40.0);
+        let classes: Vec<(usize, f64)> = response.abc_summary.iter().map(|c| (c.customer_count, c.revenue_share_pct)).collect();
+        assert_eq!(classes, vec![(4, 85.0), (3, 12.0), (3, 3.0)]);
+
+        let top: Vec<(usize, f64)> = response.top_n_shares.iter().map(|t| (t.customers, t.revenue_share_pct)).collect();
+        assert_eq!(top, vec![(1, 40.0), (5, 90.0), (10, 100.0)]);
+        assert_eq!(response.top_pct_shares[1].customers, 2);
+        assert_eq!(response.top_pct_shares[1].revenue_share_pct, 60.0);
+
+        // Delta at exactly 10% is not above the default threshold
+        assert_eq!(response.customers_above_threshold, vec!["Alpha", "Beta", "Gamma"]);
+        assert!(response.single_customer_flag);
+        assert_eq!(response.concentration_risk, "Severe");
+        assert!(response.concentration_issues.iter().any(|i| i.contains("Alpha is 40.0% of revenue")));
+    }
+
+    #[test]
+    fn test_analyze_customer_concentration_shares_sum_to_100() {
+        let params: CustomerConcentrationParams = serde_json::from_value(serde_json::json!({
+            "customers": [
+                {"name": "Acme", "revenue": "100.10"},
+                {"name": "Globex", "revenue": "100.10"},
+                {"name": "Initech", "revenue": "100.10"}
+            ]
+        }))
+        .unwrap();
+
+        let response = CustomerConcentration::calculate(params).unwrap();
+
+        assert_eq!(response.total_revenue, Decimal::new(30030, 2));
+        let shares: Vec<f64> = response.customers.iter().map(|c| c.share_pct).collect();
+        assert_eq!(shares, vec![33.34, 33.33, 33.33]);
+        assert_eq!(response.customers[2].cumulative_share_pct, 100.0);
+    }
+
+    #[test]
+    fn test_analyze_customer_concentration_duplicate_customer() {
+        let params: CustomerConcentrationParams = serde_json::from_value(serde_json::json!({
+            "customers": [{"name": "Acme", "revenue": 100}, {"name": "acme", "revenue": "$50"}]
+        }))
+        .unwrap();
+
+        let error = CustomerConcentration::calculate(params).unwrap_err();
+        assert!(error.message.contains("Duplicate customer 'acme'"));
+    }
+}
@@ -0,0 +1,574 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::calculation::{converter, fx_failure, field, field_parser, money, Calculation};
+use super::error::FinanceError;
+use super::extraction::{InputField, InputUnit};
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{check_money_range, decimal_from_f64, decimal_to_f64, round_ratio};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_opt, FieldParser, NumberUnit};
+
+const FAIR_VALUE_BAND: f64 = 0.10;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_dividend_discount
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendStage {
+    #[schemars(description = "Number of years in the stage")]
+    pub years: u32,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Annual dividend growth rate during the stage as a ratio (e.g., 0.08, '8%' or '800bps')")]
+    pub growth_rate: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendDiscountParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Most recent annual dividend per share (D0)")]
+    pub current_dividend: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Required return on equity (cost of equity) as a ratio (e.g., 0.09, '9%' or '900bps')")]
+    pub required_return: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Perpetual dividend growth rate after the explicit stages as a ratio (e.g., 0.03 or '3%')")]
+    pub terminal_growth_rate: String,
+    #[schemars(description = "Explicit growth stages before the terminal stage (omit for the Gordon growth model)")]
+    #[serde(default)]
+    pub stages: Option<Vec<DividendStage>>,
+    #[schemars(description = "Current share price, for upside and the implied required return")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub current_price: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendYear {
+    #[schemars(description = "Year from today")]
+    pub year: u32,
+    #[schemars(description = "Dividend per share paid in the year", with = "String")]
+    pub dividend: Decimal,
+    #[schemars(description = "Growth rate applied in the year as percentage")]
+    pub growth_pct: f64,
+    #[schemars(description = "Present value of the dividend", with = "String")]
+    pub present_value: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendDiscountResponse {
+    #[schemars(description = "Model applied: Gordon Growth or Multi-Stage")]
+    pub model: String,
+    #[schemars(description = "Intrinsic value per share", with = "String")]
+    pub intrinsic_value: Decimal,
+    #[schemars(description = "Explicit-stage dividends with present values")]
+    pub dividend_schedule: Vec<DividendYear>,
+    #[schemars(description = "Present value of explicit-stage dividends", with = "String")]
+    pub pv_of_stage_dividends: Decimal,
+    #[schemars(description = "Terminal value at the end of the explicit stages (Gordon value of later dividends)", with = "String")]
+    pub terminal_value: Decimal,
+    #[schemars(description = "Present value of the terminal value", with = "String")]
+    pub pv_of_terminal_value: Decimal,
+    #[schemars(description = "Share of intrinsic value from the terminal value as percentage")]
+    pub terminal_value_share_pct: f64,
+    #[schemars(description = "Next year's dividend / intrinsic value as percentage")]
+    pub implied_dividend_yield_pct: f64,
+    #[schemars(description = "Current share price, if given", with = "Option<String>")]
+    pub current_price: Option<Decimal>,
+    #[schemars(description = "Intrinsic value / price - 1 as percentage")]
+    pub upside_pct: Option<f64>,
+    #[schemars(description = "Required return implied by the price under Gordon growth (D1 / P + g) as percentage")]
+    pub implied_required_return_pct: Option<f64>,
+    #[schemars(description = "Valuation signal: Undervalued, Fairly Valued, Overvalued (within ±10% is fair)")]
+    pub valuation_signal: Option<String>,
+    #[schemars(description = "Plain language summary of the valuation")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 9 for a 9% required return)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Function: assess_dividend_sustainability
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendSustainabilityParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Dividends paid for the period (total or per share, matching the other amounts)")]
+    pub dividends_paid: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Net income for the period (total or EPS)")]
+    pub net_income: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Free cash flow for the period (total or per share)")]
+    pub free_cash_flow: String,
+    #[schemars(description = "Dividend growth rate as a ratio (e.g., 0.08, '8%' or '800bps')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub dividend_growth_rate: Option<String>,
+    #[schemars(description = "Earnings growth rate as a ratio over the same period (e.g., 0.05 or '5%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub earnings_growth_rate: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DividendSustainabilityResponse {
+    #[schemars(description = "Dividends / net income as percentage (None when net income is not positive)")]
+    pub payout_ratio_pct: Option<f64>,
+    #[schemars(description = "Dividends / free cash flow as percentage (None when free cash flow is not positive)")]
+    pub fcf_payout_ratio_pct: Option<f64>,
+    #[schemars(description = "Free cash flow / dividends")]
+    pub fcf_coverage: f64,
+    #[schemars(description = "Dividend growth minus earnings growth in percentage points, when both are given")]
+    pub growth_gap_pct: Option<f64>,
+    #[schemars(description = "Component scores 0-100 for payout, fcf_coverage and growth_alignment")]
+    pub components: HashMap<String, f64>,
+    #[schemars(description = "Composite sustainability score 0-100")]
+    pub sustainability_score: f64,
+    #[schemars(description = "Rating: Highly Sustainable, Sustainable, At Risk, Unsustainable")]
+    pub sustainability_rating: String,
+    #[schemars(description = "Warnings about payout, cash coverage and growth")]
+    pub sustainability_issues: Vec<String>,
+    #[schemars(description = "Human-readable assessment of dividend sustainability")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 9 for a 9% required return)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+
+fn optional_rate(units: &mut FieldParser, value: Option<&str>, name: &str) -> Result<Option<f64>, FinanceError> {
+    value.map(|value| field(units, value, name, NumberUnit::Ratio)).transpose()
+}
+
+/// Linear score from 0 at `worst` to 1 at `best`, clamped
+fn linear_score(value: f64, worst: f64, best: f64) -> f64 {
+    ((value - worst) / (best - worst)).clamp(0.0, 1.0)
+}
+
+/// Value a share as the present value of its dividends: explicit stages then Gordon growth
+pub fn calculate_dividend_discount_internal(
+    params: &DividendDiscountParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<DividendDiscountResponse, FinanceError> {
+    let rounding = fx.rounding();
+    let current_dividend = money(fx, &params.current_dividend, "current_dividend", Period::Current)?;
+    let current_price = params
+        .current_price
+        .as_deref()
+        .map(|p| money(fx, p, "current_price", Period::Current))
+        .transpose()?;
+
+    if current_dividend <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("current_dividend", Some(0.0), None, "current_dividend must be positive"));
+    }
+    if current_price.is_some_and(|p| p <= Decimal::ZERO) {
+        return Err(FinanceError::out_of_range("current_price", Some(0.0), None, "current_price must be positive"));
+    }
+    let r = field(units, &params.required_return, "required_return", NumberUnit::Ratio)?;
+    let g = field(units, &params.terminal_growth_rate, "terminal_growth_rate", NumberUnit::Ratio)?;
+    if r <= g {
+        return Err(FinanceError::out_of_range(
+            "required_return",
+            Some(g),
+            None,
+            format!(
+                "required_return ({:.4}) must exceed terminal_growth_rate ({:.4}) for the terminal value to converge",
+                r, g
+            ),
+        ));
+    }
+    if g <= -1.0 {
+        return Err(FinanceError::out_of_range("terminal_growth_rate", Some(-1.0), None, "Rates must be greater than -1"));
+    }
+    let stages = params
+        .stages
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(i, s)| Ok((s.years, field(units, &s.growth_rate, &format!("stages.{}.growth_rate", i), NumberUnit::Ratio)?)))
+        .collect::<Result<Vec<(u32, f64)>, FinanceError>>()?;
+    if let Some(index) = stages.iter().position(|&(years, growth_rate)| years == 0 || growth_rate <= -1.0) {
+        return Err(FinanceError::out_of_range(
+            &format!("stages.{}", index),
+            None,
+            None,
+            "Each stage needs at least one year and a growth rate above -1",
+        ));
+    }
+
+    let decimal = |value: f64, name: &str| decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(name, e));
+    let too_large = |name: &str| {
+        FinanceError::out_of_range(name, None, None, "Projected dividends exceed the supported magnitude of 1e18")
+    };
+    let growth_factor = |rate: f64, name: &str| decimal(rate, name).map(|rate| Decimal::ONE + rate);
+    let present_value = |amount: Decimal, discount: Decimal| {
+        amount
+            .checked_div(discount)
+            .and_then(|pv| check_money_range(pv).ok())
+            .ok_or_else(|| too_large("required_return"))
+    };
+    let discount_step = growth_factor(r, "required_return")?;
+    let terminal_growth = growth_factor(g, "terminal_growth_rate")?;
+
+    let mut dividend = current_dividend;
+    let mut discount = Decimal::ONE;
+    let mut dividend_schedule = Vec::new();
+    let mut pv_of_stage_dividends = Decimal::ZERO;
+    let mut next_dividend = None;
+    let mut year = 0u32;
+    for (index, &(years, growth_rate)) in stages.iter().enumerate() {
+        let name = format!("stages.{}.growth_rate", index);
+        let step = growth_factor(growth_rate, &name)?;
+        for _ in 0..years {
+            year += 1;
+            dividend = dividend
+                .checked_mul(step)
+                .and_then(|d| check_money_range(d).ok())
+                .ok_or_else(|| too_large(&name))?;
+            discount = discount.checked_mul(discount_step).ok_or_else(|| too_large("required_return"))?;
+            let present_value = present_value(dividend, discount)?;
+            pv_of_stage_dividends += present_value;
+            next_dividend.get_or_insert(dividend);
+            dividend_schedule.push(DividendYear {
+                year,
+                dividend: rounding.round_price(dividend),
+                growth_pct: round_ratio(growth_rate * 100.0, 2),
+                present_value: rounding.round_price(present_value),
+            });
+        }
+    }
+
+    let terminal_value = (dividend * terminal_growth)
+        .checked_div(decimal(r - g, "required_return")?)
+        .and_then(|tv| check_money_range(tv).ok())
+        .ok_or_else(|| too_large("terminal_growth_rate"))?;
+    let pv_of_terminal_value = present_value(terminal_value, discount)?;
+    let intrinsic_value = pv_of_stage_dividends + pv_of_terminal_value;
+    let next_dividend = next_dividend.unwrap_or(current_dividend * terminal_growth);
+    let share_of_value = |part: Decimal| decimal_to_f64(part) / decimal_to_f64(intrinsic_value);
+
+    let upside = current_price.map(|p| decimal_to_f64(intrinsic_value) / decimal_to_f64(p) - 1.0);
+    let valuation_signal = upside.map(|u| {
+        if u > FAIR_VALUE_BAND {
+            "Undervalued"
+        } else if u < -FAIR_VALUE_BAND {
+            "Overvalued"
+        } else {
+            "Fairly Valued"
+        }
+        .to_string()
+    });
+    let implied_required_return =
+        current_price.map(|p| decimal_to_f64(current_dividend * terminal_growth) / decimal_to_f64(p) + g);
+
+    let model = if stages.is_empty() { "Gordon Growth" } else { "Multi-Stage" };
+    let mut interpretation = format!(
+        "{} intrinsic value of {:.2} per share at a {:.1}% required return, with {:.0}% from the terminal value.",
+        model,
+        intrinsic_value,
+        r * 100.0,
+        share_of_value(pv_of_terminal_value) * 100.0
+    );
+    if let (Some(price), Some(upside), Some(signal)) = (current_price, upside, valuation_signal.as_ref()) {
+        interpretation.push_str(&format!(
+            " Against a price of {:.2} that is {:+.1}% ({}).",
+            price,
+            upside * 100.0,
+            signal
+        ));
+    }
+
+    Ok(DividendDiscountResponse {
+        model: model.to_string(),
+        intrinsic_value: rounding.round(intrinsic_value),
+        dividend_schedule,
+        pv_of_stage_dividends: rounding.round(pv_of_stage_dividends),
+        terminal_value: rounding.round(terminal_value),
+        pv_of_terminal_value: rounding.round(pv_of_terminal_value),
+        terminal_value_share_pct: round_ratio(share_of_value(pv_of_terminal_value) * 100.0, 2),
+        implied_dividend_yield_pct: round_ratio(share_of_value(next_dividend) * 100.0, 2),
+        current_price: current_price.map(|p| rounding.round(p)),
+        upside_pct: upside.map(|u| round_ratio(u * 100.0, 2)),
+        implied_required_return_pct: implied_required_return.map(|r| round_ratio(r * 100.0, 2)),
+        valuation_signal,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+/// Check whether a dividend is covered by earnings and free cash flow and growing sustainably
+pub fn assess_dividend_sustainability_internal(
+    params: &DividendSustainabilityParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<DividendSustainabilityResponse, FinanceError> {
+    let dividends = money(fx, &params.dividends_paid, "dividends_paid", Period::Current)?;
+    let net_income = money(fx, &params.net_income, "net_income", Period::Current)?;
+    let free_cash_flow = money(fx, &params.free_cash_flow, "free_cash_flow", Period::Current)?;
+    if dividends <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("dividends_paid", Some(0.0), None, "dividends_paid must be positive"));
+    }
+
+    let ratio = |part: Decimal, whole: Decimal| decimal_to_f64(part) / decimal_to_f64(whole);
+    let payout_ratio = (net_income > Decimal::ZERO).then(|| ratio(dividends, net_income));
+    let fcf_payout_ratio = (free_cash_flow > Decimal::ZERO).then(|| ratio(dividends, free_cash_flow));
+    let fcf_coverage = ratio(free_cash_flow, dividends);
+    let dividend_growth_rate = optional_rate(units, params.dividend_growth_rate.as_deref(), "dividend_growth_rate")?;
+    let earnings_growth_rate = optional_rate(units, params.earnings_growth_rate.as_deref(), "earnings_growth_rate")?;
+    let growth_gap = match (dividend_growth_rate, earnings_growth_rate) {
+        (Some(dividend_growth), Some(earnings_growth)) => Some(dividend_growth - earnings_growth),
+        _ => None,
+    };
+
+    // Payout: 60% or less is fully covered, 100%+ pays out more than is earned
+    let payout_score = payout_ratio.map_or(0.0, |p| linear_score(p, 1.0, 0.6));
+    // FCF coverage: 1.5x scores full marks, 0.8x or less none
+    let coverage_score = linear_score(fcf_coverage, 0.8, 1.5);
+    // Growth: dividends growing no faster than earnings score full marks, 10pp faster none
+    let growth_score = growth_gap.map(|gap| linear_score(gap, 0.10, 0.0));
+
+    let mut components = HashMap::new();
+    components.insert("payout".to_string(), round_ratio(payout_score * 100.0, 2));
+    components.insert("fcf_coverage".to_string(), round_ratio(coverage_score * 100.0, 2));
+    let sustainability_score = match growth_score {
+        Some(score) => {
+            components.insert("growth_alignment".to_string(), round_ratio(score * 100.0, 2));
+            (payout_score * 0.40 + coverage_score * 0.40 + score * 0.20) * 100.0
+        }
+        None => (payout_score + coverage_score) / 2.0 * 100.0,
+    };
+
+    let (sustainability_rating, interpretation) = if sustainability_score >= 80.0 {
+        ("Highly Sustainable", "The dividend is well covered by earnings and free cash flow with room to grow.")
+    } else if sustainability_score >= 60.0 {
+        ("Sustainable", "The dividend is covered but leaves limited buffer if earnings or cash flow weaken.")
+    } else if sustainability_score >= 40.0 {
+        ("At Risk", "The dividend is stretched relative to earnings or cash flow; growth may need to slow.")
+    } else {
+        ("Unsustainable", "The dividend is not covered by earnings or free cash flow; a cut is a material risk.")
+    };
+
+    let mut sustainability_issues = Vec::new();
+    match payout_ratio {
+        Some(p) if p > 1.0 => sustainability_issues.push(format!("Payout ratio of {:.0}% exceeds earnings", p * 100.0)),
+        Some(p) if p > 0.8 => sustainability_issues.push(format!("High payout ratio of {:.0}%", p * 100.0)),
+        None => sustainability_issues.push("Net income is not positive; the dividend is paid from reserves or cash".to_string()),
+        _ => {}
+    }
+    if fcf_coverage < 1.0 {
+        sustainability_issues.push(format!("Free cash flow covers only {:.2}x the dividend", fcf_coverage));
+    }
+    if let Some(gap) = growth_gap
+        && gap > 0.0
+    {
+        sustainability_issues.push(format!(
+            "Dividends growing {:.1} points faster than earnings",
+            gap * 100.0
+        ));
+    }
+
+    Ok(DividendSustainabilityResponse {
+        payout_ratio_pct: payout_ratio.map(|p| round_ratio(p * 100.0, 2)),
+        fcf_payout_ratio_pct: fcf_payout_ratio.map(|p| round_ratio(p * 100.0, 2)),
+        fcf_coverage: round_ratio(fcf_coverage, 2),
+        growth_gap_pct: growth_gap.map(|g| round_ratio(g * 100.0, 2)),
+        components,
+        sustainability_score: round_ratio(sustainability_score, 2),
+        sustainability_rating: sustainability_rating.to_string(),
+        sustainability_issues,
+        interpretation: interpretation.to_string(),
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Dividend discount valuation of a share (`calculate_dividend_discount`)
+pub struct DividendDiscount;
+
+impl Calculation for DividendDiscount {
+    const NAME: &'static str = "calculate_dividend_discount";
+    const DESCRIPTION: &'static str = "Value a share with the dividend discount model. Takes the most recent annual dividend per share, the required return (cost of equity), a terminal growth rate and optional explicit growth stages (years and growth rate each); without stages it applies the Gordon growth model. Returns the intrinsic value per share, the explicit-stage dividend schedule with present values, terminal value and its share of value, implied dividend yield and, with a current price, upside, the price-implied required return and a valuation signal. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "intrinsic_value";
+    const BAND_FIELD: Option<&'static str> = Some("valuation_signal");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("current_dividend", InputUnit::Amount, &["dividend per share", "annual dividend", "current dividend"]),
+        InputField::required("required_return", InputUnit::Ratio, &["required return", "cost of equity"]),
+        InputField::required("terminal_growth_rate", InputUnit::Ratio, &["terminal growth", "long-term growth", "long-term dividend growth"]),
+        InputField::optional("current_price", InputUnit::Amount, &["current share price", "share price", "stock price"]),
+    ];
+
+    type Params = DividendDiscountParams;
+    type Response = DividendDiscountResponse;
+
+    fn calculate(params: DividendDiscountParams) -> Result<DividendDiscountResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = calculate_dividend_discount_internal(&params, &mut fx, &mut units)
+            .map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+/// Dividend sustainability from payout and cash flow coverage (`assess_dividend_sustainability`)
+pub struct DividendSustainability;
+
+impl Calculation for DividendSustainability {
+    const NAME: &'static str = "assess_dividend_sustainability";
+    const DESCRIPTION: &'static str = "Assess whether a dividend is sustainable. Takes dividends paid, net income and free cash flow for the same period (totals or per share) and optional dividend and earnings growth rates. Returns the payout ratio, FCF payout ratio and FCF coverage, the dividend vs earnings growth gap, component scores, a composite sustainability score (0-100), a rating (Highly Sustainable, Sustainable, At Risk, Unsustainable), sustainability issues and an interpretation. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "sustainability_score";
+    const BAND_FIELD: Option<&'static str> = Some("sustainability_rating");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("dividends_paid", InputUnit::Amount, &["dividends paid", "total dividends"]),
+        InputField::required("net_income", InputUnit::Amount, &["net income", "net profit", "net earnings"]),
+        InputField::required("free_cash_flow", InputUnit::Amount, &["free cash flow", "fcf"]),
+        InputField::optional("dividend_growth_rate", InputUnit::Ratio, &["dividend growth"]),
+        InputField::optional("earnings_growth_rate", InputUnit::Ratio, &["earnings growth", "net income growth", "eps growth"]),
+    ];
+
+    type Params = DividendSustainabilityParams;
+    type Response = DividendSustainabilityResponse;
+
+    fn calculate(params: DividendSustainabilityParams) -> Result<DividendSustainabilityResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = assess_dividend_sustainability_internal(&params, &mut fx, &mut units)
+            .map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_dividend_discount_gordon_growth() {
+        let params: DividendDiscountParams = serde_json::from_value(serde_json::json!({
+            "current_dividend": "2.00",
+            "required_return": 0.09,
+            "terminal_growth_rate": 0.04,
+            "current_price": 50
+        }))
+        .unwrap();
+
+        let response = DividendDiscount::calculate(params).unwrap();
+
+        // D1 = 2.08, value = 2.08 / (0.09 - 0.04) = 41.60
+        assert_eq!(response.model, "Gordon Growth");
+        assert_eq!(response.intrinsic_value, Decimal::new(416, 1));
+        assert!(response.dividend_schedule.is_empty());
+        assert_eq!(response.terminal_value_share_pct, 100.0);
+        assert_eq!(response.upside_pct, Some(-16.8));
+        // 2.08 / 50 + 4% = 8.16%
+        assert_eq!(response.implied_required_return_pct, Some(8.16));
+        assert_eq!(response.valuation_signal.as_deref(), Some("Overvalued"));
+    }
+
+    #[test]
+    fn test_calculate_dividend_discount_multi_stage() {
+        let params: DividendDiscountParams = serde_json::from_value(serde_json::json!({
+            "current_dividend": 1,
+            "required_return": 0.10,
+            "terminal_growth_rate": 0.05,
+            "stages": [{"years": 2, "growth_rate": 0.10}]
+        }))
+        .unwrap();
+
+        let response = DividendDiscount::calculate(params).unwrap();
+
+        // Dividends 1.10 and 1.21 discount to 1.00 each; TV = 1.21 x 1.05 / 0.05 = 25.41 -> PV 21.00
+        assert_eq!(response.model, "Multi-Stage");
+        assert_eq!(response.dividend_schedule.len(), 2);
+        assert_eq!(response.dividend_schedule[1].dividend, Decimal::new(121, 2));
+        assert_eq!(response.pv_of_stage_dividends, Decimal::TWO);
+        assert_eq!(response.terminal_value, Decimal::new(2541, 2));
+        assert_eq!(response.intrinsic_value, Decimal::new(23, 0));
+        assert_eq!(response.valuation_signal, None);
+    }
+
+    #[test]
+    fn test_calculate_dividend_discount_growth_above_return_errors() {
+        let params: DividendDiscountParams = serde_json::from_value(serde_json::json!({
+            "current_dividend": 1,
+            "required_return": 0.05,
+            "terminal_growth_rate": 0.06
+        }))
+        .unwrap();
+
+        assert!(DividendDiscount::calculate(params).is_err());
+    }
+
+    #[test]
+    fn test_assess_dividend_sustainability_covered() {
+        let params: DividendSustainabilityParams = serde_json::from_value(serde_json::json!({
+            "dividends_paid": 40,
+            "net_income": "100",
+            "free_cash_flow": 80,
+            "dividend_growth_rate": 0.05,
+            "earnings_growth_rate": 0.07
+        }))
+        .unwrap();
+
+        let response = DividendSustainability::calculate(params).unwrap();
+
+        assert_eq!(response.payout_ratio_pct, Some(40.0));
+        assert_eq!(response.fcf_payout_ratio_pct, Some(50.0));
+        assert_eq!(response.fcf_coverage, 2.0);
+        assert_eq!(response.growth_gap_pct, Some(-2.0));
+        assert_eq!(response.sustainability_score, 100.0);
+        assert_eq!(response.sustainability_rating, "Highly Sustainable");
+        assert!(response.sustainability_issues.is_empty());
+    }
+
+    #[test]
+    fn test_assess_dividend_sustainability_uncovered() {
+        let params: DividendSustainabilityParams = serde_json::from_value(serde_json::json!({
+            "dividends_paid": 110,
+            "net_income": 100,
+            "free_cash_flow": 90,
+            "dividend_growth_rate": 0.12,
+            "earnings_growth_rate": 0.02
+        }))
+        .unwrap();
+
+        let response = DividendSustainability::calculate(params).unwrap();
+
+        assert_eq!(response.payout_ratio_pct, Some(110.0));
+        // Only FCF coverage scores: (0.82 - 0.8) / 0.7 x 40%
+        assert_eq!(response.sustainability_score, 1.04);
+        assert_eq!(response.sustainability_rating, "Unsustainable");
+        assert!(response.sustainability_issues.iter().any(|i| i.contains("exceeds earnings")));
+        assert!(response.sustainability_issues.iter().any(|i| i.contains("0.82x")));
+        assert!(response.sustainability_issues.iter().any(|i| i.contains("10.0 points faster")));
+    }
+}
@@ -0,0 +1,308 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::calculation::{converter, fx_failure, money, Calculation};
+use super::error::FinanceError;
+use super::extraction::{InputField, InputUnit};
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::business_metrics::quality_grade;
+use super::money::{decimal_to_f64, round_ratio};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_opt};
+
+const ACCRUALS_WEIGHT: f64 = 0.40;
+const CASH_CONVERSION_WEIGHT: f64 = 0.40;
+const NON_RECURRING_WEIGHT: f64 = 0.20;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_earnings_quality
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct EarningsQualityParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Net income for the period")]
+    pub net_income: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Cash flow from operating activities for the period")]
+    pub operating_cash_flow: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Total assets at period end")]
+    pub total_assets: String,
+    #[schemars(description = "Total assets at the start of the period, for average assets (default: period end)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub prior_total_assets: Option<String>,
+    #[schemars(description = "Change in current assets; with change_current_liabilities selects the balance-sheet accrual method")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub change_current_assets: Option<String>,
+    #[schemars(description = "Change in cash and equivalents (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub change_cash: Option<String>,
+    #[schemars(description = "Change in current liabilities")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub change_current_liabilities: Option<String>,
+    #[schemars(description = "Change in short-term debt within current liabilities (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub change_short_term_debt: Option<String>,
+    #[schemars(description = "Change in income taxes payable (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub change_taxes_payable: Option<String>,
+    #[schemars(description = "Depreciation and amortization expense (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub depreciation: Option<String>,
+    #[schemars(description = "Non-recurring items included in net income: positive for one-off gains, negative for one-off charges")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub non_recurring_items: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct EarningsQualityResponse {
+    #[schemars(description = "Sloan accrual ratio as decimal: total accruals / average total assets")]
+    pub accrual_ratio: f64,
+    #[schemars(description = "Sloan accrual ratio as percentage")]
+    pub accrual_ratio_pct: f64,
+    #[schemars(description = "Accrual method used: balance_sheet or cash_flow")]
+    pub accrual_method: String,
+    #[schemars(description = "Total accruals used in the accrual ratio", with = "String")]
+    pub total_accruals: Decimal,
+    #[schemars(description = "Cash-flow accrual ratio (net income - operating cash flow) / average total assets as decimal")]
+    pub cash_flow_accrual_ratio: f64,
+    #[schemars(description = "Operating cash flow / net income (None when net income is not positive)")]
+    pub cash_conversion: Option<f64>,
+    #[schemars(description = "Absolute non-recurring items as percentage of net income, when given")]
+    pub non_recurring_share_pct: Option<f64>,
+    #[schemars(description = "Net income excluding non-recurring items", with = "String")]
+    pub recurring_net_income: Decimal,
+    #[schemars(description = "Component scores 0.0-1.0 for accruals, cash_conversion and non_recurring")]
+    pub components: HashMap<String, f64>,
+    #[schemars(description = "Composite earnings quality score 0.0-1.0 scale where 1.0 is perfect")]
+    pub quality_score: f64,
+    #[schemars(description = "Letter grade A through F based on quality score")]
+    pub grade: String,
+    #[schemars(description = "Actionable guidance based on the grade")]
+    pub recommendation: String,
+    #[schemars(description = "Warnings about accruals, cash backing and one-off items")]
+    pub earnings_quality_issues: Vec<String>,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+/// Score how well reported earnings are backed by cash and recurring activity
+pub fn calculate_earnings_quality_internal(
+    params: &EarningsQualityParams,
+    fx: &mut CurrencyConverter,
+) -> Result<EarningsQualityResponse, FinanceError> {
+    let mut amount = |value: Option<&str>, name: &str| value.map(|v| money(fx, v, name, Period::Current)).transpose();
+
+    let net_income = amount(Some(&params.net_income), "net_income")?.unwrap_or_default();
+    let operating_cash_flow = amount(Some(&params.operating_cash_flow), "operating_cash_flow")?.unwrap_or_default();
+    let total_assets = amount(Some(&params.total_assets), "total_assets")?.unwrap_or_default();
+    let prior_total_assets = amount(params.prior_total_assets.as_deref(), "prior_total_assets")?;
+    let change_current_assets = amount(params.change_current_assets.as_deref(), "change_current_assets")?;
+    let change_current_liabilities = amount(params.change_current_liabilities.as_deref(), "change_current_liabilities")?;
+    let change_cash = amount(params.change_cash.as_deref(), "change_cash")?.unwrap_or_default();
+    let change_short_term_debt = amount(params.change_short_term_debt.as_deref(), "change_short_term_debt")?.unwrap_or_default();
+    let change_taxes_payable = amount(params.change_taxes_payable.as_deref(), "change_taxes_payable")?.unwrap_or_default();
+    let depreciation = amount(params.depreciation.as_deref(), "depreciation")?.unwrap_or_default();
+    let non_recurring = amount(params.non_recurring_items.as_deref(), "non_recurring_items")?;
+
+    for (name, assets) in [("total_assets", Some(total_assets)), ("prior_total_assets", prior_total_assets)] {
+        if assets.is_some_and(|a| a <= Decimal::ZERO) {
+            return Err(FinanceError::out_of_range(name, Some(0.0), None, "Total assets must be positive"));
+        }
+    }
+    let average_assets = decimal_to_f64(prior_total_assets.map_or(total_assets, |prior| (total_assets + prior) / Decimal::TWO));
+
+    // Balance-sheet accruals (Sloan 1996) when the working capital changes are given
+    let cash_flow_accruals = net_income - operating_cash_flow;
+    let (total_accruals, accrual_method) = match (change_current_assets, change_current_liabilities) {
+        (Some(delta_ca), Some(delta_cl)) => (
+            (delta_ca - change_cash) - (delta_cl - change_short_term_debt - change_taxes_payable) - depreciation,
+            "balance_sheet",
+        ),
+        (None, None) => (cash_flow_accruals, "cash_flow"),
+        (delta_ca, _) => {
+            let missing = if delta_ca.is_none() { "change_current_assets" } else { "change_current_liabilities" };
+            return Err(FinanceError::insufficient_data(
+                "Provide both change_current_assets and change_current_liabilities for balance-sheet accruals",
+            )
+            .with_field(missing));
+        }
+    };
+    let accrual_ratio = decimal_to_f64(total_accruals) / average_assets;
+    let cash_flow_accrual_ratio = decimal_to_f64(cash_flow_accruals) / average_assets;
+
+    let cash_conversion = (net_income > Decimal::ZERO).then(|| decimal_to_f64(operating_cash_flow) / decimal_to_f64(net_income));
+    let non_recurring_share = non_recurring
+        .filter(|_| !net_income.is_zero())
+        .map(|items| decimal_to_f64(items.abs()) / decimal_to_f64(net_income.abs()));
+
+    // Accruals: -10% of assets scores 1.0, +10% scores 0.0
+    let accruals_score = ((0.10 - accrual_ratio) / 0.20).clamp(0.0, 1.0);
+    // Cash conversion: 1.2x scores 1.0, 0.5x or less scores 0.0; losses backed by cash score neutral
+    let cash_conversion_score = match cash_conversion {
+        Some(conversion) => ((conversion - 0.5) / 0.7).clamp(0.0, 1.0),
+        None if operating_cash_flow > Decimal::ZERO => 0.5,
+        None => 0.0,
+    };
+    let non_recurring_score = non_recurring_share.map(|share| (1.0 - share / 0.5).clamp(0.0, 1.0));
+
+    let mut components = HashMap::new();
+    components.insert("accruals".to_string(), round_ratio(accruals_score, 4));
+    components.insert("cash_conversion".to_string(), round_ratio(cash_conversion_score, 4));
+    let quality_score = match non_recurring_score {
+        Some(score) => {
+            components.insert("non_recurring".to_string(), round_ratio(score, 4));
+            accruals_score * ACCRUALS_WEIGHT + cash_conversion_score * CASH_CONVERSION_WEIGHT + score * NON_RECURRING_WEIGHT
+        }
+        // Without non-recurring data the other two components share its weight
+        None => (accruals_score + cash_conversion_score) / 2.0,
+    };
+
+    let grade = quality_grade(quality_score);
+    let recommendation = match grade {
+        "A" => "High earnings quality. Profits are backed by cash with low accruals and few one-off items.",
+        "B" => "Good earnings quality. Monitor working capital build-up and one-off items.",
+        "C" => "Moderate earnings quality. Reconcile the gap between profit and operating cash flow before relying on earnings.",
+        "D" => "Poor earnings quality. Earnings lean on accruals or one-off items; stress-test valuation on cash earnings.",
+        _ => "Critical earnings quality concerns. Reported profit is weakly backed by cash; investigate revenue recognition and reserves.",
+    };
+
+    let mut earnings_quality_issues = Vec::new();
+    if accrual_ratio > 0.10 {
+        earnings_quality_issues.push(format!(
+            "High accruals: {:.1}% of average assets, historically linked to weaker future earnings",
+            accrual_ratio * 100.0
+        ));
+    }
+    match cash_conversion {
+        Some(conversion) if conversion < 0.8 => earnings_quality_issues.push(format!(
+            "Low cash conversion: operating cash flow is {:.0}% of net income",
+            conversion * 100.0
+        )),
+        None => earnings_quality_issues.push("Net income is not positive; cash conversion is not meaningful".to_string()),
+        _ => {}
+    }
+    if operating_cash_flow < Decimal::ZERO && net_income > Decimal::ZERO {
+        earnings_quality_issues.push("Profitable on paper but operating cash flow is negative".to_string());
+    }
+    if let Some(share) = non_recurring_share
+        && share > 0.20
+    {
+        earnings_quality_issues.push(format!("Non-recurring items are {:.1}% of net income", share * 100.0));
+    }
+
+    Ok(EarningsQualityResponse {
+        accrual_ratio: round_ratio(accrual_ratio, 4),
+        accrual_ratio_pct: round_ratio(accrual_ratio * 100.0, 2),
+        accrual_method: accrual_method.to_string(),
+        total_accruals: fx.rounding().round(total_accruals),
+        cash_flow_accrual_ratio: round_ratio(cash_flow_accrual_ratio, 4),
+        cash_conversion: cash_conversion.map(|v| round_ratio(v, 2)),
+        non_recurring_share_pct: non_recurring_share.map(|s| round_ratio(s * 100.0, 2)),
+        recurring_net_income: fx.rounding().round(net_income - non_recurring.unwrap_or_default()),
+        components,
+        quality_score: round_ratio(quality_score, 4),
+        grade: grade.to_string(),
+        recommendation: recommendation.to_string(),
+        earnings_quality_issues,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Earnings quality and accruals (`calculate_earnings_quality`)
+pub struct EarningsQuality;
+
+impl Calculation for EarningsQuality {
+    const NAME: &'static str = "calculate_earnings_quality";
+    const DESCRIPTION: &'static str = "Evaluate earnings quality and accruals. Takes net income, operating cash flow, total assets (and prior total assets for the average), optional balance-sheet changes (current assets, cash, current liabilities, short-term debt, taxes payable, depreciation) and non-recurring items included in net income. Returns the Sloan accrual ratio (balance-sheet or cash-flow method), cash conversion of earnings, non-recurring item share, recurring net income, component scores, a composite quality score (0.0-1.0) with the same A-F grade scale as calculate_revenue_quality_score, a recommendation and earnings quality issues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "quality_score";
+    const BAND_FIELD: Option<&'static str> = Some("grade");
+    const INPUT_FIELDS: &'static [InputField] = &[
+        InputField::required("net_income", InputUnit::Amount, &["net income", "net profit", "net earnings"]),
+        InputField::required("operating_cash_flow", InputUnit::Amount, &["operating cash flow", "cash flow from operations", "cash from operations"]),
+        InputField::required("total_assets", InputUnit::Amount, &["total assets"]),
+        InputField::optional("prior_total_assets", InputUnit::Amount, &["prior year total assets", "opening total assets", "total assets at the start"]),
+        InputField::optional("depreciation", InputUnit::Amount, &["depreciation and amortization", "depreciation"]),
+        InputField::optional("non_recurring_items", InputUnit::Amount, &["non-recurring items", "one-off items", "one-time items"]),
+    ];
+
+    type Params = EarningsQualityParams;
+    type Response = EarningsQualityResponse;
+
+    fn calculate(params: EarningsQualityParams) -> Result<EarningsQualityResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut result = calculate_earnings_quality_internal(&params, &mut fx).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_earnings_quality_high_quality() {
+        let params: EarningsQualityParams = serde_json::from_value(serde_json::json!({
+            "net_income": 100,
+            "operating_cash_flow": "120",
+            "total_assets": 1100,
+            "prior_total_assets": 900,
+            "non_recurring_items": 10
+        }))
+        .unwrap();
+
+        let response = EarningsQuality::calculate(params).unwrap();
+
+        // Accruals (100 - 120) / average assets 1000 = -2%
+        assert_eq!(response.accrual_method, "cash_flow");
+        assert_eq!(response.accrual_ratio, -0.02);
+        assert_eq!(response.cash_conversion, Some(1.2));
+        assert_eq!(response.non_recurring_share_pct, Some(10.0));
+        assert_eq!(response.recurring_net_income, Decimal::from(90));
+        // 0.6 x 40% + 1.0 x 40% + 0.8 x 20% = 0.80
+        assert_eq!(response.quality_score, 0.8);
+        assert_eq!(response.grade, "A");
+        assert!(response.earnings_quality_issues.is_empty());
+    }
+
+    #[test]
+    fn test_calculate_earnings_quality_balance_sheet_accruals() {
+        let params: EarningsQualityParams = serde_json::from_value(serde_json::json!({
+            "net_income": 100,
+            "operating_cash_flow": 40,
+            "total_assets": 1000,
+            "change_current_assets": 150,
+            "change_cash": 10,
+            "change_current_liabilities": 20,
+            "change_short_term_debt": 5,
+            "depreciation": 30,
+            "non_recurring_items": -30
+        }))
+        .unwrap();
+
+        let response = EarningsQuality::calculate(params).unwrap();
+
+        // (150 - 10) - (20 - 5 - 0) - 30 = 95 -> 9.5% of assets
+        assert_eq!(response.accrual_method, "balance_sheet");
+        assert_eq!(response.total_accruals, Decimal::from(95));
+        assert_eq!(response.accrual_ratio_pct, 9.5);
+        assert_eq!(response.cash_flow_accrual_ratio, 0.06);
+        assert_eq!(response.recurring_net_income, Decimal::from(130));
+        assert_eq!(response.grade, "F");
+        assert!(response.earnings_quality_issues.iter().any(|i| i.contains("Low cash conversion")));
+        assert!(response.earnings_quality_issues.iter().any(|i| i.contains("Non-recurring items are 30.0%")));
+    }
+}
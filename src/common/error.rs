@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "mcp")]
+use rmcp::model::{CallToolResult, Content};
+
+#[cfg(feature = "mcp")]
+use super::metrics::increment_errors;
+
+// =================== DATA STRUCTURES ===================
+
+/// Stable, machine-readable error categories returned to clients and counted in metrics
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, schemars::JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// An argument is missing, malformed or inconsistent with the others
+    InvalidInput,
+    /// A numeric argument falls outside its allowed range
+    OutOfRange,
+    /// Parts that must add up to a whole do not (consideration mix, weights, shares)
+    SumMismatch,
+    /// Too few values or periods for the calculation
+    InsufficientData,
+    /// A dependency such as the vector store or a data file could not be reached or read
+    UpstreamUnavailable,
+    /// The engine failed to produce a response
+    Internal,
+}
+
+impl ErrorCode {
+    /// Label used for the code in metrics and logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidInput => "INVALID_INPUT",
+            ErrorCode::OutOfRange => "OUT_OF_RANGE",
+            ErrorCode::SumMismatch => "SUM_MISMATCH",
+            ErrorCode::InsufficientData => "INSUFFICIENT_DATA",
+            ErrorCode::UpstreamUnavailable => "UPSTREAM_UNAVAILABLE",
+            ErrorCode::Internal => "INTERNAL",
+        }
+    }
+}
+
+/// Allowed bounds of a numeric argument; a missing side is unbounded
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct AllowedRange {
+    #[schemars(description = "Smallest allowed value")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[schemars(description = "Largest allowed value")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+/// Error returned by every calculation, serialized as the body of an error tool result
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct FinanceError {
+    #[schemars(description = "Machine-readable error code")]
+    pub code: ErrorCode,
+    #[schemars(description = "Human-readable description of the error")]
+    pub message: String,
+    #[schemars(description = "Argument that caused the error, when one did")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    #[schemars(description = "Allowed range of the argument, for OUT_OF_RANGE errors")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_range: Option<AllowedRange>,
+}
+
+// =================== CONSTRUCTION ===================
+
+impl FinanceError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            field: None,
+            allowed_range: None,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    /// An argument that could not be parsed, reported as "Invalid <field>: <reason>"
+    pub fn invalid_field(field: &str, reason: impl fmt::Display) -> Self {
+        Self::invalid_input(format!("Invalid {}: {}", field, reason)).with_field(field)
+    }
+
+    /// A numeric argument outside `[min, max]`; pass `None` for an open side
+    pub fn out_of_range(field: &str, min: Option<f64>, max: Option<f64>, message: impl Into<String>) -> Self {
+        Self {
+            allowed_range: Some(AllowedRange { min, max }),
+            ..Self::new(ErrorCode::OutOfRange, message).with_field(field)
+        }
+    }
+
+    pub fn sum_mismatch(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::SumMismatch, message)
+    }
+
+    pub fn insufficient_data(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InsufficientData, message)
+    }
+
+    pub fn upstream_unavailable(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::UpstreamUnavailable, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Name the offending argument, keeping a more specific one already set
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field.get_or_insert_with(|| field.to_string());
+        self
+    }
+
+    /// Prefix the message with where the error happened ("Base case failed: ...")
+    pub fn context(mut self, prefix: &str) -> Self {
+        self.message = format!("{}: {}", prefix, self.message);
+        self
+    }
+
+    /// Count the error by code and render it as an error tool result, carrying the error both as
+    /// pretty-printed JSON text and as `structuredContent`
+    #[cfg(feature = "mcp")]
+    pub fn into_tool_result(self) -> CallToolResult {
+        increment_errors(self.code);
+        let body = serde_json::to_string_pretty(&self).unwrap_or_else(|_| self.message.clone());
+        CallToolResult {
+            structured_content: serde_json::to_value(&self).ok(),
+            ..CallToolResult::error(vec![Content::text(body)])
+        }
+    }
+}
+
+impl fmt::Display for FinanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
@@ -0,0 +1,376 @@
+use serde::{Deserialize, Serialize};
+
+use super::currency::{parse_currency_amount, CURRENCY_SYMBOLS, ISO_CURRENCY_CODES};
+use super::error::FinanceError;
+use super::parsing::{parse_f64_as, NumberUnit, MAGNITUDE_SUFFIXES};
+
+/// Longest stretch of text after a label that is searched for its value, in bytes
+const MAX_VALUE_DISTANCE: usize = 120;
+/// Unit notations recognized after a number besides magnitudes, longest first
+const UNIT_SUFFIXES: [&str; 7] = ["percent", "pct", "bps", "bp", "%", "x", "×"];
+/// Four-digit numbers in this range written without a unit are taken to be years, not values
+const YEARS: std::ops::RangeInclusive<u32> = 1900..=2100;
+/// Words just before a label that put its value in an earlier period ("prior year total assets")
+const PRIOR_PERIOD_QUALIFIERS: [&str; 4] = ["prior", "previous", "preceding", "last"];
+
+// =================== DATA STRUCTURES ===================
+
+/// What an input holds, which decides which numbers in the text can be its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputUnit {
+    /// A monetary amount, kept as written so the calculation parses its currency and magnitude
+    Amount,
+    /// A fraction such as a growth rate or margin ("9%", "900bps", "0.09"), written as a number
+    Ratio,
+    /// A value on a 0-100 scale, written as a number
+    Score,
+    /// A multiple such as leverage or coverage ("3.2x"), written as a number
+    Multiple,
+}
+
+/// A calculation argument that can be read from document text, found after one of its labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputField {
+    pub name: &'static str,
+    pub unit: InputUnit,
+    /// Lowercase phrases written before the value, in order of preference
+    pub labels: &'static [&'static str],
+    pub required: bool,
+}
+
+impl InputField {
+    pub const fn required(name: &'static str, unit: InputUnit, labels: &'static [&'static str]) -> Self {
+        Self { name, unit, labels, required: true }
+    }
+
+    pub const fn optional(name: &'static str, unit: InputUnit, labels: &'static [&'static str]) -> Self {
+        Self { name, unit, labels, required: false }
+    }
+}
+
+/// Text of one retrieved document chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceChunk {
+    pub file_id: String,
+    pub filename: String,
+    pub score: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct InputSource {
+    #[schemars(description = "Position of the chunk in the retrieved results")]
+    pub chunk_index: usize,
+    #[schemars(description = "File ID of the chunk in the vector store")]
+    pub file_id: String,
+    #[schemars(description = "Filename of the source document")]
+    pub filename: String,
+    #[schemars(description = "Similarity score of the chunk (0.0-1.0)")]
+    pub score: f64,
+    #[schemars(description = "Text the value was read from, from its label to the number")]
+    pub excerpt: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ExtractedInput {
+    #[schemars(description = "Calculation argument")]
+    pub field: String,
+    #[schemars(description = "Value passed to the calculation")]
+    pub value: serde_json::Value,
+    #[schemars(description = "Chunk the value was read from; absent when it was given in the arguments")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<InputSource>,
+}
+
+/// Calculation arguments assembled from the given arguments and the retrieved chunks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedArguments {
+    pub arguments: serde_json::Value,
+    pub inputs: Vec<ExtractedInput>,
+}
+
+// =================== EXTRACTION ===================
+
+/// Complete `arguments` with every other field found in `chunks`, recording where each came from.
+///
+/// Chunks are searched from the highest score down and a field takes the first number after one
+/// of its labels that fits its unit. Given arguments are used as they are. Fails with
+/// INSUFFICIENT_DATA naming every required field that is neither given nor found.
+pub fn extract_inputs(
+    fields: &[InputField],
+    chunks: &[SourceChunk],
+    mut arguments: serde_json::Map<String, serde_json::Value>,
+) -> Result<ExtractedArguments, FinanceError> {
+    let mut inputs: Vec<ExtractedInput> = arguments
+        .iter()
+        .map(|(field, value)| ExtractedInput { field: field.clone(), value: value.clone(), source: None })
+        .collect();
+
+    let mut by_score: Vec<usize> = (0..chunks.len()).collect();
+    by_score.sort_by(|&a, &b| chunks[b].score.total_cmp(&chunks[a].score));
+
+    let mut missing: Vec<&str> = Vec::new();
+    for field in fields {
+        if arguments.contains_key(field.name) {
+            continue;
+        }
+        let found = by_score
+            .iter()
+            .find_map(|&index| find_value(field, &chunks[index].text).map(|(value, excerpt)| (index, value, excerpt)));
+        match found {
+            Some((index, value, excerpt)) => {
+                let chunk = &chunks[index];
+                arguments.insert(field.name.to_string(), value.clone());
+                inputs.push(ExtractedInput {
+                    field: field.name.to_string(),
+                    value,
+                    source: Some(InputSource {
+                        chunk_index: index,
+                        file_id: chunk.file_id.clone(),
+                        filename: chunk.filename.clone(),
+                        score: chunk.score,
+                        excerpt,
+                    }),
+                });
+            }
+            None if field.required => missing.push(field.name),
+            None => {}
+        }
+    }
+
+    if let Some(first) = missing.first() {
+        return Err(FinanceError::insufficient_data(format!(
+            "Could not find {} in the retrieved chunks; pass the missing values in arguments",
+            missing.join(", ")
+        ))
+        .with_field(first));
+    }
+
+    Ok(ExtractedArguments { arguments: serde_json::Value::Object(arguments), inputs })
+}
+
+/// First value of `field` in `text` with the excerpt it was read from
+fn find_value(field: &InputField, text: &str) -> Option<(serde_json::Value, String)> {
+    // ASCII lowercasing keeps byte offsets valid in the original text
+    let lower = text.to_ascii_lowercase();
+    for label in field.labels {
+        for (start, _) in lower.match_indices(label) {
+            let end = start + label.len();
+            let before = lower[..start].chars().next_back();
+            let after = lower[end..].chars().next();
+            if before.is_some_and(char::is_alphanumeric) || after.is_some_and(char::is_alphanumeric) {
+                continue;
+            }
+            if prior_period_qualified(&lower[..start]) {
+                continue;
+            }
+
+            let window = value_window(text, end);
+            for (token_start, token_end) in number_candidates(window) {
+                if let Some(value) = read_value(&window[token_start..token_end], field.unit) {
+                    let excerpt = text[start..end + token_end].split_whitespace().collect::<Vec<_>>().join(" ");
+                    return Some((value, excerpt));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Whether the words right before a label name an earlier period, so "prior year total assets"
+/// or "last year's revenue" is not read as the current figure
+fn prior_period_qualified(preceding: &str) -> bool {
+    let clause = preceding.rsplit(['\n', ';', ':', '.', ',']).next().unwrap_or_default();
+    let words: Vec<&str> = clause.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    words[words.len().saturating_sub(3)..]
+        .iter()
+        .any(|word| PRIOR_PERIOD_QUALIFIERS.contains(word))
+}
+
+/// Text after a label up to the end of its line, clause or sentence
+fn value_window(text: &str, from: usize) -> &str {
+    let rest = &text[from..];
+    let mut chars = rest.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let sentence_end = c == '.' && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+        if i >= MAX_VALUE_DISTANCE || c == '\n' || c == ';' || sentence_end {
+            return &rest[..i];
+        }
+    }
+    rest
+}
+
+/// Byte ranges of the numbers in `window` with the currency, sign and unit written around them,
+/// longest reading first for each number
+fn number_candidates(window: &str) -> Vec<(usize, usize)> {
+    let bytes = window.as_bytes();
+    let mut candidates = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        // Digits with grouping and decimal separators: "1,234.5", "1.234,5", "1'234"
+        let digits_start = i;
+        let mut end = i + 1;
+        while end < bytes.len() {
+            if bytes[end].is_ascii_digit() {
+                end += 1;
+            } else if matches!(bytes[end], b',' | b'.' | b'\'') && bytes.get(end + 1).is_some_and(u8::is_ascii_digit) {
+                end += 2;
+            } else {
+                break;
+            }
+        }
+        let digits_end = end;
+        i = end;
+
+        // Currency symbol or ISO code before the number: "$1.2M", "EUR 1.200"
+        let mut start = digits_start;
+        let before = &window[..digits_start];
+        let tagged = before.strip_suffix(' ').unwrap_or(before);
+        if let Some((symbol, _)) = CURRENCY_SYMBOLS.iter().find(|(symbol, _)| tagged.ends_with(symbol)) {
+            start = tagged.len() - symbol.len();
+        } else if let Some(code_start) = iso_code_before(tagged) {
+            start = code_start;
+        } else if before.chars().next_back().is_some_and(char::is_alphabetic) {
+            // Part of a word such as "FY2024" or "Q3"
+            continue;
+        }
+
+        // Unit after the number, optionally after a space: "9%", "12.5M", "3.2x", "1.2 billion"
+        let rest = &window[digits_end..];
+        let spaced = rest.trim_start_matches(' ');
+        let spaced_lower = spaced.to_ascii_lowercase();
+        let unit = UNIT_SUFFIXES
+            .iter()
+            .chain(MAGNITUDE_SUFFIXES.iter().map(|(suffix, _)| suffix))
+            .find(|suffix| {
+                spaced_lower.starts_with(**suffix)
+                    && !spaced_lower[suffix.len()..].chars().next().is_some_and(char::is_alphanumeric)
+            });
+        match unit {
+            Some(suffix) => end = digits_end + (rest.len() - spaced.len()) + suffix.len(),
+            // Part of a word such as "3rd" or "10K-filing"
+            None if rest.chars().next().is_some_and(char::is_alphabetic) => continue,
+            None => {}
+        }
+
+        // Sign, or accounting parentheses when they close after the number
+        if start > 0 && bytes[start - 1] == b'-' {
+            start -= 1;
+        } else if start > 0 && bytes[start - 1] == b'(' && window[end..].starts_with(')') {
+            start -= 1;
+            end += 1;
+        }
+
+        let bare = start == digits_start && end == digits_end;
+        let is_year = digits_end - digits_start == 4
+            && window[digits_start..digits_end].parse::<u32>().is_ok_and(|year| YEARS.contains(&year));
+        if bare && is_year {
+            continue;
+        }
+
+        // ISO code after the number: "950 GBP"
+        let after = &window[end..];
+        let code = after.trim_start_matches(' ');
+        if code.get(..3).is_some_and(|c| ISO_CURRENCY_CODES.contains(&c))
+            && !code[3..].chars().next().is_some_and(char::is_alphanumeric)
+        {
+            candidates.push((start, end + (after.len() - code.len()) + 3));
+        }
+        candidates.push((start, end));
+    }
+    candidates
+}
+
+/// Start of an upper-case ISO currency code ending `text`, if there is one
+fn iso_code_before(text: &str) -> Option<usize> {
+    let code_start = text.len().checked_sub(3)?;
+    let code = text.get(code_start..)?;
+    let standalone = !text[..code_start].chars().next_back().is_some_and(char::is_alphanumeric);
+    (standalone && ISO_CURRENCY_CODES.contains(&code)).then_some(code_start)
+}
+
+/// The argument value of a number read as `unit`, or `None` when it cannot be one
+fn read_value(token: &str, unit: InputUnit) -> Option<serde_json::Value> {
+    let value = match unit {
+        InputUnit::Amount => {
+            return parse_currency_amount(token, None).ok().map(|_| serde_json::Value::String(token.to_string()));
+        }
+        // A bare "9" for a growth rate is ambiguous, so only numbers that read as plausible count
+        InputUnit::Ratio => parse_f64_as(token, NumberUnit::Ratio, None).ok().filter(|n| n.warning.is_none())?.value,
+        InputUnit::Score => parse_f64_as(token, NumberUnit::Percentage, None).ok().filter(|n| n.warning.is_none())?.value,
+        InputUnit::Multiple => {
+            let number = token.trim_end_matches(['x', 'X', '×']).trim_end();
+            parse_f64_as(number, NumberUnit::Amount, None).ok()?.value
+        }
+    };
+    // Percent conversion leaves binary noise (9.5% -> 0.09500000000000001)
+    serde_json::Number::from_f64((value * 1e10).round() / 1e10).map(serde_json::Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::calculation::{calculation_input_fields, run_calculation_internal};
+    use crate::common::error::ErrorCode;
+
+    #[test]
+    fn test_extract_inputs() {
+        let chunk = |file_id: &str, score: f64, text: &str| SourceChunk {
+            file_id: file_id.to_string(),
+            filename: format!("{}.pdf", file_id),
+            score,
+            text: text.to_string(),
+        };
+        let chunks = vec![
+            chunk("q3-deck", 0.82, "FY2024 highlights: customer satisfaction reached 89 points; revenue growth of 4% in Q2."),
+            chunk("annual-report", 0.91, "In 2024 revenue growth was 9% year over year.\nSLA compliance held at 98.5% across regions."),
+        ];
+        let fields = calculation_input_fields("calculate_company_health_score").unwrap();
+        let extracted = extract_inputs(fields, &chunks, serde_json::Map::new()).unwrap();
+        assert_eq!(
+            extracted.arguments,
+            serde_json::json!({ "revenue_growth": 0.09, "sla_compliance": 0.985, "customer_satisfaction": 89.0 })
+        );
+
+        // Each input cites the best-scoring chunk it was found in
+        let source = |field: &str| extracted.inputs.iter().find(|i| i.field == field).unwrap().source.clone().unwrap();
+        assert_eq!(source("revenue_growth").chunk_index, 1);
+        assert_eq!(source("revenue_growth").excerpt, "revenue growth was 9%");
+        assert_eq!(source("customer_satisfaction").filename, "q3-deck.pdf");
+        let result = run_calculation_internal("calculate_company_health_score", extracted.arguments).unwrap();
+        assert_eq!(result["risk_level"], serde_json::json!("LOW"));
+
+        // Given arguments win over the documents; amounts keep their currency notation
+        let chunks = vec![chunk("10-k", 0.9, "Prior year revenue: $100M. Current year revenue (USD): 112 million USD.")];
+        let fields = calculation_input_fields("calculate_organic_growth").unwrap();
+        let extracted = extract_inputs(fields, &chunks, serde_json::Map::new()).unwrap();
+        assert_eq!(extracted.arguments["revenue_prior"], serde_json::json!("$100M"));
+        assert_eq!(extracted.arguments["revenue_current"], serde_json::json!("112 million USD"));
+        let given = serde_json::json!({ "revenue_prior": "105" }).as_object().cloned().unwrap();
+        let extracted = extract_inputs(fields, &chunks, given).unwrap();
+        assert_eq!(extracted.arguments["revenue_prior"], serde_json::json!("105"));
+        assert!(extracted.inputs.iter().any(|i| i.field == "revenue_prior" && i.source.is_none()));
+
+        let error = extract_inputs(fields, &chunks[..0], serde_json::Map::new()).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InsufficientData);
+        assert_eq!(error.field.as_deref(), Some("revenue_prior"));
+        assert!(error.message.contains("revenue_prior, revenue_current"));
+
+        // A label qualified by an earlier period is not read as the current figure
+        let chunks = vec![chunk(
+            "balance-sheet",
+            0.9,
+            "Prior year total assets 1,200. Last year's net income was 90.\nTotal assets 1,500, net income 110; operating cash flow 130.",
+        )];
+        let fields = calculation_input_fields("calculate_earnings_quality").unwrap();
+        let extracted = extract_inputs(fields, &chunks, serde_json::Map::new()).unwrap();
+        assert_eq!(extracted.arguments["total_assets"], serde_json::json!("1,500"));
+        assert_eq!(extracted.arguments["prior_total_assets"], serde_json::json!("1,200"));
+        assert_eq!(extracted.arguments["net_income"], serde_json::json!("110"));
+    }
+}
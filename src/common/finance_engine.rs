@@ -1,363 +1,32 @@
-use serde::{Deserialize, Deserializer, Serialize, de};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fmt;
 use std::env;
+use std::sync::Arc;
 
-use super::metrics::{increment_requests, increment_errors, RequestTimer};
+use super::calculation::{
+    calculation_input_fields, calculation_names, calculation_output_fields, for_each_calculation,
+    run_calculation_internal, Calculation, CalculationVisitor,
+};
+use super::error::FinanceError;
+use super::extraction::{extract_inputs, ExtractedInput, SourceChunk};
+use super::metrics::{increment_errors, increment_requests, RequestTimer};
+use super::parsing::deserialize_bool_flexible_opt;
+use super::batch::{batch_calculate_internal, BatchCalculateParams, BatchCalculateResponse};
+use super::scenario::{analyze_scenarios_internal, ScenarioAnalysisParams, ScenarioAnalysisResponse};
+use super::simulation::{simulate_internal, SimulationParams, SimulationResponse};
 
 use rmcp::{
     ServerHandler,
-    handler::server::router::tool::ToolRouter,
+    handler::server::router::tool::{ToolRoute, ToolRouter},
+    handler::server::tool::{schema_for_output, schema_for_type},
     handler::server::wrapper::Parameters,
-    model::{ServerCapabilities, ServerInfo, CallToolResult, Content},
+    model::{ServerCapabilities, ServerInfo, CallToolResult, Content, JsonObject, Tool},
     ErrorData as McpError,
     schemars, tool, tool_handler, tool_router
 };
 
-// =================== PARSING UTILITIES ===================
-
-/// Sanitize user input for safe inclusion in error messages
-fn sanitize_for_error_message(input: &str) -> String {
-    let truncated = if input.len() > 50 { 
-        format!("{}...", &input[..47])
-    } else { 
-        input.to_string() 
-    };
-    
-    truncated
-        .chars()
-        .map(|c| match c {
-            '\n' | '\r' | '\t' => ' ',
-            '"' | '\'' | '`' => '?',
-            '\\' => '?',
-            '<' | '>' => '?',
-            c if c.is_ascii_graphic() || c == ' ' => c,
-            _ => '?'
-        })
-        .collect()
-}
-
-/// Validate input length and format for security
-fn validate_input_security(input: &str, field_name: &str) -> Result<(), String> {
-    if input.len() > 100 {
-        return Err(format!("Invalid {}: input too long (max 100 characters)", field_name));
-    }
-    
-    if input.contains('\0') {
-        return Err(format!("Invalid {}: input contains null bytes", field_name));
-    }
-    
-    let control_char_count = input.chars().filter(|c| c.is_control()).count();
-    if control_char_count > 2 {
-        return Err(format!("Invalid {}: input contains too many control characters", field_name));
-    }
-    
-    Ok(())
-}
-
-/// Parse a string to f64
-fn parse_f64_from_string(s: &str) -> Result<f64, String> {
-    let trimmed = s.trim();
-    
-    if let Err(e) = validate_input_security(trimmed, "number") {
-        return Err(e);
-    }
-    
-    if trimmed.is_empty() {
-        return Err("Empty string cannot be parsed as number".to_string());
-    }
-    
-    let sanitized = sanitize_for_error_message(trimmed);
-    
-    let cleaned = trimmed
-        .replace(',', "")
-        .replace('$', "")
-        .replace('€', "")
-        .replace('£', "")
-        .replace('¥', "")
-        .replace('%', "");
-    
-    match cleaned.parse::<f64>() {
-        Ok(value) => {
-            if value.is_infinite() || value.is_nan() {
-                Err(format!("Invalid number: '{}'", sanitized))
-            } else {
-                Ok(value)
-            }
-        },
-        Err(_) => Err(format!("Cannot parse '{}' as a number", sanitized))
-    }
-}
-
-// =================== CUSTOM DESERIALIZERS ===================
-
-/// Custom deserializer that accepts both f64 numbers and strings
-fn deserialize_flexible_f64<'de, D>(deserializer: D) -> Result<String, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct FlexibleF64Visitor;
-
-    impl<'de> de::Visitor<'de> for FlexibleF64Visitor {
-        type Value = String;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a number or a string representing a number")
-        }
-
-        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
-        }
-
-        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
-        }
-
-        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
-        }
-
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value.to_string())
-        }
-
-        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value)
-        }
-    }
-
-    deserializer.deserialize_any(FlexibleF64Visitor)
-}
-
 // =================== DATA STRUCTURES ===================
 
-// Function: calculate_company_health_score
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct CompanyHealthScoreParams {
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Year-over-year revenue growth rate as decimal (e.g., 0.09 for 9%)")]
-    pub revenue_growth: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Service Level Agreement compliance rate as decimal (e.g., 0.985 for 98.5%)")]
-    pub sla_compliance: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Customer satisfaction score on 0-100 scale")]
-    pub customer_satisfaction: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct CompanyHealthScoreResponse {
-    #[schemars(description = "Composite health score 0-100")]
-    pub overall_score: f64,
-    #[schemars(description = "Individual dimension scores before weighting")]
-    pub components: HashMap<String, f64>,
-    #[schemars(description = "Point contribution of each dimension to final score")]
-    pub weighted_contributions: HashMap<String, f64>,
-    #[schemars(description = "Risk level: LOW, MEDIUM, HIGH, or CRITICAL")]
-    pub risk_level: String,
-    #[schemars(description = "Human-readable assessment of health status")]
-    pub interpretation: String,
-}
-
-// Function: calculate_revenue_quality_score
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct RevenueQualityScoreParams {
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Dollar amount of revenue growing above 15% year-over-year")]
-    pub high_growth_revenue: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Dollar amount of revenue growing 0-15% year-over-year")]
-    pub stable_revenue: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Dollar amount of revenue with negative year-over-year growth")]
-    pub declining_revenue: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Total company revenue for normalization")]
-    pub total_revenue: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct RevenueQualityScoreResponse {
-    #[schemars(description = "Composite quality score 0.0-1.0 scale where 1.0 is perfect")]
-    pub quality_score: f64,
-    #[schemars(description = "Percentage breakdown of revenue by growth category")]
-    pub distribution: HashMap<String, f64>,
-    #[schemars(description = "Letter grade A through F based on quality score")]
-    pub grade: String,
-    #[schemars(description = "Actionable strategic guidance based on score")]
-    pub recommendation: String,
-    #[schemars(description = "Industry benchmark for comparison")]
-    pub target_score: f64,
-    #[schemars(description = "Distance from benchmark, negative means exceeding target")]
-    pub gap_to_target: f64,
-}
-
-// Function: calculate_hhi_and_diversification
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct HHIParams {
-    #[schemars(description = "Revenue values for each business segment")]
-    pub revenues: Vec<f64>,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct HHIResponse {
-    #[schemars(description = "Herfindahl-Hirschman Index value 0.0-1.0")]
-    pub hhi: f64,
-    #[schemars(description = "Inverse of HHI, where higher means more diversified")]
-    pub diversification_score: f64,
-    #[schemars(description = "Effective number of equal-sized segments")]
-    pub effective_n: f64,
-    #[schemars(description = "Risk level: LOW, MEDIUM, or HIGH")]
-    pub risk_level: String,
-    #[schemars(description = "Risk interpretation in plain language")]
-    pub assessment: String,
-    #[schemars(description = "Individual segment shares as decimals")]
-    pub market_shares: Vec<f64>,
-    #[schemars(description = "Highest individual segment share")]
-    pub largest_share: f64,
-    #[schemars(description = "Specific warnings about concentration risks")]
-    pub concentration_issues: Vec<String>,
-}
-
-// Function: calculate_operating_leverage
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct OperatingLeverageParams {
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Year-over-year revenue growth rate as decimal (e.g., 0.09 for 9%)")]
-    pub revenue_growth_rate: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Year-over-year operating cost growth rate as decimal (e.g., 0.06 for 6%)")]
-    pub cost_growth_rate: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct OperatingLeverageResponse {
-    #[schemars(description = "Operating leverage ratio (revenue growth / cost growth)")]
-    pub operating_leverage: f64,
-    #[schemars(description = "Revenue growth rate as percentage")]
-    pub revenue_growth_pct: f64,
-    #[schemars(description = "Cost growth rate as percentage")]
-    pub cost_growth_pct: f64,
-    #[schemars(description = "Margin expansion in basis points")]
-    pub margin_expansion_bps: f64,
-    #[schemars(description = "Efficiency rating: Excellent, Good, Adequate, or Poor")]
-    pub efficiency_rating: String,
-    #[schemars(description = "Plain language interpretation of the leverage")]
-    pub interpretation: String,
-}
-
-// Function: calculate_portfolio_momentum
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct PortfolioSegmentData {
-    #[schemars(description = "Segment revenue in millions")]
-    pub revenue: f64,
-    #[schemars(description = "Year-over-year growth rate as decimal (e.g., 0.20 for 20%)")]
-    pub growth_rate: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct PortfolioMomentumParams {
-    #[schemars(description = "Dictionary of segment names to revenue and growth rate data")]
-    pub segments: HashMap<String, PortfolioSegmentData>,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct SegmentMomentumContribution {
-    #[schemars(description = "Segment revenue")]
-    pub revenue: f64,
-    #[schemars(description = "Segment revenue as percentage of total")]
-    pub revenue_pct: f64,
-    #[schemars(description = "Segment growth rate as percentage")]
-    pub growth_rate: f64,
-    #[schemars(description = "Contribution to overall momentum as percentage")]
-    pub contribution_to_momentum: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct PortfolioMomentumResponse {
-    #[schemars(description = "Portfolio momentum as decimal")]
-    pub portfolio_momentum: f64,
-    #[schemars(description = "Portfolio momentum as percentage")]
-    pub portfolio_momentum_pct: f64,
-    #[schemars(description = "Total revenue across all segments")]
-    pub total_revenue: f64,
-    #[schemars(description = "Individual segment contributions to momentum")]
-    pub segment_contributions: HashMap<String, SegmentMomentumContribution>,
-    #[schemars(description = "Name of segment contributing most to momentum")]
-    pub top_contributor: String,
-    #[schemars(description = "Momentum rating: Strong, Moderate, Weak, or Declining")]
-    pub momentum_rating: String,
-}
-
-// Function: calculate_gini_coefficient
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct GiniCoefficientParams {
-    #[schemars(description = "List of revenue values by segment (any order)")]
-    pub revenues: Vec<f64>,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct GiniCoefficientResponse {
-    #[schemars(description = "Gini coefficient (0-1 scale, higher = more concentrated)")]
-    pub gini_coefficient: f64,
-    #[schemars(description = "Diversification score (1 - Gini, higher = more diversified)")]
-    pub diversification_score: f64,
-    #[schemars(description = "Concentration level: Low, Moderate, or High")]
-    pub concentration_level: String,
-    #[schemars(description = "Largest segment share as percentage")]
-    pub largest_segment_share: f64,
-    #[schemars(description = "Smallest segment share as percentage")]
-    pub smallest_segment_share: f64,
-    #[schemars(description = "Effective number of equal-sized segments")]
-    pub effective_segments: f64,
-    #[schemars(description = "Revenue values sorted in ascending order")]
-    pub sorted_revenues: Vec<f64>,
-}
-
-// Function 11: calculate_organic_growth
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct OrganicGrowthParams {
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Revenue from prior period")]
-    pub revenue_prior: String,
-    #[serde(deserialize_with = "deserialize_flexible_f64")]
-    #[schemars(description = "Revenue from current period")]
-    pub revenue_current: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
-pub struct OrganicGrowthResponse {
-    #[schemars(description = "Organic growth rate as decimal")]
-    pub organic_growth_rate: f64,
-    #[schemars(description = "Organic growth rate as percentage")]
-    pub organic_growth_pct: f64,
-    #[schemars(description = "Absolute dollar growth")]
-    pub absolute_growth: f64,
-    #[schemars(description = "Prior period revenue")]
-    pub revenue_prior: f64,
-    #[schemars(description = "Current period revenue")]
-    pub revenue_current: f64,
-    #[schemars(description = "Growth rating: Exceptional, Strong, Moderate, Weak, or Declining")]
-    pub growth_rating: String,
-    #[schemars(description = "Annualized CAGR as percentage")]
-    pub annualized_cagr: f64,
-}
-
 // Function 12: get_metrics_from_vector_store
 #[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
 pub struct VectorStoreQueryParams {
@@ -379,113 +48,6 @@ pub struct VectorStoreQueryParams {
     pub rewrite_query: Option<bool>,
 }
 
-/// Custom deserializer for optional boolean that accepts both boolean and string representations
-fn deserialize_bool_flexible_opt<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    use serde::de::{self, Visitor};
-    use std::fmt;
-
-    struct BoolVisitor;
-
-    impl<'de> Visitor<'de> for BoolVisitor {
-        type Value = Option<bool>;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a boolean or a string representing a boolean")
-        }
-
-        fn visit_none<E>(self) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(None)
-        }
-
-        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
-        where
-            D: serde::Deserializer<'de>,
-        {
-            deserializer.deserialize_any(InnerBoolVisitor).map(Some)
-        }
-
-        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(Some(value))
-        }
-
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            match value.to_lowercase().as_str() {
-                "true" | "1" | "yes" => Ok(Some(true)),
-                "false" | "0" | "no" => Ok(Some(false)),
-                _ => Err(de::Error::custom(format!(
-                    "invalid boolean string: {}",
-                    value
-                ))),
-            }
-        }
-
-        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            self.visit_str(&value)
-        }
-
-        fn visit_unit<E>(self) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(None)
-        }
-    }
-
-    struct InnerBoolVisitor;
-
-    impl<'de> Visitor<'de> for InnerBoolVisitor {
-        type Value = bool;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a boolean or a string representing a boolean")
-        }
-
-        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value)
-        }
-
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            match value.to_lowercase().as_str() {
-                "true" | "1" | "yes" => Ok(true),
-                "false" | "0" | "no" => Ok(false),
-                _ => Err(de::Error::custom(format!(
-                    "invalid boolean string: {}",
-                    value
-                ))),
-            }
-        }
-
-        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            self.visit_str(&value)
-        }
-    }
-
-    deserializer.deserialize_option(BoolVisitor)
-}
 
 fn default_max_results() -> Option<usize> {
     None
@@ -532,6 +94,48 @@ pub struct VectorStoreQueryResponse {
     pub query: String,
 }
 
+// Function: run_calculation_pipeline
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CalculationPipelineParams {
+    #[schemars(description = "Name of the calculation to run (e.g., 'calculate_company_health_score', 'calculate_organic_growth')")]
+    pub function_name: String,
+    #[schemars(description = "Company name to query metrics for")]
+    pub company_name: String,
+    #[schemars(description = "Arguments used as given instead of being extracted (e.g., values missing from the documents, 'locale' or currency options)")]
+    #[serde(default)]
+    pub arguments: Option<serde_json::Value>,
+    #[schemars(description = "Maximum number of chunks to retrieve (default: 5)")]
+    #[serde(default = "default_max_results")]
+    pub max_num_results: Option<usize>,
+    #[schemars(description = "Minimum score threshold for chunks (default: 0.8)")]
+    #[serde(default = "default_score_threshold_opt")]
+    pub score_threshold: Option<f64>,
+    #[schemars(description = "Ranker to use for scoring (default: 'default')")]
+    #[serde(default = "default_ranker_opt")]
+    pub ranker: Option<String>,
+    #[schemars(description = "Whether to rewrite the query (default: false)")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt")]
+    pub rewrite_query: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct CalculationPipelineResponse {
+    #[schemars(description = "Calculation that was run")]
+    pub function_name: String,
+    #[schemars(description = "Company the metrics were retrieved for")]
+    pub company_name: String,
+    #[schemars(description = "Query that was executed")]
+    pub query: String,
+    #[schemars(description = "Number of chunks retrieved")]
+    pub total_chunks: usize,
+    #[schemars(description = "Arguments the calculation was run with")]
+    pub arguments: serde_json::Value,
+    #[schemars(description = "Every argument with the chunk and text it was read from")]
+    pub inputs: Vec<ExtractedInput>,
+    #[schemars(description = "Calculation result")]
+    pub result: serde_json::Value,
+}
+
 // OpenAI Vector Store API response structures
 #[derive(Debug, Serialize)]
 struct RankingOptions {
@@ -605,422 +209,77 @@ struct FileCountsInfo {
     total: u32,
 }
 
-// =================== FINANCE ENGINE ===================
 
-#[derive(Debug, Clone)]
-pub struct FinanceEngine {
-    tool_router: ToolRouter<Self>,
-}
+// =================== TOOL RESULTS ===================
 
-impl FinanceEngine {
-    /// Calculate company health score using only directly extractable metrics
-    fn calculate_company_health_score_internal(
-        revenue_growth: f64,
-        sla_compliance: f64,
-        customer_satisfaction: f64,
-    ) -> Result<CompanyHealthScoreResponse, String> {
-        // Validation
-        if sla_compliance < 0.0 || sla_compliance > 1.0 {
-            return Err("SLA compliance must be between 0.0 and 1.0".to_string());
-        }
-        if customer_satisfaction < 0.0 || customer_satisfaction > 100.0 {
-            return Err("Customer satisfaction must be between 0.0 and 100.0".to_string());
-        }
-
-        // Convert to 0-100 scale
-        // Revenue Growth: 0% growth = 0 points, 15%+ growth = 100 points
-        let revenue_score = ((revenue_growth / 0.15) * 100.0).min(100.0).max(0.0);
-        
-        // Service Level Agreement Compliance: Direct percentage conversion
-        let sla_score = sla_compliance * 100.0;
-        
-        // Customer Satisfaction: Already 0-100, use as-is
-        let satisfaction_score = customer_satisfaction;
-
-        let mut components = HashMap::new();
-        components.insert("revenue".to_string(), revenue_score);
-        components.insert("sla".to_string(), sla_score);
-        components.insert("satisfaction".to_string(), satisfaction_score);
-
-        // Apply weights (redistributed from original 5 metrics to 3)
-        // Original: revenue=30%, sla=25%, innovation=20%, satisfaction=15%, pipeline=10%
-        // Adjusted: revenue=40%, sla=35%, satisfaction=25% (proportionally redistributed)
-        let weights = [
-            ("revenue", 0.40),
-            ("sla", 0.35),
-            ("satisfaction", 0.25),
-        ];
-
-        let mut weighted_contributions = HashMap::new();
-        let mut overall_score = 0.0;
-
-        for (name, weight) in weights.iter() {
-            let contribution = components[*name] * weight;
-            weighted_contributions.insert(name.to_string(), contribution);
-            overall_score += contribution;
-        }
-
-        // Classify risk
-        let (risk_level, interpretation) = if overall_score >= 80.0 {
-            ("LOW", "Company health is excellent across measured dimensions.")
-        } else if overall_score >= 65.0 {
-            ("MEDIUM", "Company health is good but some areas need attention for optimal performance.")
-        } else if overall_score >= 50.0 {
-            ("HIGH", "Company faces significant challenges requiring strategic intervention.")
-        } else {
-            ("CRITICAL", "Company health is critical with severe issues across key performance indicators.")
-        };
-
-        Ok(CompanyHealthScoreResponse {
-            overall_score,
-            components,
-            weighted_contributions,
-            risk_level: risk_level.to_string(),
-            interpretation: interpretation.to_string(),
-        })
-    }
-
-    /// Calculate revenue quality score
-    fn calculate_revenue_quality_score_internal(
-        high_growth_revenue: f64,
-        stable_revenue: f64,
-        declining_revenue: f64,
-        total_revenue: f64,
-    ) -> Result<RevenueQualityScoreResponse, String> {
-        // Validation
-        if high_growth_revenue < 0.0 || stable_revenue < 0.0 || declining_revenue < 0.0 || total_revenue <= 0.0 {
-            return Err("All revenue amounts must be non-negative and total must be positive".to_string());
-        }
-
-        let sum = high_growth_revenue + stable_revenue + declining_revenue;
-        if (sum - total_revenue).abs() > 0.01 * total_revenue {
-            return Err("Revenue categories must sum to total revenue".to_string());
-        }
-
-        // Calculate distribution
-        let high_growth_pct = high_growth_revenue / total_revenue;
-        let stable_pct = stable_revenue / total_revenue;
-        let declining_pct = declining_revenue / total_revenue;
-
-        let mut distribution = HashMap::new();
-        distribution.insert("high_growth".to_string(), high_growth_pct * 100.0);
-        distribution.insert("stable".to_string(), stable_pct * 100.0);
-        distribution.insert("declining".to_string(), declining_pct * 100.0);
-
-        // Calculate quality score with weights
-        let quality_score = (high_growth_pct * 1.0) + (stable_pct * 0.7) + (declining_pct * 0.0);
-
-        // Assign grade
-        let grade = if quality_score >= 0.80 {
-            "A"
-        } else if quality_score >= 0.65 {
-            "B"
-        } else if quality_score >= 0.50 {
-            "C"
-        } else if quality_score >= 0.35 {
-            "D"
-        } else {
-            "F"
-        };
-
-        // Generate recommendation
-        let recommendation = match grade {
-            "A" => "Excellent revenue quality. Continue investing in high-growth segments and maintain momentum.",
-            "B" => "Good revenue quality with room for improvement. Focus on accelerating growth in stable segments.",
-            "C" => "Moderate revenue quality. Strategic pivot needed to increase high-growth revenue proportion.",
-            "D" => "Poor revenue quality. Urgent action required to address declining revenue and stimulate growth.",
-            _ => "Critical revenue quality issues. Immediate restructuring needed to reverse declining trends.",
-        };
-
-        let target_score = 0.75;
-        let gap_to_target = quality_score - target_score;
-
-        Ok(RevenueQualityScoreResponse {
-            quality_score,
-            distribution,
-            grade: grade.to_string(),
-            recommendation: recommendation.to_string(),
-            target_score,
-            gap_to_target,
-        })
-    }
-
-    /// Calculate HHI and diversification checked [√]
-    fn calculate_hhi_and_diversification_internal(revenues: Vec<f64>) -> Result<HHIResponse, String> {
-        if revenues.len() < 2 {
-            return Err("Must contain at least 2 segments".to_string());
-        }
-
-        for (i, &rev) in revenues.iter().enumerate() {
-            if rev < 0.0 {
-                return Err(format!("Revenue at index {} cannot be negative", i));
-            }
-        }
-
-        let total: f64 = revenues.iter().sum();
-        if total <= 0.0 {
-            return Err("Total revenue must be positive".to_string());
-        }
-
-        // Calculate market shares
-        let market_shares: Vec<f64> = revenues.iter().map(|r| r / total).collect();
-        
-        // Calculate HHI
-        let hhi: f64 = market_shares.iter().map(|s| s * s).sum();
-        
-        let diversification_score = 1.0 - hhi;
-        let effective_n = 1.0 / hhi;
-        let largest_share = market_shares.iter().cloned().fold(0.0, f64::max);
-
-        // Determine risk level
-        let risk_level = if hhi < 0.15 {
-            "LOW"
-        } else if hhi <= 0.25 {
-            "MEDIUM"
-        } else {
-            "HIGH"
-        };
-
-        let assessment = format!(
-            "Revenue concentration is {} with HHI of {:.3}. The portfolio behaves like {:.1} equal-sized segments.",
-            risk_level.to_lowercase(), hhi, effective_n
-        );
-
-        // Identify concentration issues
-        let mut concentration_issues = Vec::new();
-        if largest_share > 0.50 {
-            concentration_issues.push(format!("Single segment dominance: {:.1}% of revenue", largest_share * 100.0));
-        }
-        if hhi > 0.35 {
-            concentration_issues.push("HHI exceeds 0.35 indicating severe concentration".to_string());
-        }
-        if effective_n < 3.0 {
-            concentration_issues.push(format!("Effective segment count ({:.1}) is below recommended minimum of 3", effective_n));
-        }
-
-        Ok(HHIResponse {
-            hhi,
-            diversification_score,
-            effective_n,
-            risk_level: risk_level.to_string(),
-            assessment,
-            market_shares,
-            largest_share,
-            concentration_issues,
-        })
-    }
-
-    /// Calculate operating leverage ratio checked [√]
-    fn calculate_operating_leverage_internal(
-        revenue_growth_rate: f64,
-        cost_growth_rate: f64,
-    ) -> Result<OperatingLeverageResponse, String> {
-        // Validation
-        if cost_growth_rate == 0.0 {
-            return Err("Cost growth rate cannot be zero".to_string());
-        }
-
-        let operating_leverage = revenue_growth_rate / cost_growth_rate;
-        let margin_expansion_bps = (revenue_growth_rate - cost_growth_rate) * 10000.0;
-
-        let efficiency_rating = if operating_leverage >= 1.5 {
-            "Excellent"
-        } else if operating_leverage >= 1.2 {
-            "Good"
-        } else if operating_leverage >= 1.0 {
-            "Adequate"
-        } else {
-            "Poor"
-        };
-
-        let interpretation = format!("Revenue growing {:.1}x faster than costs", operating_leverage);
-
-        Ok(OperatingLeverageResponse {
-            operating_leverage: (operating_leverage * 100.0).round() / 100.0,
-            revenue_growth_pct: (revenue_growth_rate * 1000.0).round() / 10.0,
-            cost_growth_pct: (cost_growth_rate * 1000.0).round() / 10.0,
-            margin_expansion_bps: margin_expansion_bps.round(),
-            efficiency_rating: efficiency_rating.to_string(),
-            interpretation,
-        })
-    }
-
-    /// Calculate portfolio momentum index checked [√]
-    fn calculate_portfolio_momentum_internal(
-        segments: HashMap<String, PortfolioSegmentData>,
-    ) -> Result<PortfolioMomentumResponse, String> {
-        if segments.is_empty() {
-            return Err("Segments cannot be empty".to_string());
-        }
-
-        let total_revenue: f64 = segments.values().map(|s| s.revenue).sum();
-
-        if total_revenue == 0.0 {
-            return Err("Total revenue cannot be zero".to_string());
-        }
-
-        let mut momentum = 0.0;
-        let mut segment_contributions = HashMap::new();
-        let mut max_contribution = 0.0;
-        let mut top_contributor = String::new();
-
-        for (name, data) in segments.iter() {
-            let weight = data.revenue / total_revenue;
-            let contribution = weight * data.growth_rate;
-            momentum += contribution;
-
-            let contrib_pct = contribution * 100.0;
-            if contrib_pct > max_contribution {
-                max_contribution = contrib_pct;
-                top_contributor = name.clone();
-            }
-
-            segment_contributions.insert(
-                name.clone(),
-                SegmentMomentumContribution {
-                    revenue: (data.revenue * 100.0).round() / 100.0,
-                    revenue_pct: (weight * 1000.0).round() / 10.0,
-                    growth_rate: (data.growth_rate * 1000.0).round() / 10.0,
-                    contribution_to_momentum: (contrib_pct * 100.0).round() / 100.0,
-                },
-            );
-        }
-
-        let momentum_rating = if momentum > 0.10 {
-            "Strong"
-        } else if momentum > 0.05 {
-            "Moderate"
-        } else if momentum > 0.0 {
-            "Weak"
-        } else {
-            "Declining"
-        };
-
-        Ok(PortfolioMomentumResponse {
-            portfolio_momentum: (momentum * 10000.0).round() / 10000.0,
-            portfolio_momentum_pct: (momentum * 10000.0).round() / 100.0,
-            total_revenue: (total_revenue * 100.0).round() / 100.0,
-            segment_contributions,
-            top_contributor,
-            momentum_rating: momentum_rating.to_string(),
-        })
-    }
-
-    /// Calculate Gini coefficient for revenue concentration checked [√]
-    fn calculate_gini_coefficient_internal(revenues: Vec<f64>) -> Result<GiniCoefficientResponse, String> {
-        if revenues.is_empty() {
-            return Err("Revenue list cannot be empty".to_string());
-        }
-
-        for rev in revenues.iter() {
-            if *rev < 0.0 {
-                return Err("Revenues cannot be negative".to_string());
-            }
-        }
-
-        let total_revenue: f64 = revenues.iter().sum();
-        if total_revenue == 0.0 {
-            return Err("Total revenue cannot be zero".to_string());
-        }
-
-        let mut sorted_revenues = revenues.clone();
-        sorted_revenues.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-        let n = sorted_revenues.len() as f64;
-        let cumsum: f64 = sorted_revenues
-            .iter()
-            .enumerate()
-            .map(|(i, &rev)| (i as f64 + 1.0) * rev)
-            .sum();
-
-        let gini = (2.0 * cumsum) / (n * total_revenue) - (n + 1.0) / n;
-        let diversification_score = 1.0 - gini;
-
-        let largest_share = revenues.iter().cloned().fold(0.0, f64::max) / total_revenue * 100.0;
-        let smallest_share = revenues.iter().cloned().fold(f64::INFINITY, f64::min) / total_revenue * 100.0;
-
-        let effective_segments = if gini > 0.0 {
-            1.0 / (gini + 0.0001)
-        } else {
-            n
-        };
-
-        let concentration_level = if gini < 0.25 {
-            "Low"
-        } else if gini < 0.40 {
-            "Moderate"
-        } else {
-            "High"
-        };
-
-        let sorted_revenues_rounded: Vec<f64> = sorted_revenues
-            .iter()
-            .map(|r| (r * 100.0).round() / 100.0)
-            .collect();
+/// JSON schema of a tool's response type, advertised as the tool's `outputSchema`
+fn output_schema<T: schemars::JsonSchema + 'static>() -> Arc<JsonObject> {
+    schema_for_output::<T>().unwrap_or_else(|e| panic!("Invalid output schema: {}", e))
+}
 
-        Ok(GiniCoefficientResponse {
-            gini_coefficient: (gini * 1000.0).round() / 1000.0,
-            diversification_score: (diversification_score * 1000.0).round() / 1000.0,
-            concentration_level: concentration_level.to_string(),
-            largest_segment_share: (largest_share * 10.0).round() / 10.0,
-            smallest_segment_share: (smallest_share * 10.0).round() / 10.0,
-            effective_segments: (effective_segments * 100.0).round() / 100.0,
-            sorted_revenues: sorted_revenues_rounded,
-        })
+/// Successful tool result carrying the response both as pretty-printed text and as `structuredContent`
+fn success_result<T: Serialize>(response: &T) -> CallToolResult {
+    let structured = match serde_json::to_value(response) {
+        Ok(value) => value,
+        Err(e) => return FinanceError::internal(format!("Serialization error: {}", e)).into_tool_result(),
+    };
+    match serde_json::to_string_pretty(response) {
+        Ok(json_str) => CallToolResult {
+            structured_content: Some(structured),
+            ..CallToolResult::success(vec![Content::text(json_str)])
+        },
+        Err(e) => FinanceError::internal(format!("Serialization error: {}", e)).into_tool_result(),
     }
+}
 
-    /// Calculate organic growth rate checked [√]
-    fn calculate_organic_growth_internal(
-        revenue_prior: f64,
-        revenue_current: f64,
-    ) -> Result<OrganicGrowthResponse, String> {
-        if revenue_prior <= 0.0 {
-            return Err("Prior period revenue must be positive".to_string());
-        }
+/// Run calculation-heavy tool work on the blocking thread pool, so a large simulation or batch
+/// does not stall the async runtime serving other requests
+async fn run_blocking<T, F>(work: F) -> Result<T, FinanceError>
+where
+    F: FnOnce() -> Result<T, FinanceError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| Err(FinanceError::internal(format!("Calculation task failed: {}", e))))
+}
 
-        let absolute_growth = revenue_current - revenue_prior;
-        let growth_rate = absolute_growth / revenue_prior;
-
-        let growth_rating = if growth_rate > 0.15 {
-            "Exceptional"
-        } else if growth_rate > 0.10 {
-            "Strong"
-        } else if growth_rate > 0.05 {
-            "Moderate"
-        } else if growth_rate > 0.0 {
-            "Weak"
-        } else {
-            "Declining"
-        };
+// =================== FINANCE ENGINE ===================
 
-        Ok(OrganicGrowthResponse {
-            organic_growth_rate: (growth_rate * 10000.0).round() / 10000.0,
-            organic_growth_pct: (growth_rate * 10000.0).round() / 100.0,
-            absolute_growth: (absolute_growth * 100.0).round() / 100.0,
-            revenue_prior: (revenue_prior * 100.0).round() / 100.0,
-            revenue_current: (revenue_current * 100.0).round() / 100.0,
-            growth_rating: growth_rating.to_string(),
-            annualized_cagr: (growth_rate * 10000.0).round() / 100.0,
-        })
-    }
+#[derive(Debug, Clone)]
+pub struct FinanceEngine {
+    tool_router: ToolRouter<Self>,
+}
 
+impl FinanceEngine {
     /// Validate vector store query parameters
     fn validate_vector_store_params(
         function_name: &str,
         max_num_results: Option<usize>,
         score_threshold: Option<f64>,
-    ) -> Result<(), String> {
+    ) -> Result<(), FinanceError> {
         // Validate function name by trying to generate a query
-        Self::generate_query_for_function(function_name, "test")?;
+        Self::generate_query_for_function(function_name, "test")
+            .map_err(|e| FinanceError::invalid_input(e).with_field("function_name"))?;
 
         let max_results = max_num_results.unwrap_or(5);
         if max_results == 0 || max_results > 10 {
-            return Err("max_num_results must be between 1 and 10".to_string());
+            return Err(FinanceError::out_of_range(
+                "max_num_results",
+                Some(1.0),
+                Some(10.0),
+                "max_num_results must be between 1 and 10",
+            ));
         }
 
         let threshold = score_threshold.unwrap_or(0.8);
-        if threshold < 0.0 || threshold > 1.0 {
-            return Err("score_threshold must be between 0.0 and 1.0".to_string());
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(FinanceError::out_of_range(
+                "score_threshold",
+                Some(0.0),
+                Some(1.0),
+                "score_threshold must be between 0.0 and 1.0",
+            ));
         }
 
         Ok(())
@@ -1191,58 +450,243 @@ impl FinanceEngine {
         })
     }
 
+    /// Validate the parameters, resolve the vector store from the environment and run the query
+    async fn search_vector_store(params: VectorStoreQueryParams) -> Result<VectorStoreQueryResponse, FinanceError> {
+        // Validate parameters first (before any network calls)
+        Self::validate_vector_store_params(&params.function_name, params.max_num_results, params.score_threshold)
+            .map_err(|e| e.context("Validation error"))?;
+
+        // Get vector store name from environment
+        let vector_store_name = env::var("VECTOR_STORE_NAME")
+            .map_err(|_| FinanceError::upstream_unavailable("VECTOR_STORE_NAME environment variable not set"))?;
+
+        // Get vector store ID by name
+        let vector_store_id = Self::get_vector_store_id_by_name(&vector_store_name)
+            .await
+            .map_err(|e| FinanceError::upstream_unavailable(format!("Failed to get vector store ID: {}", e)))?;
+
+        // Build the base URL
+        let base_url = Self::build_llama_stack_url()
+            .map_err(|e| FinanceError::upstream_unavailable(format!("Failed to build LLama Stack URL: {}", e)))?;
+
+        // Build the full search API URL
+        let vector_store_api_url = format!("{}/v1/openai/v1/vector_stores/{}/search", base_url, vector_store_id);
+
+        Self::get_metrics_from_vector_store_internal(
+            vector_store_api_url,
+            params.function_name,
+            params.company_name,
+            params.max_num_results,
+            params.score_threshold,
+            params.ranker,
+            params.rewrite_query,
+        )
+        .await
+        .map_err(|e| FinanceError::upstream_unavailable(format!("Vector store query error: {}", e)))
+    }
+
+    /// Retrieve the chunks for a calculation, extract its arguments from them and run it
+    async fn run_calculation_pipeline_internal(
+        params: CalculationPipelineParams,
+    ) -> Result<CalculationPipelineResponse, FinanceError> {
+        // Check the calculation and arguments before any network calls
+        let fields = calculation_input_fields(&params.function_name)
+            .map_err(|e| FinanceError::invalid_input(format!("Validation error: {}", e)).with_field("function_name"))?;
+        if fields.is_empty() {
+            let supported: Vec<&str> = calculation_names()
+                .into_iter()
+                .filter(|name| calculation_input_fields(name).is_ok_and(|fields| !fields.is_empty()))
+                .collect();
+            return Err(FinanceError::invalid_input(format!(
+                "Validation error: '{}' takes structured inputs that cannot be extracted from text; call it directly. \
+                 Calculations supported by the pipeline are: {}",
+                params.function_name,
+                supported.join(", ")
+            ))
+            .with_field("function_name"));
+        }
+        let arguments = match params.arguments {
+            None => serde_json::Map::new(),
+            Some(serde_json::Value::Object(arguments)) => arguments,
+            Some(_) => {
+                return Err(FinanceError::invalid_input("Validation error: arguments must be a JSON object").with_field("arguments"));
+            }
+        };
+
+        let retrieved = Self::search_vector_store(VectorStoreQueryParams {
+            function_name: params.function_name.clone(),
+            company_name: params.company_name.clone(),
+            max_num_results: params.max_num_results,
+            score_threshold: params.score_threshold,
+            ranker: params.ranker,
+            rewrite_query: params.rewrite_query,
+        })
+        .await?;
+
+        let chunks: Vec<SourceChunk> = retrieved
+            .chunks
+            .iter()
+            .map(|chunk| SourceChunk {
+                file_id: chunk.file_id.clone(),
+                filename: chunk.filename.clone(),
+                score: chunk.score,
+                text: chunk.content.iter().map(|c| c.text.as_str()).collect::<Vec<_>>().join("\n"),
+            })
+            .collect();
+        let extracted = extract_inputs(fields, &chunks, arguments)?;
+
+        let result = run_calculation_internal(&params.function_name, extracted.arguments.clone())
+            .map_err(|e| e.context("Calculation on the extracted inputs failed"))?;
+
+        Ok(CalculationPipelineResponse {
+            function_name: params.function_name,
+            company_name: params.company_name,
+            query: retrieved.query,
+            total_chunks: retrieved.total_chunks,
+            arguments: extracted.arguments,
+            inputs: extracted.inputs,
+            result,
+        })
+    }
+
     /// Generate an appropriate query based on the function name and company
     fn generate_query_for_function(function_name: &str, company_name: &str) -> Result<String, String> {
         let query = match function_name {
             "calculate_company_health_score" => {
                 format!(
-                    "What are the revenue growth, SLA compliance, and customer satisfaction metrics for company {}?",
+                    "What are the revenue growth, SLA compliance, and customer satisfaction metrics for company {}?",
+                    company_name
+                )
+            },
+            "calculate_revenue_quality_score" => {
+                format!(
+                    "What is the high growth revenue, stable revenue, declining revenue, \
+                     and total revenue for company {}?",
+                    company_name
+                )
+            },
+            "calculate_hhi_and_diversification" => {
+                format!(
+                    "What are the revenue values for each business segment of company {}?",
+                    company_name
+                )
+            },
+            "calculate_operating_leverage" => {
+                format!(
+                    "What is the revenue growth rate and cost growth rate for company {}?",
+                    company_name
+                )
+            },
+            "calculate_portfolio_momentum" => {
+                format!(
+                    "What are the revenue and growth rate for each segment of company {}?",
+                    company_name
+                )
+            },
+            "calculate_gini_coefficient" => {
+                format!(
+                    "What are the revenue values by segment for company {}?",
+                    company_name
+                )
+            },
+            "calculate_organic_growth" => {
+                format!(
+                    "What is the current revenue and prior period revenue for company {}?",
+                    company_name
+                )
+            },
+            "calculate_budget_variance" => {
+                format!(
+                    "What are the budgeted and actual revenue, cost of goods sold, and operating expenses by category \
+                     (with unit volumes and prices where available) for company {}?",
+                    company_name
+                )
+            },
+            "calculate_inflation_adjusted_growth" => {
+                format!(
+                    "What are the yearly revenue figures and the consumer price index or inflation rates \
+                     of its main markets for company {}?",
+                    company_name
+                )
+            },
+            "calculate_lbo_returns" => {
+                format!(
+                    "What are the EBITDA, projected EBITDA, purchase multiple, debt financing terms (amounts, interest rates, \
+                     amortization) and expected exit multiple for the buyout of company {}?",
+                    company_name
+                )
+            },
+            "calculate_accretion_dilution" => {
+                format!(
+                    "What are the net income, diluted shares and share price of the acquirer and target, the purchase price, \
+                     cash/stock/debt consideration, financing rates and expected synergies for the acquisition involving company {}?",
                     company_name
                 )
             },
-            "calculate_revenue_quality_score" => {
+            "calculate_cap_table" => {
                 format!(
-                    "What is the high growth revenue, stable revenue, declining revenue, \
-                     and total revenue for company {}?",
+                    "What are the shareholders and share counts, option pool, funding rounds with pre-money valuations and investments, \
+                     SAFEs or convertible notes with caps and discounts, and liquidation preference terms of company {}?",
                     company_name
                 )
             },
-            "calculate_hhi_and_diversification" => {
+            "check_covenant_compliance" => {
                 format!(
-                    "What are the revenue values for each business segment of company {}?",
+                    "What are the EBITDA, total debt, cash, interest expense, scheduled debt repayments, capex, cash taxes, undrawn revolver \
+                     and financial covenant thresholds with step-down dates for company {}?",
                     company_name
                 )
             },
-            "calculate_operating_leverage" => {
+            "benchmark_against_peers" => {
                 format!(
-                    "What is the revenue growth rate and cost growth rate for company {}?",
+                    "What are the key financial ratios of company {} and of its listed peers or competitors, \
+                     such as margins, growth rates, leverage and returns on capital?",
                     company_name
                 )
             },
-            "calculate_portfolio_momentum" => {
+            "analyze_customer_concentration" => {
                 format!(
-                    "What are the revenue and growth rate for each segment of company {}?",
+                    "What is the revenue by customer, including the largest customers and their share of total revenue, for company {}?",
                     company_name
                 )
             },
-            "calculate_gini_coefficient" => {
+            "estimate_credit_rating" => {
                 format!(
-                    "What are the revenue values by segment for company {}?",
+                    "What are the net debt, EBITDA, interest expense, EBITDA margin and total revenue of company {}?",
                     company_name
                 )
             },
-            "calculate_organic_growth" => {
+            "calculate_unit_economics" => {
                 format!(
-                    "What is the current revenue and prior period revenue for company {}?",
+                    "What are the revenue, cost of goods sold, variable costs and allocated fixed costs by product line or business segment for company {}?",
+                    company_name
+                )
+            },
+            "calculate_earnings_quality" => {
+                format!(
+                    "What are the net income, cash flow from operations, total assets at the start and end of the period, changes in current assets, cash, \
+                     current liabilities, short-term debt and taxes payable, depreciation and any one-off or non-recurring items for company {}?",
+                    company_name
+                )
+            },
+            "calculate_dividend_discount" => {
+                format!(
+                    "What are the annual dividend per share, expected dividend growth rates, cost of equity or required return and current share price for company {}?",
+                    company_name
+                )
+            },
+            "assess_dividend_sustainability" => {
+                format!(
+                    "What are the dividends paid, net income, free cash flow, dividend growth rate and earnings growth rate for company {}?",
                     company_name
                 )
             },
             _ => {
-                return Err(format!("Unknown function name: '{}'. Valid functions are: \
-                    calculate_company_health_score, calculate_revenue_quality_score, \
-                    calculate_hhi_and_diversification, calculate_operating_leverage, \
-                    calculate_portfolio_momentum, calculate_gini_coefficient, \
-                    calculate_organic_growth", function_name));
+                return Err(format!(
+                    "Unknown function name: '{}'. Valid functions are: {}",
+                    function_name,
+                    calculation_names().join(", ")
+                ));
             }
         };
 
@@ -1254,270 +698,135 @@ impl FinanceEngine {
 impl FinanceEngine {
     pub fn new() -> Self {
         Self {
-            tool_router: Self::tool_router(),
+            tool_router: Self::tool_router() + Self::calculation_router(),
         }
     }
 
-    #[tool(description = "Calculate comprehensive company health score (0-100) by combining three weighted dimensions: revenue growth (40%), Service Level Agreement compliance (35%), and customer satisfaction (25%). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from. Returns overall score, individual components, weighted contributions, risk level classification (LOW/MEDIUM/HIGH/CRITICAL), and interpretation.")]
-    pub async fn calculate_company_health_score(
-        &self,
-        Parameters(params): Parameters<CompanyHealthScoreParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let _timer = RequestTimer::new();
-        increment_requests();
-
-        // Parse parameters
-        let revenue_growth = match parse_f64_from_string(&params.revenue_growth) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid revenue_growth: {}", e))]));
-            }
-        };
-
-        let sla_compliance = match parse_f64_from_string(&params.sla_compliance) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid sla_compliance: {}", e))]));
-            }
-        };
-
-        let customer_satisfaction = match parse_f64_from_string(&params.customer_satisfaction) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid customer_satisfaction: {}", e))]));
-            }
-        };
-
-        match Self::calculate_company_health_score_internal(
-            revenue_growth,
-            sla_compliance,
-            customer_satisfaction,
-        ) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
+    /// One tool route per [`Calculation`], each handled by [`Self::call_calculation`]
+    fn calculation_router() -> ToolRouter<Self> {
+        struct Routes(ToolRouter<FinanceEngine>);
+        impl CalculationVisitor for Routes {
+            fn visit<C: Calculation>(&mut self) {
+                let tool = Tool::new(C::NAME, C::DESCRIPTION, schema_for_type::<C::Params>())
+                    .with_output_schema::<C::Response>();
+                self.0.add_route(ToolRoute::new(tool, FinanceEngine::call_calculation::<C>));
             }
         }
-    }
-
-    #[tool(description = "Evaluate revenue quality and sustainability by categorizing revenue into high-growth (>15% YoY), stable (0-15% YoY), and declining (<0% YoY) segments. Applies quality weights (1.0, 0.7, 0.0) to calculate composite quality score (0.0-1.0). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from. Returns quality score, distribution breakdown, letter grade (A-F), strategic recommendation, and gap to industry benchmark (0.75).")]
-    pub async fn calculate_revenue_quality_score(
-        &self,
-        Parameters(params): Parameters<RevenueQualityScoreParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let _timer = RequestTimer::new();
-        increment_requests();
-
-        let high_growth_revenue = match parse_f64_from_string(&params.high_growth_revenue) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid high_growth_revenue: {}", e))]));
-            }
-        };
-
-        let stable_revenue = match parse_f64_from_string(&params.stable_revenue) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid stable_revenue: {}", e))]));
-            }
-        };
-
-        let declining_revenue = match parse_f64_from_string(&params.declining_revenue) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid declining_revenue: {}", e))]));
-            }
-        };
-
-        let total_revenue = match parse_f64_from_string(&params.total_revenue) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid total_revenue: {}", e))]));
-            }
-        };
 
-        match Self::calculate_revenue_quality_score_internal(
-            high_growth_revenue,
-            stable_revenue,
-            declining_revenue,
-            total_revenue,
-        ) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
-            }
-        }
+        let mut routes = Routes(ToolRouter::new());
+        for_each_calculation(&mut routes);
+        routes.0
     }
 
-    #[tool(description = "Compute Herfindahl-Hirschman Index (HHI) to measure revenue concentration risk across business segments. HHI is sum of squared market shares (0.0-1.0). Returns HHI, diversification score (1-HHI), effective number of segments (1/HHI), risk classification (LOW <0.15, MEDIUM 0.15-0.25, HIGH >0.25), market shares, largest share, and concentration warnings. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
-    pub async fn calculate_hhi_and_diversification(
+    /// Run a calculation as a tool call: time and count the request, then return the response or the error
+    pub fn call_calculation<C: Calculation>(
         &self,
-        Parameters(params): Parameters<HHIParams>,
+        Parameters(params): Parameters<C::Params>,
     ) -> Result<CallToolResult, McpError> {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        match Self::calculate_hhi_and_diversification_internal(params.revenues) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
-            }
+        match C::calculate(params) {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
     }
 
-    #[tool(description = "Calculate operating leverage ratio measuring relationship between revenue growth and cost growth to assess operational scalability. Ratio > 1.0 indicates positive operating leverage (revenue growing faster than costs). Returns operating leverage ratio, growth rates, margin expansion in basis points, efficiency rating (Excellent/Good/Adequate/Poor), and interpretation. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
-    pub async fn calculate_operating_leverage(
+    #[tool(description = "Run a Monte Carlo simulation over any calculation tool (e.g., calculate_company_health_score) to turn a deterministic score into a risk-aware one. Takes the calculation name, its base arguments, a probability distribution (normal, triangular, uniform, lognormal) for each uncertain numeric input addressed by path (e.g., 'revenue_growth', 'revenues.0', 'segments.cloud.revenue'), an iteration count and an optional seed. Returns mean, standard deviation, percentiles (p5-p95) and a histogram for every numeric output, plus the probability of each risk/rating band. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract the base metrics from.", output_schema = output_schema::<SimulationResponse>())]
+    pub async fn simulate(
         &self,
-        Parameters(params): Parameters<OperatingLeverageParams>,
+        Parameters(params): Parameters<SimulationParams>,
     ) -> Result<CallToolResult, McpError> {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        let revenue_growth_rate = match parse_f64_from_string(&params.revenue_growth_rate) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid revenue_growth_rate: {}", e))]));
-            }
-        };
-
-        let cost_growth_rate = match parse_f64_from_string(&params.cost_growth_rate) {
-            Ok(v) => v,
+        let (primary_metric, band_field) = match calculation_output_fields(&params.function_name) {
+            Ok(fields) => fields,
             Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid cost_growth_rate: {}", e))]));
+                return Ok(FinanceError::invalid_input(format!("Validation error: {}", e))
+                    .with_field("function_name")
+                    .into_tool_result());
             }
         };
 
-        match Self::calculate_operating_leverage_internal(revenue_growth_rate, cost_growth_rate) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
-            }
+        let function_name = params.function_name.clone();
+        let simulation = run_blocking(move || {
+            simulate_internal(params, primary_metric, band_field, |arguments| {
+                run_calculation_internal(&function_name, arguments)
+            })
+        });
+        match simulation.await {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
     }
 
-    #[tool(description = "Calculate revenue-weighted portfolio momentum index measuring aggregate growth trajectory across business segments. Computes weighted average growth rate where each segment's contribution is proportional to its revenue share. Returns portfolio momentum (decimal and percentage), total revenue, per-segment contributions, top contributor, and momentum rating (Strong >10%, Moderate 5-10%, Weak 0-5%, Declining <0%). Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
-    pub async fn calculate_portfolio_momentum(
+    #[tool(description = "Run scenario and sensitivity analysis on any calculation tool (e.g., calculate_company_health_score) in a single call instead of re-calling it input by input. Takes the calculation name and its base arguments plus named scenarios (e.g., base/bull/bear input overrides) and/or one-at-a-time ±% shocks. Returns side-by-side scenario results with deltas versus the base case, a tornado ranking of input influence on the headline metric, and the input values at which the risk/rating band flips. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract the base metrics from.", output_schema = output_schema::<ScenarioAnalysisResponse>())]
+    pub async fn analyze_scenarios(
         &self,
-        Parameters(params): Parameters<PortfolioMomentumParams>,
+        Parameters(params): Parameters<ScenarioAnalysisParams>,
     ) -> Result<CallToolResult, McpError> {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        match Self::calculate_portfolio_momentum_internal(params.segments) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
+        let (primary_metric, band_field) = match calculation_output_fields(&params.function_name) {
+            Ok(fields) => fields,
             Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
+                return Ok(FinanceError::invalid_input(format!("Validation error: {}", e))
+                    .with_field("function_name")
+                    .into_tool_result());
             }
+        };
+
+        let function_name = params.function_name.clone();
+        let analysis = run_blocking(move || {
+            analyze_scenarios_internal(params, primary_metric, band_field, |arguments| {
+                run_calculation_internal(&function_name, arguments)
+            })
+        });
+        match analysis.await {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
     }
 
-    #[tool(description = "Calculate Gini coefficient measuring revenue distribution inequality across segments for concentration risk assessment. Gini ranges 0-1 (0=perfect equality, 1=complete inequality). Returns Gini coefficient, diversification score (1-Gini), concentration level (Low <0.25, Moderate 0.25-0.40, High >0.40), largest/smallest segment shares, effective number of segments, and sorted revenues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
-    pub async fn calculate_gini_coefficient(
+    #[tool(description = "Run many calculations in one call, e.g. the same score for a list of companies or several tools for one company. Takes a list of items, each with a calculation name (e.g., calculate_company_health_score), its arguments exactly as they would be passed to the tool and an optional id, plus an optional max_concurrency (default 4, max 16). Items run in parallel and each is validated and counted like a single call; a failing item returns its error in its own entry without failing the batch. Returns per-item results or errors in request order with success and failure counts. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract the metrics from.", output_schema = output_schema::<BatchCalculateResponse>())]
+    pub async fn batch_calculate(
         &self,
-        Parameters(params): Parameters<GiniCoefficientParams>,
+        Parameters(params): Parameters<BatchCalculateParams>,
     ) -> Result<CallToolResult, McpError> {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        match Self::calculate_gini_coefficient_internal(params.revenues) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
-            }
+        // Each item counts as a request of its own, with its errors counted by code
+        let batch = run_blocking(move || {
+            batch_calculate_internal(params, |function_name, arguments| {
+                let _timer = RequestTimer::new();
+                increment_requests();
+                run_calculation_internal(function_name, arguments).inspect_err(|e| increment_errors(e.code))
+            })
+        });
+        match batch.await {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
     }
 
-    #[tool(description = "Calculate year-over-year organic revenue growth excluding acquisitions, divestitures, and other inorganic factors. This is the purest measure of underlying business performance. Returns organic growth rate (decimal and percentage), absolute dollar growth, prior/current revenue values, growth rating (Exceptional >15%, Strong 10-15%, Moderate 5-10%, Weak 0-5%, Declining <0%), and annualized CAGR. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
-    pub async fn calculate_organic_growth(
+    #[tool(description = "Run a calculation straight from the documents of a company instead of copying numbers by hand. Takes the calculation name and company name, queries the vector store like `get_metrics_from_vector_store`, reads the calculation inputs from the returned chunks by their labels (e.g., 'revenue growth of 9%'), runs the calculation and returns the result with the arguments used and, for each input, the chunk and text it was read from. Values the documents lack, and options such as locale or currency, can be passed in `arguments`, which take precedence over extracted values. Supports calculate_company_health_score, calculate_revenue_quality_score, calculate_operating_leverage, calculate_organic_growth, estimate_credit_rating, calculate_earnings_quality, calculate_dividend_discount and assess_dividend_sustainability. Requires the same environment variables as `get_metrics_from_vector_store`.", output_schema = output_schema::<CalculationPipelineResponse>())]
+    pub async fn run_calculation_pipeline(
         &self,
-        Parameters(params): Parameters<OrganicGrowthParams>,
+        Parameters(params): Parameters<CalculationPipelineParams>,
     ) -> Result<CallToolResult, McpError> {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        let revenue_prior = match parse_f64_from_string(&params.revenue_prior) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid revenue_prior: {}", e))]));
-            }
-        };
-
-        let revenue_current = match parse_f64_from_string(&params.revenue_current) {
-            Ok(v) => v,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Invalid revenue_current: {}", e))]));
-            }
-        };
-
-        match Self::calculate_organic_growth_internal(revenue_prior, revenue_current) {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Calculation error: {}", e))]))
-            }
+        match Self::run_calculation_pipeline_internal(params).await {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
     }
 
-    #[tool(description = "Retrieve financial metrics from a vector store for a specific finance calculation function. Automatically generates appropriate queries based on the function name (e.g., 'calculate_organic_growth' generates query for current and prior revenue). Supports all 7 calculation functions. Requires VECTOR_STORE_NAME, LLAMA_STACK_HOST, LLAMA_STACK_PORT, and LLAMA_STACK_SECURE environment variables. Returns an array of matching chunks with content, similarity scores, and metadata. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.")]
+    #[tool(description = "Retrieve financial metrics from a vector store for a specific finance calculation function. Automatically generates appropriate queries based on the function name (e.g., 'calculate_organic_growth' generates query for current and prior revenue). Supports all 20 calculation functions. Requires VECTOR_STORE_NAME, LLAMA_STACK_HOST, LLAMA_STACK_PORT, and LLAMA_STACK_SECURE environment variables. Returns an array of matching chunks with content, similarity scores, and metadata. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.", output_schema = output_schema::<VectorStoreQueryResponse>())]
     pub async fn get_metrics_from_vector_store(
         &self,
         Parameters(params): Parameters<VectorStoreQueryParams>,
@@ -1525,67 +834,16 @@ impl FinanceEngine {
         let _timer = RequestTimer::new();
         increment_requests();
 
-        // Validate parameters first (before any network calls)
-        if let Err(e) = Self::validate_vector_store_params(
-            &params.function_name,
-            params.max_num_results,
-            params.score_threshold,
-        ) {
-            increment_errors();
-            return Ok(CallToolResult::error(vec![Content::text(format!("Validation error: {}", e))]));
+        match Self::search_vector_store(params).await {
+            Ok(result) => Ok(success_result(&result)),
+            Err(e) => Ok(e.into_tool_result()),
         }
+    }
+}
 
-        // Get vector store name from environment
-        let vector_store_name = match env::var("VECTOR_STORE_NAME") {
-            Ok(name) => name,
-            Err(_) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text("VECTOR_STORE_NAME environment variable not set")]));
-            }
-        };
-
-        // Get vector store ID by name
-        let vector_store_id = match Self::get_vector_store_id_by_name(&vector_store_name).await {
-            Ok(id) => id,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Failed to get vector store ID: {}", e))]));
-            }
-        };
-
-        // Build the base URL
-        let base_url = match Self::build_llama_stack_url() {
-            Ok(url) => url,
-            Err(e) => {
-                increment_errors();
-                return Ok(CallToolResult::error(vec![Content::text(format!("Failed to build LLama Stack URL: {}", e))]));
-            }
-        };
-
-        // Build the full search API URL
-        let vector_store_api_url = format!("{}/v1/openai/v1/vector_stores/{}/search", base_url, vector_store_id);
-
-        match Self::get_metrics_from_vector_store_internal(
-            vector_store_api_url,
-            params.function_name,
-            params.company_name,
-            params.max_num_results,
-            params.score_threshold,
-            params.ranker,
-            params.rewrite_query,
-        ).await {
-            Ok(result) => match serde_json::to_string_pretty(&result) {
-                Ok(json_str) => Ok(CallToolResult::success(vec![Content::text(json_str)])),
-                Err(e) => {
-                    increment_errors();
-                    Ok(CallToolResult::error(vec![Content::text(format!("Serialization error: {}", e))]))
-                }
-            },
-            Err(e) => {
-                increment_errors();
-                Ok(CallToolResult::error(vec![Content::text(format!("Vector store query error: {}", e))]))
-            }
-        }
+impl Default for FinanceEngine {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -1600,7 +858,7 @@ impl ServerHandler for FinanceEngine {
 
         ServerInfo {
             instructions: Some(
-                "Finance Engine providing eight calculation functions for financial analysis and business intelligence:\
+                "Finance Engine providing twenty-five calculation functions for financial analysis and business intelligence:\
                  \n\n**Critical Business Metrics**\
                  \n1. calculate_company_health_score - Comprehensive 0-100 health score combining five weighted dimensions: revenue growth (30%), SLA compliance (25%), modern revenue percentage (20%), customer satisfaction (15%), and pipeline coverage (10%)\
                  \n2. calculate_revenue_quality_score - Revenue quality evaluation with high-growth, stable, and declining categorization\
@@ -1613,12 +871,40 @@ impl ServerHandler for FinanceEngine {
                  \n7. calculate_organic_growth - Year-over-year organic revenue growth excluding inorganic factors\
                  \n\n**Vector Store Integration**\
                  \n8. get_metrics_from_vector_store - Retrieve financial metrics from OpenAI vector store using semantic search\
+                 \n\n**Risk & Scenario Analysis**\
+                 \n9. simulate - Monte Carlo simulation over any calculation returning output percentiles, histograms and rating band probabilities\
+                 \n10. analyze_scenarios - Named scenarios and ±% sensitivity shocks over any calculation with tornado ranking and band flip thresholds\
+                 \n\n**FP&A**\
+                 \n11. calculate_budget_variance - Budget vs actual variances flagged favorable/unfavorable with price, volume and mix split of revenue\
+                 \n12. calculate_inflation_adjusted_growth - Real versus nominal growth and real CAGR by deflating a series with a CPI or deflator index\
+                 \n\n**Deal Analysis**\
+                 \n13. calculate_lbo_returns - Leveraged buyout sources and uses, debt paydown schedule, MOIC, IRR and returns attribution\
+                 \n14. calculate_accretion_dilution - M&A pro-forma EPS, accretion/dilution % and breakeven synergies\
+                 \n15. calculate_cap_table - Fully diluted ownership across rounds and liquidation waterfall\
+                 \n\n**Credit Analysis**\
+                 \n16. check_covenant_compliance - Covenant pass/fail, headroom and EBITDA cushion with step-downs\
+                 \n17. estimate_credit_rating - Implied rating bucket and default probability from a versioned scorecard\
+                 \n\n**Benchmarking**\
+                 \n18. benchmark_against_peers - Percentile ranks, z-scores and quartiles against a peer group\
+                 \n\n**Customer Analytics**\
+                 \n19. analyze_customer_concentration - Customer Pareto/ABC classes, top-N shares and single-customer exposure\
+                 \n\n**Product Analytics**\
+                 \n20. calculate_unit_economics - Gross and contribution margin per product line with negative-contribution flags\
+                 \n\n**Earnings Analysis**\
+                 \n21. calculate_earnings_quality - Sloan accrual ratio, cash conversion and non-recurring share with A-F grade\
+                 \n\n**Valuation & Income**\
+                 \n22. calculate_dividend_discount - Gordon growth and multi-stage dividend discount valuation with upside vs price\
+                 \n23. assess_dividend_sustainability - Payout ratio, FCF coverage and dividend vs earnings growth with sustainability rating\
+                 \n\n**Batch Processing**\
+                 \n24. batch_calculate - Many calculations in one call with bounded parallelism and per-item results or errors\
+                 \n\n**Document Pipeline**\
+                 \n25. run_calculation_pipeline - Retrieve a company's documents, extract the calculation inputs and run it, citing the chunk behind every input\
                  \n\nAll functions perform sophisticated multi-step calculations with comprehensive validation.".into()
             ),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             server_info: rmcp::model::Implementation {
-                name: name,
-                version: version, 
+                name,
+                version, 
                 title: Some(title), 
                 icons: None, 
                 website_url: Some(website_url) 
@@ -1631,263 +917,156 @@ impl ServerHandler for FinanceEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::common::business_metrics::{
+        CompanyHealthScore, CompanyHealthScoreParams, HHI, HHIParams, HHIResponse, OrganicGrowth, OrganicGrowthParams,
+        RevenueQualityScore, RevenueQualityScoreParams,
+    };
+    use crate::common::error::{AllowedRange, ErrorCode};
+    use crate::common::metrics::METRICS;
 
     #[tokio::test]
-    async fn test_calculate_company_health_score() {
+    async fn test_structured_error_response() {
         let engine = FinanceEngine::new();
         let params = CompanyHealthScoreParams {
             revenue_growth: "0.09".to_string(),
-            sla_compliance: "0.985".to_string(),
-            customer_satisfaction: "89.0".to_string(),
+            sla_compliance: "98.5".to_string(),
+            customer_satisfaction: "89".to_string(),
+            locale: None,
         };
-        
-        let result = engine.calculate_company_health_score(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: CompanyHealthScoreResponse = serde_json::from_str(json_text).unwrap();
-        
-        // With 3 metrics: revenue=40%, sla=35%, satisfaction=25%
-        // revenue_score = (0.09 / 0.15) * 100 = 60
-        // sla_score = 0.985 * 100 = 98.5
-        // satisfaction_score = 89
-        // Overall = 60*0.40 + 98.5*0.35 + 89*0.25 = 24 + 34.475 + 22.25 = 80.725
-        assert!(response.overall_score > 80.0 && response.overall_score < 81.0);
-        assert_eq!(response.risk_level, "LOW");
-        
-        // Verify component scores
-        assert!((response.components["revenue"] - 60.0).abs() < 0.1);
-        assert!((response.components["sla"] - 98.5).abs() < 0.1);
-        assert!((response.components["satisfaction"] - 89.0).abs() < 0.1);
-        
-        // Verify weighted contributions
-        assert!((response.weighted_contributions["revenue"] - 24.0).abs() < 0.1);
-        assert!((response.weighted_contributions["sla"] - 34.475).abs() < 0.1);
-        assert!((response.weighted_contributions["satisfaction"] - 22.25).abs() < 0.1);
-    }
 
-    #[tokio::test]
-    async fn test_calculate_revenue_quality_score() {
-        let engine = FinanceEngine::new();
+        let result = engine.call_calculation::<CompanyHealthScore>(Parameters(params)).unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let json_text = result.content[0].raw.as_text().unwrap().text.as_str();
+        let error: FinanceError = serde_json::from_str(json_text).unwrap();
+        assert_eq!(error.code, ErrorCode::OutOfRange);
+        assert_eq!(error.field.as_deref(), Some("sla_compliance"));
+        assert_eq!(error.allowed_range, Some(AllowedRange { min: Some(0.0), max: Some(1.0) }));
+        assert!(json_text.contains("\"code\": \"OUT_OF_RANGE\""));
+        assert_eq!(result.structured_content, Some(serde_json::to_value(&error).unwrap()));
+
+        // Counted under its code
+        let sum_mismatches = || METRICS.errors_by_code.with_label_values(&["SUM_MISMATCH"]).get();
+        let before = sum_mismatches();
         let params = RevenueQualityScoreParams {
-            high_growth_revenue: "15.0".to_string(),
-            stable_revenue: "25.0".to_string(),
-            declining_revenue: "10.0".to_string(),
-            total_revenue: "50.0".to_string(),
-        };
-        
-        let result = engine.calculate_revenue_quality_score(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: RevenueQualityScoreResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.quality_score >= 0.0 && response.quality_score <= 1.0);
-        assert!(!response.grade.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_calculate_hhi_and_diversification() {
-        let engine = FinanceEngine::new();
-        let params = HHIParams {
-            revenues: vec![15.0, 25.0, 5.0, 8.0],
-        };
-        
-        let result = engine.calculate_hhi_and_diversification(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: HHIResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.hhi >= 0.0 && response.hhi <= 1.0);
-        assert!(response.effective_n >= 1.0);
-    }
-
-    #[tokio::test]
-    async fn test_calculate_operating_leverage() {
-        let engine = FinanceEngine::new();
-        let params = OperatingLeverageParams {
-            revenue_growth_rate: "0.09".to_string(),
-            cost_growth_rate: "0.06".to_string(),
-        };
-        
-        let result = engine.calculate_operating_leverage(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: OperatingLeverageResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert_eq!(response.operating_leverage, 1.5);
-        assert_eq!(response.revenue_growth_pct, 9.0);
-        assert_eq!(response.cost_growth_pct, 6.0);
-        assert_eq!(response.margin_expansion_bps, 300.0);
-        assert_eq!(response.efficiency_rating, "Excellent");
-        assert!(!response.interpretation.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_calculate_operating_leverage_zero_cost_growth() {
-        let engine = FinanceEngine::new();
-        let params = OperatingLeverageParams {
-            revenue_growth_rate: "0.09".to_string(),
-            cost_growth_rate: "0.0".to_string(),
+            high_growth_revenue: "50".to_string(),
+            stable_revenue: "30".to_string(),
+            declining_revenue: "10".to_string(),
+            total_revenue: "200".to_string(),
+            currency: Default::default(),
         };
-        
-        let result = engine.calculate_operating_leverage(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        assert!(json_text.contains("Cost growth rate cannot be zero"));
+        let result = engine.call_calculation::<RevenueQualityScore>(Parameters(params)).unwrap();
+        let json_text = result.content[0].raw.as_text().unwrap().text.as_str();
+        let error: FinanceError = serde_json::from_str(json_text).unwrap();
+        assert_eq!(error.code, ErrorCode::SumMismatch);
+        assert!(sum_mismatches() >= before + 1.0);
     }
 
     #[tokio::test]
-    async fn test_calculate_portfolio_momentum() {
-        let engine = FinanceEngine::new();
-        let mut segments = HashMap::new();
-        segments.insert("subscription".to_string(), PortfolioSegmentData {
-            revenue: 15.0,
-            growth_rate: 0.20,
-        });
-        segments.insert("enterprise".to_string(), PortfolioSegmentData {
-            revenue: 25.0,
-            growth_rate: 0.14,
-        });
-        segments.insert("upsell".to_string(), PortfolioSegmentData {
-            revenue: 5.0,
-            growth_rate: 0.19,
-        });
-        segments.insert("legacy".to_string(), PortfolioSegmentData {
-            revenue: 8.0,
-            growth_rate: -0.20,
-        });
-        
-        let params = PortfolioMomentumParams { segments };
-        
-        let result = engine.calculate_portfolio_momentum(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: PortfolioMomentumResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.portfolio_momentum > 0.0);
-        assert_eq!(response.total_revenue, 53.0);
-        assert_eq!(response.momentum_rating, "Strong");
-        assert!(!response.top_contributor.is_empty());
-        assert_eq!(response.segment_contributions.len(), 4);
-    }
+    async fn test_structured_content_and_output_schema() {
+        let tools = FinanceEngine::new().tool_router.list_all();
+        assert_eq!(tools.len(), 25);
+        for tool in &tools {
+            let schema = tool.output_schema.as_ref().unwrap_or_else(|| panic!("{} has no output schema", tool.name));
+            assert_eq!(schema.get("type"), Some(&serde_json::json!("object")));
+        }
+        let hhi = tools.iter().find(|t| t.name == "calculate_hhi_and_diversification").unwrap();
+        let properties = hhi.output_schema.as_ref().unwrap().get("properties").unwrap();
+        assert!(properties.get("hhi").is_some());
+        assert!(properties.get("effective_n").is_some());
 
-    #[tokio::test]
-    async fn test_calculate_gini_coefficient() {
         let engine = FinanceEngine::new();
-        let params = GiniCoefficientParams {
-            revenues: vec![15.0, 25.0, 5.0, 8.0],
+        let params = HHIParams {
+            revenues: vec!["500".to_string(), "300".to_string(), "200".to_string()],
+            currency: Default::default(),
         };
-        
-        let result = engine.calculate_gini_coefficient(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: GiniCoefficientResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.gini_coefficient >= 0.0);
-        assert!(response.gini_coefficient <= 1.0);
-        // Use approximate comparison for floating point
-        assert!((response.diversification_score - (1.0 - response.gini_coefficient)).abs() < 0.001);
-        assert!(response.largest_segment_share > response.smallest_segment_share);
-        assert_eq!(response.sorted_revenues.len(), 4);
-        assert!(response.sorted_revenues[0] <= response.sorted_revenues[3]);
+        let result = engine.call_calculation::<HHI>(Parameters(params)).unwrap();
+        assert_eq!(result.is_error, Some(false));
+        let json_text = result.content[0].raw.as_text().unwrap().text.as_str();
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured, serde_json::from_str::<serde_json::Value>(json_text).unwrap());
+        let response: HHIResponse = serde_json::from_value(structured).unwrap();
+        assert_eq!(response.risk_level, "HIGH");
+
+        // Errors carry the serialized FinanceError
+        let params = HHIParams { revenues: vec![], currency: Default::default() };
+        let result = engine.call_calculation::<HHI>(Parameters(params)).unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let error: FinanceError = serde_json::from_value(result.structured_content.unwrap()).unwrap();
+        assert_eq!(error.code, ErrorCode::InsufficientData);
     }
 
     #[tokio::test]
-    async fn test_calculate_gini_coefficient_empty_list() {
+    async fn test_calculation_registry() {
         let engine = FinanceEngine::new();
-        let params = GiniCoefficientParams {
-            revenues: vec![],
-        };
-        
-        let result = engine.calculate_gini_coefficient(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        assert!(json_text.contains("Revenue list cannot be empty"));
-    }
+        let names = calculation_names();
+        assert_eq!(names.len(), 20);
+        for name in &names {
+            assert!(engine.tool_router.has_route(name), "{} is not routed", name);
+            assert!(calculation_output_fields(name).is_ok());
+        }
+        assert!(engine.tool_router.has_route("simulate"));
 
-    #[tokio::test]
-    async fn test_calculate_organic_growth() {
-        let engine = FinanceEngine::new();
-        let params = OrganicGrowthParams {
-            revenue_prior: "48.7".to_string(),
-            revenue_current: "53.0".to_string(),
-        };
-        
-        let result = engine.calculate_organic_growth(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: OrganicGrowthResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.organic_growth_rate > 0.0);
-        assert_eq!(response.revenue_prior, 48.7);
-        assert_eq!(response.revenue_current, 53.0);
-        assert_eq!(response.absolute_growth, 4.3);
-        // Growth rate is 8.83%, which falls in Moderate range (5-10%)
-        assert_eq!(response.growth_rating, "Moderate");
-        assert_eq!(response.organic_growth_pct, response.annualized_cagr);
+        // The generic handler and the by-name dispatcher return the same response
+        let arguments = serde_json::json!({ "revenue_prior": "100", "revenue_current": "112" });
+        let params: OrganicGrowthParams = serde_json::from_value(arguments.clone()).unwrap();
+        let result = engine.call_calculation::<OrganicGrowth>(Parameters(params)).unwrap();
+        assert_eq!(result.structured_content, Some(run_calculation_internal("calculate_organic_growth", arguments).unwrap()));
     }
 
     #[tokio::test]
-    async fn test_calculate_organic_growth_negative_prior() {
+    async fn test_batch_calculate() {
         let engine = FinanceEngine::new();
-        let params = OrganicGrowthParams {
-            revenue_prior: "0".to_string(),
-            revenue_current: "53.0".to_string(),
-        };
-        
-        let result = engine.calculate_organic_growth(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        assert!(json_text.contains("Prior period revenue must be positive"));
+        let requests = || METRICS.requests_total.get();
+        let invalid_inputs = || METRICS.errors_by_code.with_label_values(&["INVALID_INPUT"]).get();
+        let (requests_before, invalid_before) = (requests(), invalid_inputs());
+
+        let params: BatchCalculateParams = serde_json::from_value(serde_json::json!({
+            "items": [
+                { "id": "acme", "function_name": "calculate_organic_growth",
+                  "arguments": { "revenue_prior": "100", "revenue_current": "112" } },
+                { "id": "globex", "function_name": "calculate_organic_growth",
+                  "arguments": { "revenue_prior": "abc", "revenue_current": "112" } },
+                { "function_name": "calculate_nothing", "arguments": {} },
+                { "id": "initech", "function_name": "calculate_hhi_and_diversification",
+                  "arguments": { "revenues": ["500", "300", "200"] } }
+            ],
+            "max_concurrency": 2
+        }))
+        .unwrap();
+        let result = engine.batch_calculate(Parameters(params)).await.unwrap();
+        assert_eq!(result.is_error, Some(false));
+        let response: BatchCalculateResponse = serde_json::from_value(result.structured_content.unwrap()).unwrap();
+        assert_eq!((response.total_items, response.succeeded, response.failed), (4, 2, 2));
+        assert_eq!(response.concurrency, 2);
+
+        // Results keep request order and ids, failures stay in their own entry
+        let ids: Vec<Option<&str>> = response.results.iter().map(|r| r.id.as_deref()).collect();
+        assert_eq!(ids, vec![Some("acme"), Some("globex"), None, Some("initech")]);
+        assert_eq!(response.results[0].result.as_ref().unwrap()["organic_growth_pct"], serde_json::json!(12.0));
+        assert_eq!(response.results[1].error.as_ref().unwrap().field.as_deref(), Some("revenue_prior"));
+        assert_eq!(response.results[2].error.as_ref().unwrap().field.as_deref(), Some("function_name"));
+        assert_eq!(response.results[3].result.as_ref().unwrap()["hhi"], serde_json::json!(0.38));
+
+        // Every item is counted like a single call, plus the batch itself
+        assert!(requests() >= requests_before + 5.0);
+        assert!(invalid_inputs() >= invalid_before + 2.0);
     }
 
     #[tokio::test]
-    async fn test_calculate_organic_growth_declining() {
+    async fn test_calculation_pipeline() {
+        // Calculations without extractable inputs are refused before any network call
         let engine = FinanceEngine::new();
-        let params = OrganicGrowthParams {
-            revenue_prior: "53.0".to_string(),
-            revenue_current: "48.0".to_string(),
-        };
-        
-        let result = engine.calculate_organic_growth(Parameters(params)).await;
-        assert!(result.is_ok());
-        
-        let call_result = result.unwrap();
-        let content = call_result.content;
-        let json_text = content[0].raw.as_text().unwrap().text.as_str();
-        let response: OrganicGrowthResponse = serde_json::from_str(json_text).unwrap();
-        
-        assert!(response.organic_growth_rate < 0.0);
-        assert_eq!(response.growth_rating, "Declining");
+        let params: CalculationPipelineParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_hhi_and_diversification",
+            "company_name": "Acme"
+        }))
+        .unwrap();
+        let result = engine.run_calculation_pipeline(Parameters(params)).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let json_text = result.content[0].raw.as_text().unwrap().text.as_str();
+        let error: FinanceError = serde_json::from_str(json_text).unwrap();
+        assert_eq!(error.field.as_deref(), Some("function_name"));
+        assert!(error.message.contains("calculate_organic_growth"));
     }
 
     #[test]
@@ -2085,7 +1264,6 @@ mod tests {
         assert_eq!(parsed.ranker, None);
     }
 
-
     #[test]
     fn test_generate_query_for_function() {
         // Test organic growth
@@ -2106,7 +1284,10 @@ mod tests {
 
         // Test invalid function
         let result = FinanceEngine::generate_query_for_function("invalid_function", "Company");
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("Unknown function name"));
+        let error = result.unwrap_err();
+        assert!(error.contains("Unknown function name"));
+        for name in calculation_names() {
+            assert!(error.contains(name), "missing {}", name);
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,451 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+use super::calculation::{converter, money, Calculation};
+use super::business_metrics::growth_rating;
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyOptions, Period};
+use super::money::{decimal_from_f64, decimal_to_f64, round_ratio, scale_money, MoneyRounding};
+use super::parsing::{
+    deserialize_bool_flexible_opt, deserialize_flexible_f64, deserialize_flexible_f64_opt,
+    parse_f64_from_string, sanitize_for_error_message, FieldParser, NumberUnit,
+};
+
+// =================== DATA STRUCTURES ===================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PriceIndexPoint {
+    #[schemars(description = "Period label, usually the year (e.g., '2023')")]
+    pub period: String,
+    #[schemars(description = "CPI or GDP deflator level for the period (any base, e.g., 2015 = 100)")]
+    pub index: f64,
+}
+
+/// Optional inflation adjustment, flattened into the parameters of the growth tools
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct InflationOptions {
+    #[schemars(description = "Deflate the nominal growth into real growth. Default: false")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt", skip_serializing_if = "Option::is_none")]
+    pub adjust_for_inflation: Option<bool>,
+    #[schemars(description = "Inflation over the growth period as a ratio (e.g., 0.08 or '8%'). Takes precedence over price_index")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt", skip_serializing_if = "Option::is_none")]
+    pub inflation_rate: Option<String>,
+    #[schemars(description = "CPI or deflator series; falls back to the PRICE_INDEX_FILE CSV when omitted")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_index: Option<Vec<PriceIndexPoint>>,
+    #[schemars(description = "Period of the prior value in the price index (default: second to last period)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prior_period: Option<String>,
+    #[schemars(description = "Period of the current value in the price index (default: last period)")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_period: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct InflationAdjustment {
+    #[schemars(description = "Inflation over the growth period as decimal")]
+    pub inflation_rate: f64,
+    #[schemars(description = "Inflation over the growth period as percentage")]
+    pub inflation_pct: f64,
+    #[schemars(description = "Nominal growth as percentage")]
+    pub nominal_growth_pct: f64,
+    #[schemars(description = "Real growth as decimal: (1 + nominal) / (1 + inflation) - 1")]
+    pub real_growth_rate: f64,
+    #[schemars(description = "Real growth as percentage")]
+    pub real_growth_pct: f64,
+    #[schemars(description = "Price index period of the prior value, when a series was used")]
+    pub prior_period: Option<String>,
+    #[schemars(description = "Price index period of the current value, when a series was used")]
+    pub current_period: Option<String>,
+    #[schemars(description = "Where inflation came from: inflation_rate, request, or file")]
+    pub index_source: String,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 8 for 8% inflation)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// Function: calculate_inflation_adjusted_growth
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct PeriodValue {
+    #[schemars(description = "Period label matching the price index (e.g., '2023')")]
+    pub period: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Nominal value for the period (number or currency-tagged amount)")]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct InflationAdjustedGrowthParams {
+    #[schemars(description = "Nominal values in chronological order, one per year")]
+    pub values: Vec<PeriodValue>,
+    #[schemars(description = "CPI or deflator series covering every period; falls back to the PRICE_INDEX_FILE CSV when omitted")]
+    #[serde(default)]
+    pub price_index: Option<Vec<PriceIndexPoint>>,
+    #[schemars(description = "Period whose prices values are restated to (default: last period)")]
+    #[serde(default)]
+    pub base_period: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RealValuePoint {
+    #[schemars(description = "Period label")]
+    pub period: String,
+    #[schemars(description = "Nominal value", with = "String")]
+    pub nominal_value: Decimal,
+    #[schemars(description = "Price index level for the period")]
+    pub price_index: f64,
+    #[schemars(description = "Value restated to base period prices", with = "String")]
+    pub real_value: Decimal,
+    #[schemars(description = "Nominal growth versus the previous period as percentage")]
+    pub nominal_growth_pct: Option<f64>,
+    #[schemars(description = "Inflation versus the previous period as percentage")]
+    pub inflation_pct: Option<f64>,
+    #[schemars(description = "Real growth versus the previous period as percentage")]
+    pub real_growth_pct: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct InflationAdjustedGrowthResponse {
+    #[schemars(description = "Period whose prices real values are expressed in")]
+    pub base_period: String,
+    #[schemars(description = "Nominal and real values per period")]
+    pub periods: Vec<RealValuePoint>,
+    #[schemars(description = "Nominal growth from first to last period as percentage")]
+    pub nominal_growth_pct: f64,
+    #[schemars(description = "Real growth from first to last period as percentage")]
+    pub real_growth_pct: f64,
+    #[schemars(description = "Cumulative inflation from first to last period as percentage")]
+    pub cumulative_inflation_pct: f64,
+    #[schemars(description = "Nominal compound annual growth rate as percentage")]
+    pub nominal_cagr_pct: f64,
+    #[schemars(description = "Real compound annual growth rate as percentage")]
+    pub real_cagr_pct: f64,
+    #[schemars(description = "Average annual inflation as percentage")]
+    pub average_inflation_pct: f64,
+    #[schemars(description = "Real growth rating: Exceptional, Strong, Moderate, Weak, or Declining")]
+    pub real_growth_rating: String,
+    #[schemars(description = "Where the price index came from: request or file")]
+    pub index_source: String,
+    #[schemars(description = "Plain language comparison of nominal and real growth")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How amounts written with magnitude suffixes or accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+}
+
+// =================== PRICE INDEX ===================
+
+/// Parse a `period,index` CSV; a header row and `#` comment lines are skipped
+fn parse_price_index_csv(contents: &str) -> Result<Vec<PriceIndexPoint>, String> {
+    let mut points = Vec::new();
+    let mut header_allowed = true;
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_first_row = std::mem::replace(&mut header_allowed, false);
+
+        let mut columns = line.split(',').map(|c| c.trim().trim_matches('"'));
+        let (Some(period), Some(index)) = (columns.next(), columns.next()) else {
+            return Err(format!("Line {}: expected 'period,index'", line_no + 1));
+        };
+        match parse_f64_from_string(index) {
+            Ok(index) => points.push(PriceIndexPoint { period: period.to_string(), index }),
+            Err(_) if is_first_row => continue,
+            Err(_) => {
+                return Err(format!(
+                    "Line {}: invalid index '{}'",
+                    line_no + 1,
+                    sanitize_for_error_message(index)
+                ))
+            }
+        }
+    }
+    Ok(points)
+}
+
+fn load_price_index_file() -> Result<Option<Vec<PriceIndexPoint>>, String> {
+    let Ok(path) = env::var("PRICE_INDEX_FILE") else {
+        return Ok(None);
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read PRICE_INDEX_FILE '{}': {}", path, e))?;
+    parse_price_index_csv(&contents)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse PRICE_INDEX_FILE '{}': {}", path, e))
+}
+
+/// Price index series from the request, or from PRICE_INDEX_FILE, with its source label
+fn resolve_price_index(inline: Option<&Vec<PriceIndexPoint>>) -> Result<(Vec<PriceIndexPoint>, &'static str), FinanceError> {
+    let (series, source) = match inline {
+        Some(series) => (series.clone(), "request"),
+        None => match load_price_index_file().map_err(FinanceError::upstream_unavailable)? {
+            Some(series) => (series, "file"),
+            None => {
+                return Err(FinanceError::insufficient_data("No price index: provide price_index or set PRICE_INDEX_FILE")
+                    .with_field("price_index"))
+            }
+        },
+    };
+
+    for point in series.iter() {
+        if !point.index.is_finite() || point.index <= 0.0 {
+            return Err(FinanceError::out_of_range(
+                "price_index",
+                Some(0.0),
+                None,
+                format!("Price index for period '{}' must be positive", sanitize_for_error_message(&point.period)),
+            ));
+        }
+    }
+    Ok((series, source))
+}
+
+fn index_for(series: &[PriceIndexPoint], period: &str) -> Result<f64, FinanceError> {
+    series
+        .iter()
+        .find(|p| p.period.trim() == period.trim())
+        .map(|p| p.index)
+        .ok_or_else(|| {
+            FinanceError::insufficient_data(format!("No price index for period '{}'", sanitize_for_error_message(period)))
+                .with_field("price_index")
+        })
+}
+
+// =================== CALCULATION ===================
+
+/// Deflate a nominal growth rate when the options ask for it
+pub fn apply_inflation_adjustment(
+    nominal_growth: f64,
+    options: &InflationOptions,
+) -> Result<Option<InflationAdjustment>, FinanceError> {
+    if !options.adjust_for_inflation.unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let mut units = FieldParser::default();
+    let (inflation, prior_period, current_period, source) = match options.inflation_rate.as_deref() {
+        Some(rate) => {
+            let rate = units
+                .parse(rate, "inflation_rate", NumberUnit::Ratio)
+                .map_err(|e| FinanceError::invalid_field("inflation_rate", e))?;
+            (rate, None, None, "inflation_rate")
+        }
+        None => {
+            let (series, source) = resolve_price_index(options.price_index.as_ref())?;
+            let last = |offset: usize| {
+                series
+                    .len()
+                    .checked_sub(offset)
+                    .map(|i| series[i].period.clone())
+                    .ok_or_else(|| {
+                        FinanceError::insufficient_data("Price index needs at least two periods").with_field("price_index")
+                    })
+            };
+            let prior = options.prior_period.clone().map_or_else(|| last(2), Ok)?;
+            let current = options.current_period.clone().map_or_else(|| last(1), Ok)?;
+            let inflation = index_for(&series, &current)? / index_for(&series, &prior)? - 1.0;
+            (inflation, Some(prior), Some(current), source)
+        }
+    };
+
+    if inflation <= -1.0 {
+        return Err(FinanceError::out_of_range("inflation_rate", Some(-1.0), None, "Inflation rate must be greater than -100%"));
+    }
+
+    let real_growth = (1.0 + nominal_growth) / (1.0 + inflation) - 1.0;
+    Ok(Some(InflationAdjustment {
+        inflation_rate: round_ratio(inflation, 4),
+        inflation_pct: round_ratio(inflation * 100.0, 2),
+        nominal_growth_pct: round_ratio(nominal_growth * 100.0, 2),
+        real_growth_rate: round_ratio(real_growth, 4),
+        real_growth_pct: round_ratio(real_growth * 100.0, 2),
+        prior_period,
+        current_period,
+        index_source: source.to_string(),
+        warnings: units.take_warnings(),
+    }))
+}
+
+/// Restate a nominal series to base period prices and compare nominal with real growth
+pub fn calculate_inflation_adjusted_growth_internal(
+    values: Vec<(String, Decimal)>,
+    price_index: Option<Vec<PriceIndexPoint>>,
+    base_period: Option<String>,
+    rounding: MoneyRounding,
+) -> Result<InflationAdjustedGrowthResponse, FinanceError> {
+    if values.len() < 2 {
+        return Err(FinanceError::insufficient_data("At least two periods are required").with_field("values"));
+    }
+    for (i, (period, _)) in values.iter().enumerate() {
+        if values[..i].iter().any(|(p, _)| p.trim() == period.trim()) {
+            return Err(FinanceError::invalid_input(format!("Duplicate period '{}'", sanitize_for_error_message(period)))
+                .with_field(&format!("values[{}].period", i)));
+        }
+    }
+
+    let (series, source) = resolve_price_index(price_index.as_ref())?;
+    let base_period = base_period.unwrap_or_else(|| values[values.len() - 1].0.clone());
+    let base_index = index_for(&series, &base_period)?;
+
+    let mut periods: Vec<RealValuePoint> = Vec::with_capacity(values.len());
+    let mut raw: Vec<(f64, f64, f64)> = Vec::with_capacity(values.len());
+    for (period, nominal) in values.iter() {
+        let index = index_for(&series, period)?;
+        let real = decimal_from_f64(base_index / index)
+            .and_then(|deflator| scale_money(*nominal, deflator))
+            .map_err(|e| FinanceError::invalid_field("values", e))?;
+        let (nominal_f64, real_f64) = (decimal_to_f64(*nominal), decimal_to_f64(real));
+
+        let change = |current: f64, previous: f64| (previous != 0.0).then(|| round_ratio((current / previous - 1.0) * 100.0, 2));
+        let (nominal_growth_pct, inflation_pct, real_growth_pct) = match raw.last() {
+            Some(&(prev_nominal, prev_index, prev_real)) => (
+                change(nominal_f64, prev_nominal),
+                change(index, prev_index),
+                change(real_f64, prev_real),
+            ),
+            None => (None, None, None),
+        };
+
+        raw.push((nominal_f64, index, real_f64));
+        periods.push(RealValuePoint {
+            period: period.clone(),
+            nominal_value: rounding.round(*nominal),
+            price_index: index,
+            real_value: rounding.round(real),
+            nominal_growth_pct,
+            inflation_pct,
+            real_growth_pct,
+        });
+    }
+
+    let (first_nominal, first_index, first_real) = raw[0];
+    let (last_nominal, last_index, last_real) = raw[raw.len() - 1];
+    if first_nominal <= 0.0 || last_nominal <= 0.0 {
+        return Err(FinanceError::out_of_range("values", Some(0.0), None, "First and last period values must be positive"));
+    }
+
+    let years = (raw.len() - 1) as f64;
+    let nominal_growth = last_nominal / first_nominal - 1.0;
+    let real_growth = last_real / first_real - 1.0;
+    let cumulative_inflation = last_index / first_index - 1.0;
+    let nominal_cagr = (last_nominal / first_nominal).powf(1.0 / years) - 1.0;
+    let real_cagr = (last_real / first_real).powf(1.0 / years) - 1.0;
+    let average_inflation = (last_index / first_index).powf(1.0 / years) - 1.0;
+
+    let interpretation = if real_cagr < 0.0 && nominal_cagr > 0.0 {
+        format!(
+            "Nominal growth of {:.1}% a year is an illusion: inflation of {:.1}% a year means real value shrank {:.1}% a year.",
+            nominal_cagr * 100.0,
+            average_inflation * 100.0,
+            real_cagr.abs() * 100.0
+        )
+    } else {
+        format!(
+            "Nominal CAGR of {:.1}% is {:.1}% in real terms after average inflation of {:.1}% a year.",
+            nominal_cagr * 100.0,
+            real_cagr * 100.0,
+            average_inflation * 100.0
+        )
+    };
+
+    Ok(InflationAdjustedGrowthResponse {
+        base_period,
+        periods,
+        nominal_growth_pct: round_ratio(nominal_growth * 100.0, 2),
+        real_growth_pct: round_ratio(real_growth * 100.0, 2),
+        cumulative_inflation_pct: round_ratio(cumulative_inflation * 100.0, 2),
+        nominal_cagr_pct: round_ratio(nominal_cagr * 100.0, 2),
+        real_cagr_pct: round_ratio(real_cagr * 100.0, 2),
+        average_inflation_pct: round_ratio(average_inflation * 100.0, 2),
+        real_growth_rating: growth_rating(real_cagr).to_string(),
+        index_source: source.to_string(),
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Real growth of a nominal series deflated by a price index (`calculate_inflation_adjusted_growth`)
+pub struct InflationAdjustedGrowth;
+
+impl Calculation for InflationAdjustedGrowth {
+    const NAME: &'static str = "calculate_inflation_adjusted_growth";
+    const DESCRIPTION: &'static str = "Deflate a nominal series (revenue, EBITDA, prices) by a CPI or GDP deflator series to separate real from nominal growth, e.g. in high-inflation markets. Takes yearly values with period labels and a price index given inline or read from the local PRICE_INDEX_FILE CSV. Returns values restated to base period prices, period-over-period nominal, inflation and real growth, nominal and real CAGR, cumulative inflation, and a real growth rating (Exceptional >15%, Strong 10-15%, Moderate 5-10%, Weak 0-5%, Declining <0%). The growth tools accept adjust_for_inflation for a single-period real growth figure. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "real_cagr_pct";
+    const BAND_FIELD: Option<&'static str> = Some("real_growth_rating");
+
+    type Params = InflationAdjustedGrowthParams;
+    type Response = InflationAdjustedGrowthResponse;
+
+    fn calculate(params: InflationAdjustedGrowthParams) -> Result<InflationAdjustedGrowthResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut values = Vec::with_capacity(params.values.len());
+        for (i, point) in params.values.iter().enumerate() {
+            values.push((point.period.clone(), money(&mut fx, &point.value, &format!("values[{}]", i), Period::Current)?));
+        }
+        let mut result =
+            calculate_inflation_adjusted_growth_internal(values, params.price_index, params.base_period, fx.rounding())?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_inflation_adjusted_growth() {
+        let params: InflationAdjustedGrowthParams = serde_json::from_value(serde_json::json!({
+            "values": [
+                {"period": "2021", "value": 100},
+                {"period": "2022", "value": "150"},
+                {"period": "2023", "value": 200}
+            ],
+            "price_index": [
+                {"period": "2021", "index": 100},
+                {"period": "2022", "index": 160},
+                {"period": "2023", "index": 250}
+            ],
+            "base_period": "2021"
+        }))
+        .unwrap();
+
+        let response = InflationAdjustedGrowth::calculate(params).unwrap();
+
+        // Doubling nominal revenue while prices rise 2.5x is a 20% real decline
+        assert_eq!(response.nominal_growth_pct, 100.0);
+        assert_eq!(response.real_growth_pct, -20.0);
+        assert_eq!(response.cumulative_inflation_pct, 150.0);
+        assert_eq!(response.real_cagr_pct, -10.56);
+        assert_eq!(response.real_growth_rating, "Declining");
+        assert_eq!(response.periods[2].real_value, Decimal::from(80));
+        assert_eq!(response.periods[1].real_growth_pct, Some(-6.25));
+        assert_eq!(response.index_source, "request");
+        assert!(response.interpretation.contains("illusion"));
+    }
+
+    #[test]
+    fn test_inflation_adjusted_growth_missing_period() {
+        let params: InflationAdjustedGrowthParams = serde_json::from_value(serde_json::json!({
+            "values": [{"period": "2022", "value": 100}, {"period": "2023", "value": 110}],
+            "price_index": [{"period": "2022", "index": 100}]
+        }))
+        .unwrap();
+
+        let error = InflationAdjustedGrowth::calculate(params).unwrap_err();
+        assert!(error.message.contains("No price index for period '2023'"));
+    }
+}
@@ -0,0 +1,138 @@
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::str::FromStr;
+
+use super::currency::split_currency_tag;
+use super::money::decimal_to_f64;
+use super::parsing::{parse_f64_keeping_percent, NumberLocale};
+
+// =================== JSON ARGUMENT PATHS ===================
+//
+// Tool arguments are addressed with dotted paths so that meta tools (simulation,
+// scenarios, ...) can override any numeric input of another calculation:
+//   "revenue_growth"           -> top-level field
+//   "revenues.2"               -> third element of an array
+//   "segments.cloud.revenue"   -> nested object field
+//
+// Values keep the notation of the input they replace: an input written as "9%" is read as 9
+// and a new value of 9.9 is written back as "9.9%", so the calculation still reads a percentage.
+// Currency-tagged amounts keep their currency: "EUR 500" varied to 550 is written as "EUR 550".
+
+/// Convert a dotted input path into a JSON pointer
+fn to_pointer(path: &str) -> Result<String, String> {
+    if path.trim().is_empty() {
+        return Err("Input path cannot be empty".to_string());
+    }
+
+    Ok(path
+        .split('.')
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect())
+}
+
+fn not_found(path: &str) -> String {
+    format!("Input path '{}' not found in base arguments", path)
+}
+
+/// Overwrite the numeric input at `path` with `number`, in the notation of the value it replaces
+pub fn set_number_at_path(value: &mut Value, path: &str, number: f64) -> Result<(), String> {
+    let locale = request_locale(value);
+    let node = value.pointer_mut(&to_pointer(path)?).ok_or_else(|| not_found(path))?;
+    if node.is_object() || node.is_array() {
+        return Err(format!("Input path '{}' does not point to a numeric value", path));
+    }
+    let json_number =
+        serde_json::Number::from_f64(number).ok_or_else(|| format!("Value for '{}' is not a finite number", path))?;
+
+    let text = match locale {
+        Some(NumberLocale::DecimalComma) => number.to_string().replace('.', ","),
+        _ => number.to_string(),
+    };
+    *node = match number_from_value(node, locale).map(|(_, notation)| notation) {
+        Some(Notation::Percent) => Value::String(format!("{}%", text)),
+        Some(Notation::Currency(code)) => Value::String(format!("{} {}", code, text)),
+        _ => Value::Number(json_number),
+    };
+    Ok(())
+}
+
+/// Read the numeric input at `path`, accepting numbers and numeric strings; percentages are
+/// read as written ("9%" is 9)
+pub fn get_number_at_path(value: &Value, path: &str) -> Result<f64, String> {
+    let locale = request_locale(value);
+    let node = value.pointer(&to_pointer(path)?).ok_or_else(|| not_found(path))?;
+    number_from_value(node, locale)
+        .map(|(number, _)| number)
+        .ok_or_else(|| format!("Input path '{}' does not point to a numeric value", path))
+}
+
+/// Number held by a calculation output field: a JSON number, or an exact decimal amount
+/// serialized as a string ("1250.00")
+pub fn output_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => Decimal::from_str(s).ok().map(decimal_to_f64),
+        _ => None,
+    }
+}
+
+/// List the dotted paths of every numeric leaf in the arguments, in sorted order
+pub fn numeric_leaf_paths(value: &Value) -> Vec<String> {
+    fn walk(value: &Value, prefix: &str, locale: Option<NumberLocale>, paths: &mut Vec<String>) {
+        let join = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter() {
+                    walk(child, &join(key), locale, paths);
+                }
+            }
+            Value::Array(items) => {
+                for (index, child) in items.iter().enumerate() {
+                    walk(child, &join(&index.to_string()), locale, paths);
+                }
+            }
+            leaf => {
+                if !prefix.is_empty() && number_from_value(leaf, locale).is_some() {
+                    paths.push(prefix.to_string());
+                }
+            }
+        }
+    }
+
+    let mut paths = Vec::new();
+    walk(value, "", request_locale(value), &mut paths);
+    paths.sort();
+    paths
+}
+
+/// Locale hint of the calculation arguments, used to read and write their numbers
+fn request_locale(arguments: &Value) -> Option<NumberLocale> {
+    arguments
+        .get("locale")
+        .and_then(Value::as_str)
+        .and_then(|tag| NumberLocale::from_tag(tag).ok())
+}
+
+/// How a numeric leaf was written, so a replacement can be written the same way
+enum Notation {
+    Plain,
+    Percent,
+    Currency(String),
+}
+
+/// Number held by a leaf and the notation it was written in
+fn number_from_value(value: &Value, locale: Option<NumberLocale>) -> Option<(f64, Notation)> {
+    match value {
+        Value::Number(n) => n.as_f64().map(|number| (number, Notation::Plain)),
+        Value::String(s) => match split_currency_tag(s).ok()? {
+            (rest, Some(code)) => match parse_f64_keeping_percent(&rest, locale).ok()? {
+                (number, false) => Some((number, Notation::Currency(code))),
+                (_, true) => None,
+            },
+            (_, None) => parse_f64_keeping_percent(s, locale)
+                .ok()
+                .map(|(number, percent)| (number, if percent { Notation::Percent } else { Notation::Plain })),
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,674 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::calculation::{converter, fx_failure, field, field_parser, money, moneys, rate_or, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{decimal_from_f64, decimal_to_f64, round_ratio, scale_money};
+use super::parsing::{
+    deserialize_bool_flexible_opt, deserialize_flexible_f64, deserialize_flexible_f64_opt, deserialize_flexible_f64_vec,
+    FieldParser, NumberUnit,
+};
+
+const DEFAULT_TAX_RATE: f64 = 0.25;
+const HIGH_ENTRY_LEVERAGE: f64 = 6.0;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_lbo_returns
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct DebtTranche {
+    #[schemars(description = "Tranche name (e.g., 'Term Loan B', 'Senior Notes')")]
+    pub name: String,
+    #[schemars(description = "Principal amount, number or currency-tagged (alternative to ebitda_multiple)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub amount: Option<String>,
+    #[schemars(description = "Principal as a multiple of entry EBITDA (alternative to amount)")]
+    #[serde(default)]
+    pub ebitda_multiple: Option<f64>,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Annual cash interest rate as a ratio (e.g., 0.08, '8%' or '800bps')")]
+    pub interest_rate: String,
+    #[schemars(description = "Mandatory annual amortization as a ratio of original principal (e.g., 0.05 or '5%', default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub amortization_pct: Option<String>,
+    #[schemars(description = "Whether excess cash is swept to prepay this tranche, in tranche order (default: true)")]
+    #[serde(default, deserialize_with = "deserialize_bool_flexible_opt")]
+    pub sweep: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct LboParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "EBITDA at entry (last twelve months), number or currency-tagged")]
+    pub entry_ebitda: String,
+    #[schemars(description = "Entry enterprise value as a multiple of entry EBITDA (e.g., 10.0)")]
+    pub entry_multiple: f64,
+    #[serde(deserialize_with = "deserialize_flexible_f64_vec")]
+    #[schemars(description = "Projected EBITDA for each year after entry, in order")]
+    pub ebitda_projections: Vec<String>,
+    #[schemars(description = "Debt tranches in seniority order")]
+    pub debt_tranches: Vec<DebtTranche>,
+    #[schemars(description = "Share of excess free cash flow swept to prepay debt as a ratio (e.g., 0.75 or '75%', default: 1.0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cash_sweep_pct: Option<String>,
+    #[schemars(description = "Exit enterprise value as a multiple of exit-year EBITDA")]
+    pub exit_multiple: f64,
+    #[schemars(description = "Hold period in years (default: number of EBITDA projections)")]
+    #[serde(default)]
+    pub hold_period_years: Option<usize>,
+    #[schemars(description = "Cash tax rate on EBITDA less D&A and interest as a ratio (e.g., 0.25 or '25%', default: 0.25)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub tax_rate: Option<String>,
+    #[schemars(description = "Capital expenditure as a ratio of EBITDA, also used as D&A for taxes (e.g., 0.2 or '20%', default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub capex_pct_of_ebitda: Option<String>,
+    #[schemars(description = "Transaction fees as a ratio of entry enterprise value, funded by equity (e.g., 0.02 or '2%', default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub transaction_fees_pct: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct FundingSource {
+    #[schemars(description = "Debt tranche name or 'Sponsor equity'")]
+    pub name: String,
+    #[schemars(description = "Amount raised", with = "String")]
+    pub amount: Decimal,
+    #[schemars(description = "Share of total sources as percentage")]
+    pub pct_of_total: f64,
+    #[schemars(description = "Amount as a multiple of entry EBITDA")]
+    pub ebitda_multiple: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SourcesAndUses {
+    #[schemars(description = "Purchase enterprise value (entry multiple x entry EBITDA)", with = "String")]
+    pub enterprise_value: Decimal,
+    #[schemars(description = "Transaction fees", with = "String")]
+    pub transaction_fees: Decimal,
+    #[schemars(description = "Total uses of funds", with = "String")]
+    pub total_uses: Decimal,
+    #[schemars(description = "Debt tranches and sponsor equity funding the uses")]
+    pub sources: Vec<FundingSource>,
+    #[schemars(description = "Total debt raised", with = "String")]
+    pub total_debt: Decimal,
+    #[schemars(description = "Sponsor equity cheque (total uses - total debt)", with = "String")]
+    pub sponsor_equity: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct TrancheYear {
+    #[schemars(description = "Tranche name")]
+    pub name: String,
+    #[schemars(description = "Interest on the opening balance", with = "String")]
+    pub interest: Decimal,
+    #[schemars(description = "Mandatory amortization paid", with = "String")]
+    pub mandatory_repayment: Decimal,
+    #[schemars(description = "Optional prepayment from the cash sweep", with = "String")]
+    pub sweep_repayment: Decimal,
+    #[schemars(description = "Balance at year end", with = "String")]
+    pub ending_balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct LboYear {
+    #[schemars(description = "Year after entry (1 = first year)")]
+    pub year: usize,
+    #[schemars(description = "EBITDA for the year", with = "String")]
+    pub ebitda: Decimal,
+    #[schemars(description = "Total cash interest", with = "String")]
+    pub interest_expense: Decimal,
+    #[schemars(description = "Cash taxes", with = "String")]
+    pub taxes: Decimal,
+    #[schemars(description = "Capital expenditure", with = "String")]
+    pub capex: Decimal,
+    #[schemars(description = "Free cash flow available for debt service (EBITDA - interest - taxes - capex)", with = "String")]
+    pub free_cash_flow: Decimal,
+    #[schemars(description = "Total debt repaid in the year", with = "String")]
+    pub debt_repayment: Decimal,
+    #[schemars(description = "Per-tranche interest, repayments and balances")]
+    pub tranches: Vec<TrancheYear>,
+    #[schemars(description = "Total debt at year end", with = "String")]
+    pub total_debt: Decimal,
+    #[schemars(description = "Cash balance at year end (negative means a funding shortfall)", with = "String")]
+    pub cash: Decimal,
+    #[schemars(description = "Net debt / EBITDA at year end (absent when EBITDA is not positive)")]
+    pub net_leverage: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct LboExit {
+    #[schemars(description = "Exit year")]
+    pub year: usize,
+    #[schemars(description = "Exit-year EBITDA", with = "String")]
+    pub ebitda: Decimal,
+    #[schemars(description = "Exit multiple applied")]
+    pub multiple: f64,
+    #[schemars(description = "Exit enterprise value", with = "String")]
+    pub enterprise_value: Decimal,
+    #[schemars(description = "Net debt repaid out of exit proceeds", with = "String")]
+    pub net_debt: Decimal,
+    #[schemars(description = "Equity value to the sponsor (enterprise value - net debt, floored at zero)", with = "String")]
+    pub equity_value: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ReturnsAttribution {
+    #[schemars(description = "Value from EBITDA growth at the entry multiple", with = "String")]
+    pub ebitda_growth: Decimal,
+    #[schemars(description = "Value from the change in multiple applied to exit EBITDA", with = "String")]
+    pub multiple_expansion: Decimal,
+    #[schemars(description = "Value from net debt reduction", with = "String")]
+    pub deleveraging: Decimal,
+    #[schemars(description = "Transaction fees lost at entry (negative)", with = "String")]
+    pub fees: Decimal,
+    #[schemars(description = "Total equity value created (exit equity - sponsor equity before the zero floor)", with = "String")]
+    pub total_value_creation: Decimal,
+    #[schemars(description = "Share of value creation from EBITDA growth as percentage")]
+    pub ebitda_growth_pct: Option<f64>,
+    #[schemars(description = "Share of value creation from multiple expansion as percentage")]
+    pub multiple_expansion_pct: Option<f64>,
+    #[schemars(description = "Share of value creation from deleveraging as percentage")]
+    pub deleveraging_pct: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct LboResponse {
+    #[schemars(description = "Sources and uses of funds at entry")]
+    pub sources_and_uses: SourcesAndUses,
+    #[schemars(description = "Entry net debt / EBITDA")]
+    pub entry_leverage: f64,
+    #[schemars(description = "Yearly cash flow and debt paydown schedule")]
+    pub debt_schedule: Vec<LboYear>,
+    #[schemars(description = "Exit valuation")]
+    pub exit: LboExit,
+    #[schemars(description = "Multiple on invested capital (exit equity / sponsor equity)")]
+    pub moic: f64,
+    #[schemars(description = "Internal rate of return as decimal")]
+    pub irr: f64,
+    #[schemars(description = "Internal rate of return as percentage")]
+    pub irr_pct: f64,
+    #[schemars(description = "Returns rating: Exceptional (>=25% IRR), Strong (20-25%), Acceptable (15-20%), Weak (0-15%), or Loss (<0%)")]
+    pub returns_rating: String,
+    #[schemars(description = "Split of equity value creation into EBITDA growth, multiple expansion and deleveraging")]
+    pub returns_attribution: ReturnsAttribution,
+    #[schemars(description = "Structural risks such as high entry leverage or cash shortfalls")]
+    pub lbo_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of the returns")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 8 for an 8% interest rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn ratio_in_range(name: &str, value: f64, max: f64) -> Result<f64, FinanceError> {
+    if !value.is_finite() || !(0.0..=max).contains(&value) {
+        return Err(FinanceError::out_of_range(name, Some(0.0), Some(max), format!("{} must be between 0 and {}", name, max)));
+    }
+    Ok(value)
+}
+
+fn share_pct(part: Decimal, total: Decimal) -> Option<f64> {
+    (!total.is_zero()).then(|| round_ratio(decimal_to_f64(part) / decimal_to_f64(total) * 100.0, 2))
+}
+
+fn returns_rating(irr: f64) -> &'static str {
+    if irr >= 0.25 {
+        "Exceptional"
+    } else if irr >= 0.20 {
+        "Strong"
+    } else if irr >= 0.15 {
+        "Acceptable"
+    } else if irr >= 0.0 {
+        "Weak"
+    } else {
+        "Loss"
+    }
+}
+
+struct Tranche {
+    name: String,
+    principal: Decimal,
+    balance: Decimal,
+    rate: Decimal,
+    amortization_pct: Decimal,
+    sweep: bool,
+}
+
+/// Model a leveraged buyout: fund the purchase, service and sweep debt from free cash flow, then exit.
+///
+/// Interest accrues on opening balances to avoid circularity. Free cash flow first pays mandatory
+/// amortization; the cash_sweep_pct share of the remainder prepays sweep tranches in seniority order
+/// and the rest accumulates as cash, which is netted against debt at exit.
+pub fn calculate_lbo_returns_internal(
+    params: &LboParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<LboResponse, FinanceError> {
+    let rounding = fx.rounding();
+    let entry_ebitda = money(fx, &params.entry_ebitda, "entry_ebitda", Period::Current)?;
+    let projections = moneys(fx, &params.ebitda_projections, "ebitda_projections", Period::Current)?;
+
+    if entry_ebitda <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("entry_ebitda", Some(0.0), None, "Entry EBITDA must be positive"));
+    }
+    for (name, multiple) in [("entry_multiple", params.entry_multiple), ("exit_multiple", params.exit_multiple)] {
+        if multiple <= 0.0 {
+            return Err(FinanceError::out_of_range(name, Some(0.0), None, format!("{} must be positive", name)));
+        }
+    }
+    if projections.is_empty() {
+        return Err(
+            FinanceError::insufficient_data("At least one year of EBITDA projections is required")
+                .with_field("ebitda_projections"),
+        );
+    }
+    let hold = params.hold_period_years.unwrap_or(projections.len());
+    if hold == 0 || hold > projections.len() {
+        return Err(FinanceError::out_of_range(
+            "hold_period_years",
+            Some(1.0),
+            Some(projections.len() as f64),
+            format!(
+                "Hold period must be between 1 and {} years (the number of EBITDA projections)",
+                projections.len()
+            ),
+        ));
+    }
+
+    let decimal = |value: f64, name: &str| decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(name, e));
+    let scale = |amount: Decimal, factor: Decimal, name: &str| {
+        scale_money(amount, factor).map_err(|e| FinanceError::invalid_field(name, e))
+    };
+    let entry_multiple = decimal(params.entry_multiple, "entry_multiple")?;
+    let exit_multiple = decimal(params.exit_multiple, "exit_multiple")?;
+    let rate = |units: &mut FieldParser, value: Option<&str>, name: &str, default: f64| {
+        ratio_in_range(name, rate_or(units, value, name, default)?, 1.0).and_then(|r| decimal(r, name))
+    };
+    let sweep_pct = rate(units, params.cash_sweep_pct.as_deref(), "cash_sweep_pct", 1.0)?;
+    let tax_rate = rate(units, params.tax_rate.as_deref(), "tax_rate", DEFAULT_TAX_RATE)?;
+    let capex_pct = rate(units, params.capex_pct_of_ebitda.as_deref(), "capex_pct_of_ebitda", 0.0)?;
+    let fees_pct = rate(units, params.transaction_fees_pct.as_deref(), "transaction_fees_pct", 0.0)?;
+
+    // Sources and uses
+    let enterprise_value = scale(entry_ebitda, entry_multiple, "entry_multiple")?;
+    let transaction_fees = enterprise_value * fees_pct;
+    let total_uses = enterprise_value + transaction_fees;
+
+    let mut tranches = Vec::with_capacity(params.debt_tranches.len());
+    for tranche in params.debt_tranches.iter() {
+        let path = |field: &str| format!("debt_tranches.{}.{}", tranche.name, field);
+        let principal = match (tranche.amount.as_deref(), tranche.ebitda_multiple) {
+            (Some(amount), None) => money(fx, amount, &path("amount"), Period::Current)?,
+            (None, Some(multiple)) => {
+                scale(entry_ebitda, decimal(multiple, &path("ebitda_multiple"))?, &path("ebitda_multiple"))?
+            }
+            _ => {
+                return Err(FinanceError::invalid_input(format!(
+                    "Tranche '{}': provide exactly one of amount or ebitda_multiple",
+                    tranche.name
+                ))
+                .with_field(&format!("debt_tranches.{}", tranche.name)))
+            }
+        };
+        if principal.is_sign_negative() {
+            return Err(FinanceError::out_of_range(
+                &path("amount"),
+                Some(0.0),
+                None,
+                format!("Tranche '{}': principal cannot be negative", tranche.name),
+            ));
+        }
+        let interest_rate = ratio_in_range(
+            &path("interest_rate"),
+            field(units, &tranche.interest_rate, &path("interest_rate"), NumberUnit::Ratio)?,
+            1.0,
+        )?;
+        tranches.push(Tranche {
+            name: tranche.name.clone(),
+            principal,
+            balance: principal,
+            rate: decimal(interest_rate, &path("interest_rate"))?,
+            amortization_pct: rate(units, tranche.amortization_pct.as_deref(), &path("amortization_pct"), 0.0)?,
+            sweep: tranche.sweep.unwrap_or(true),
+        });
+    }
+
+    let total_debt: Decimal = tranches.iter().map(|t| t.principal).sum();
+    let sponsor_equity = total_uses - total_debt;
+    if sponsor_equity <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range(
+            "debt_tranches",
+            None,
+            Some(decimal_to_f64(total_uses)),
+            format!("Debt of {:.2} leaves no sponsor equity against total uses of {:.2}", total_debt, total_uses),
+        ));
+    }
+
+    let multiple_of_ebitda = |amount: Decimal| round_ratio(decimal_to_f64(amount) / decimal_to_f64(entry_ebitda), 2);
+    let mut sources: Vec<FundingSource> = tranches
+        .iter()
+        .map(|t| FundingSource {
+            name: t.name.clone(),
+            amount: rounding.round(t.principal),
+            pct_of_total: round_ratio(decimal_to_f64(t.principal / total_uses) * 100.0, 2),
+            ebitda_multiple: multiple_of_ebitda(t.principal),
+        })
+        .collect();
+    sources.push(FundingSource {
+        name: "Sponsor equity".to_string(),
+        amount: rounding.round(sponsor_equity),
+        pct_of_total: round_ratio(decimal_to_f64(sponsor_equity / total_uses) * 100.0, 2),
+        ebitda_multiple: multiple_of_ebitda(sponsor_equity),
+    });
+
+    // Debt schedule
+    let mut lbo_issues = Vec::new();
+    let entry_leverage = decimal_to_f64(total_debt) / decimal_to_f64(entry_ebitda);
+    if entry_leverage > HIGH_ENTRY_LEVERAGE {
+        lbo_issues.push(format!(
+            "Entry leverage of {:.1}x EBITDA exceeds {:.1}x; refinancing and covenant risk is elevated",
+            entry_leverage, HIGH_ENTRY_LEVERAGE
+        ));
+    }
+
+    let mut cash = Decimal::ZERO;
+    let mut debt_schedule = Vec::with_capacity(hold);
+    for (i, &ebitda) in projections.iter().take(hold).enumerate() {
+        let year = i + 1;
+        let interests: Vec<Decimal> = tranches.iter().map(|t| t.balance * t.rate).collect();
+        let interest_expense: Decimal = interests.iter().sum();
+        let capex = ebitda * capex_pct;
+        let taxes = ((ebitda - capex - interest_expense) * tax_rate).max(Decimal::ZERO);
+        let free_cash_flow = ebitda - interest_expense - taxes - capex;
+
+        let mut available = free_cash_flow + cash;
+        let mut mandatory = vec![Decimal::ZERO; tranches.len()];
+        for (t, paid) in tranches.iter_mut().zip(mandatory.iter_mut()) {
+            *paid = (t.principal * t.amortization_pct).min(t.balance);
+            t.balance -= *paid;
+            available -= *paid;
+        }
+
+        let excess = (free_cash_flow - mandatory.iter().sum::<Decimal>()).min(available).max(Decimal::ZERO);
+        let mut sweep_budget = excess * sweep_pct;
+        let mut swept = vec![Decimal::ZERO; tranches.len()];
+        for (t, paid) in tranches.iter_mut().zip(swept.iter_mut()) {
+            if !t.sweep || sweep_budget <= Decimal::ZERO {
+                continue;
+            }
+            *paid = sweep_budget.min(t.balance);
+            t.balance -= *paid;
+            sweep_budget -= *paid;
+            available -= *paid;
+        }
+        cash = available;
+
+        if cash < Decimal::ZERO {
+            lbo_issues.push(format!(
+                "Year {}: cash shortfall of {:.2} after debt service; a revolver or equity injection is needed",
+                year, -cash
+            ));
+        }
+
+        let year_debt: Decimal = tranches.iter().map(|t| t.balance).sum();
+        debt_schedule.push(LboYear {
+            year,
+            ebitda: rounding.round(ebitda),
+            interest_expense: rounding.round(interest_expense),
+            taxes: rounding.round(taxes),
+            capex: rounding.round(capex),
+            free_cash_flow: rounding.round(free_cash_flow),
+            debt_repayment: rounding.round(mandatory.iter().sum::<Decimal>() + swept.iter().sum::<Decimal>()),
+            tranches: tranches
+                .iter()
+                .enumerate()
+                .map(|(j, t)| TrancheYear {
+                    name: t.name.clone(),
+                    interest: rounding.round(interests[j]),
+                    mandatory_repayment: rounding.round(mandatory[j]),
+                    sweep_repayment: rounding.round(swept[j]),
+                    ending_balance: rounding.round(t.balance),
+                })
+                .collect(),
+            total_debt: rounding.round(year_debt),
+            cash: rounding.round(cash),
+            net_leverage: (ebitda > Decimal::ZERO)
+                .then(|| round_ratio(decimal_to_f64(year_debt - cash) / decimal_to_f64(ebitda), 2)),
+        });
+    }
+
+    // Exit
+    let exit_ebitda = projections[hold - 1];
+    let exit_value = scale(exit_ebitda, exit_multiple, "exit_multiple")?;
+    let exit_net_debt = tranches.iter().map(|t| t.balance).sum::<Decimal>() - cash;
+    let exit_equity_raw = exit_value - exit_net_debt;
+    let exit_equity = exit_equity_raw.max(Decimal::ZERO);
+    if exit_equity_raw <= Decimal::ZERO {
+        lbo_issues.push("Exit value does not cover net debt; the sponsor equity is wiped out".to_string());
+    }
+
+    let moic = decimal_to_f64(exit_equity) / decimal_to_f64(sponsor_equity);
+    let irr = moic.powf(1.0 / hold as f64) - 1.0;
+
+    // Value creation bridge: sums to exit equity - sponsor equity
+    let ebitda_growth = scale(exit_ebitda - entry_ebitda, entry_multiple, "entry_multiple")?;
+    let multiple_expansion = scale(exit_ebitda, exit_multiple - entry_multiple, "exit_multiple")?;
+    let deleveraging = total_debt - exit_net_debt;
+    let total_value_creation = exit_equity_raw - sponsor_equity;
+
+    let drivers = [
+        ("EBITDA growth", ebitda_growth),
+        ("multiple expansion", multiple_expansion),
+        ("deleveraging", deleveraging),
+    ];
+    let (main_driver, _) = drivers.iter().copied().max_by_key(|(_, value)| *value).unwrap_or(("EBITDA growth", Decimal::ZERO));
+    if multiple_expansion > Decimal::ZERO && multiple_expansion > ebitda_growth + deleveraging {
+        lbo_issues.push("Returns depend mainly on multiple expansion, which the sponsor does not control".to_string());
+    }
+
+    let rating = returns_rating(irr);
+    let interpretation = format!(
+        "{} returns: {:.2}x MOIC and {:.1}% IRR over {} years on {:.2} of sponsor equity. \
+         The largest value driver is {}.",
+        rating,
+        moic,
+        irr * 100.0,
+        hold,
+        sponsor_equity,
+        main_driver
+    );
+
+    Ok(LboResponse {
+        sources_and_uses: SourcesAndUses {
+            enterprise_value: rounding.round(enterprise_value),
+            transaction_fees: rounding.round(transaction_fees),
+            total_uses: rounding.round(total_uses),
+            sources,
+            total_debt: rounding.round(total_debt),
+            sponsor_equity: rounding.round(sponsor_equity),
+        },
+        entry_leverage: round_ratio(entry_leverage, 2),
+        debt_schedule,
+        exit: LboExit {
+            year: hold,
+            ebitda: rounding.round(exit_ebitda),
+            multiple: params.exit_multiple,
+            enterprise_value: rounding.round(exit_value),
+            net_debt: rounding.round(exit_net_debt),
+            equity_value: rounding.round(exit_equity),
+        },
+        moic: round_ratio(moic, 2),
+        irr: round_ratio(irr, 4),
+        irr_pct: round_ratio(irr * 100.0, 2),
+        returns_rating: rating.to_string(),
+        returns_attribution: ReturnsAttribution {
+            ebitda_growth: rounding.round(ebitda_growth),
+            multiple_expansion: rounding.round(multiple_expansion),
+            deleveraging: rounding.round(deleveraging),
+            fees: rounding.round(-transaction_fees),
+            total_value_creation: rounding.round(total_value_creation),
+            ebitda_growth_pct: share_pct(ebitda_growth, total_value_creation),
+            multiple_expansion_pct: share_pct(multiple_expansion, total_value_creation),
+            deleveraging_pct: share_pct(deleveraging, total_value_creation),
+        },
+        lbo_issues,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Leveraged buyout returns model (`calculate_lbo_returns`)
+pub struct Lbo;
+
+impl Calculation for Lbo {
+    const NAME: &'static str = "calculate_lbo_returns";
+    const DESCRIPTION: &'static str = "Model leveraged buyout returns. Takes entry EBITDA and multiple, yearly EBITDA projections, debt tranches in seniority order (amount or EBITDA multiple, interest rate, mandatory amortization, sweep eligibility), cash sweep %, exit multiple and hold period, plus optional tax rate, capex % of EBITDA and transaction fees. Returns sources and uses, a yearly cash flow and debt paydown schedule per tranche, exit equity, MOIC, IRR with a returns rating (Exceptional >=25%, Strong 20-25%, Acceptable 15-20%, Weak 0-15%, Loss <0%), and a returns attribution split into EBITDA growth, multiple expansion and deleveraging. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "irr";
+    const BAND_FIELD: Option<&'static str> = Some("returns_rating");
+
+    type Params = LboParams;
+    type Response = LboResponse;
+
+    fn calculate(params: LboParams) -> Result<LboResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = calculate_lbo_returns_internal(&params, &mut fx, &mut units).map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::error::ErrorCode;
+
+    #[test]
+    fn test_calculate_lbo_returns_deleveraging() {
+        let params: LboParams = serde_json::from_value(serde_json::json!({
+            "entry_ebitda": "100",
+            "entry_multiple": 10.0,
+            "ebitda_projections": [100, 100, 100],
+            "debt_tranches": [{"name": "Term Loan", "amount": 500, "interest_rate": 0.10}],
+            "exit_multiple": 10.0,
+            "tax_rate": 0.0
+        }))
+        .unwrap();
+
+        let response = Lbo::calculate(params).unwrap();
+
+        assert_eq!(response.sources_and_uses.sponsor_equity, Decimal::new(500, 0));
+        assert_eq!(response.entry_leverage, 5.0);
+        // All free cash flow sweeps the loan: 500 -> 450 -> 395 -> 334.5
+        let balances: Vec<Decimal> = response.debt_schedule.iter().map(|y| y.total_debt).collect();
+        assert_eq!(balances, vec![Decimal::new(450, 0), Decimal::new(395, 0), Decimal::new(3345, 1)]);
+        assert_eq!(response.exit.equity_value, Decimal::new(6655, 1));
+        // 665.5 / 500 = 1.331x = 1.1^3, so IRR is exactly 10%
+        assert_eq!(response.moic, 1.33);
+        assert_eq!(response.irr, 0.10);
+        assert_eq!(response.returns_rating, "Weak");
+        assert_eq!(response.returns_attribution.deleveraging, Decimal::new(1655, 1));
+        assert_eq!(response.returns_attribution.deleveraging_pct, Some(100.0));
+        assert_eq!(response.returns_attribution.multiple_expansion, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_calculate_lbo_returns_attribution_sums_to_value_creation() {
+        let params: LboParams = serde_json::from_value(serde_json::json!({
+            "entry_ebitda": 80,
+            "entry_multiple": 9.0,
+            "ebitda_projections": [88, 96, 105, 115, 126],
+            "debt_tranches": [
+                {"name": "Term Loan A", "ebitda_multiple": 3.0, "interest_rate": 0.07, "amortization_pct": 0.10},
+                {"name": "Senior Notes", "ebitda_multiple": 2.0, "interest_rate": 0.09, "sweep": "no"}
+            ],
+            "cash_sweep_pct": 0.75,
+            "exit_multiple": 10.0,
+            "capex_pct_of_ebitda": 0.2,
+            "transaction_fees_pct": 0.02
+        }))
+        .unwrap();
+
+        let response = Lbo::calculate(params).unwrap();
+
+        // The bridge is exact before rounding, so the rounded parts sit within a cent per part
+        let a = &response.returns_attribution;
+        let bridge = a.ebitda_growth + a.multiple_expansion + a.deleveraging + a.fees;
+        assert!((bridge - a.total_value_creation).abs() <= Decimal::new(2, 2));
+        assert_eq!(response.exit.equity_value - response.sources_and_uses.sponsor_equity, a.total_value_creation);
+        // Notes are not swept and do not amortize
+        let last = response.debt_schedule.last().unwrap();
+        assert_eq!(last.tranches[1].ending_balance, Decimal::new(160, 0));
+        assert!(last.tranches[0].ending_balance < Decimal::new(240, 0));
+        assert!(response.moic > 1.0 && response.irr > 0.0);
+    }
+
+    #[test]
+    fn test_calculate_lbo_returns_debt_exceeds_uses() {
+        let params: LboParams = serde_json::from_value(serde_json::json!({
+            "entry_ebitda": 100,
+            "entry_multiple": 5.0,
+            "ebitda_projections": [110],
+            "debt_tranches": [{"name": "Bridge", "ebitda_multiple": 6.0, "interest_rate": 0.12}],
+            "exit_multiple": 5.0
+        }))
+        .unwrap();
+
+        let error = Lbo::calculate(params).unwrap_err();
+        assert!(error.message.contains("leaves no sponsor equity"));
+        assert_eq!(error.code, ErrorCode::OutOfRange);
+        assert_eq!(error.field.as_deref(), Some("debt_tranches"));
+    }
+
+    #[test]
+    fn test_calculate_lbo_returns_tranche_sized_twice() {
+        let params: LboParams = serde_json::from_value(serde_json::json!({
+            "entry_ebitda": 100,
+            "entry_multiple": 8.0,
+            "ebitda_projections": [110],
+            "debt_tranches": [{"name": "Term Loan", "amount": 300, "ebitda_multiple": 3.0, "interest_rate": 0.08}],
+            "exit_multiple": 8.0
+        }))
+        .unwrap();
+
+        let error = Lbo::calculate(params).unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidInput);
+        assert_eq!(error.field.as_deref(), Some("debt_tranches.Term Loan"));
+    }
+
+    #[test]
+    fn test_calculate_lbo_returns_hold_period_out_of_range() {
+        let params: LboParams = serde_json::from_value(serde_json::json!({
+            "entry_ebitda": 100,
+            "entry_multiple": 8.0,
+            "ebitda_projections": [110, 120, 130],
+            "debt_tranches": [{"name": "Term Loan", "ebitda_multiple": 4.0, "interest_rate": "8%"}],
+            "exit_multiple": 8.0,
+            "hold_period_years": 5
+        }))
+        .unwrap();
+
+        let error = Lbo::calculate(params).unwrap_err();
+        assert_eq!(error.code, ErrorCode::OutOfRange);
+        assert_eq!(error.field.as_deref(), Some("hold_period_years"));
+        assert_eq!(error.allowed_range.unwrap().max, Some(3.0));
+    }
+}
@@ -0,0 +1,463 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::calculation::{converter, field, fx_failure, field_parser, money, rate_or, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{decimal_from_f64, decimal_to_f64, round_ratio, scale_money};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_opt, FieldParser, NumberUnit};
+
+const DEFAULT_TAX_RATE: f64 = 0.25;
+const MIX_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 3);
+const BREAKEVEN_BAND: f64 = 0.001;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_accretion_dilution
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct AccretionDilutionParams {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Acquirer standalone net income, number or currency-tagged")]
+    pub acquirer_net_income: String,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Acquirer diluted shares outstanding, number or with a magnitude suffix (e.g., '450M')")]
+    pub acquirer_shares: String,
+    #[schemars(description = "Acquirer share price, used to size the stock issued (required when stock_pct > 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub acquirer_share_price: Option<String>,
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Target net income, number or currency-tagged")]
+    pub target_net_income: String,
+    #[schemars(description = "Target diluted shares outstanding (needed with offer_price_per_share), number or with a magnitude suffix")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub target_shares: Option<String>,
+    #[schemars(description = "Total equity purchase price (alternative to offer_price_per_share)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub purchase_price: Option<String>,
+    #[schemars(description = "Offer price per target share (alternative to purchase_price)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub offer_price_per_share: Option<String>,
+    #[schemars(description = "Share of the price paid from acquirer cash as a ratio (e.g., 0.4 or '40%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cash_pct: Option<String>,
+    #[schemars(description = "Share of the price paid in newly issued acquirer shares as a ratio (e.g., 0.6 or '60%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub stock_pct: Option<String>,
+    #[schemars(description = "Share of the price funded with new debt as a ratio (e.g., 0.3 or '30%')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub debt_pct: Option<String>,
+    #[schemars(description = "Pre-tax interest rate on new acquisition debt as a ratio (e.g., 0.06, '6%' or '600bps', default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub debt_interest_rate: Option<String>,
+    #[schemars(description = "Pre-tax interest income forgone on cash used as a ratio (e.g., 0.03 or '3%', default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cash_interest_rate: Option<String>,
+    #[schemars(description = "Annual pre-tax synergies (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub synergies: Option<String>,
+    #[schemars(description = "Tax rate applied to synergies and interest as a ratio (e.g., 0.25 or '25%', default: 0.25)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub tax_rate: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ProFormaNetIncome {
+    #[schemars(description = "Acquirer standalone net income", with = "String")]
+    pub acquirer_net_income: Decimal,
+    #[schemars(description = "Target net income", with = "String")]
+    pub target_net_income: Decimal,
+    #[schemars(description = "After-tax synergies", with = "String")]
+    pub after_tax_synergies: Decimal,
+    #[schemars(description = "After-tax interest income forgone on cash used (negative)", with = "String")]
+    pub forgone_interest_on_cash: Decimal,
+    #[schemars(description = "After-tax interest on new debt (negative)", with = "String")]
+    pub interest_on_new_debt: Decimal,
+    #[schemars(description = "Pro-forma combined net income", with = "String")]
+    pub pro_forma_net_income: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ConsiderationMix {
+    #[schemars(description = "Cash paid from the balance sheet", with = "String")]
+    pub cash: Decimal,
+    #[schemars(description = "Value of acquirer shares issued", with = "String")]
+    pub stock: Decimal,
+    #[schemars(description = "New debt raised", with = "String")]
+    pub debt: Decimal,
+    #[schemars(description = "New acquirer shares issued")]
+    pub new_shares_issued: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct AccretionDilutionResponse {
+    #[schemars(description = "Total equity purchase price", with = "String")]
+    pub purchase_price: Decimal,
+    #[schemars(description = "Consideration split into cash, stock and debt")]
+    pub consideration: ConsiderationMix,
+    #[schemars(description = "Bridge from standalone to pro-forma net income")]
+    pub net_income_bridge: ProFormaNetIncome,
+    #[schemars(description = "Acquirer standalone EPS", with = "String")]
+    pub standalone_eps: Decimal,
+    #[schemars(description = "Pro-forma EPS after the deal", with = "String")]
+    pub pro_forma_eps: Decimal,
+    #[schemars(description = "Pro-forma diluted shares")]
+    pub pro_forma_shares: f64,
+    #[schemars(description = "EPS accretion (positive) or dilution (negative) as decimal")]
+    pub accretion_dilution: f64,
+    #[schemars(description = "EPS accretion (positive) or dilution (negative) as percentage")]
+    pub accretion_dilution_pct: f64,
+    #[schemars(description = "Deal impact: Accretive, Dilutive, or Breakeven (within 0.1%)")]
+    pub deal_impact: String,
+    #[schemars(description = "Pre-tax synergies needed for zero dilution (0 when accretive without synergies)", with = "String")]
+    pub breakeven_synergies: Decimal,
+    #[schemars(description = "Acquirer P/E (share price / standalone EPS), when share price is given")]
+    pub acquirer_pe: Option<f64>,
+    #[schemars(description = "Purchase P/E paid for the target (purchase price / target net income)")]
+    pub purchase_pe: Option<f64>,
+    #[schemars(description = "Pro-forma ownership of existing acquirer shareholders as percentage")]
+    pub acquirer_ownership_pct: f64,
+    #[schemars(description = "Plain language summary of the deal impact")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 25 for a 25% tax rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn rate(units: &mut FieldParser, name: &str, value: Option<&str>, default: f64) -> Result<Decimal, FinanceError> {
+    let value = rate_or(units, value, name, default)?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(FinanceError::out_of_range(name, Some(0.0), Some(1.0), format!("{} must be between 0 and 1", name)));
+    }
+    decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(name, e))
+}
+
+/// Compare acquirer standalone EPS with pro-forma EPS after funding the purchase.
+///
+/// Cash costs the after-tax interest it would have earned, debt its after-tax interest, and stock
+/// dilutes through new shares issued at the acquirer share price.
+pub fn calculate_accretion_dilution_internal(
+    params: &AccretionDilutionParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<AccretionDilutionResponse, FinanceError> {
+    let rounding = fx.rounding();
+    let decimal = |value: f64, name: &str| decimal_from_f64(value).map_err(|e| FinanceError::invalid_field(name, e));
+    let too_large = |name: &str| FinanceError::invalid_field(name, "amount exceeds the supported magnitude of 1e18");
+    let acquirer_shares = field(units, &params.acquirer_shares, "acquirer_shares", NumberUnit::Amount)?;
+    let acquirer_shares = decimal(acquirer_shares, "acquirer_shares")?;
+    let target_shares = params
+        .target_shares
+        .as_deref()
+        .map(|v| field(units, v, "target_shares", NumberUnit::Amount).and_then(|s| decimal(s, "target_shares")))
+        .transpose()?;
+
+    let acquirer_net_income = money(fx, &params.acquirer_net_income, "acquirer_net_income", Period::Current)?;
+    let target_net_income = money(fx, &params.target_net_income, "target_net_income", Period::Current)?;
+    let share_price = params
+        .acquirer_share_price
+        .as_deref()
+        .map(|v| money(fx, v, "acquirer_share_price", Period::Current))
+        .transpose()?;
+    let synergies = params
+        .synergies
+        .as_deref()
+        .map(|v| money(fx, v, "synergies", Period::Current))
+        .transpose()?
+        .unwrap_or(Decimal::ZERO);
+
+    let purchase_price = match (params.purchase_price.as_deref(), params.offer_price_per_share.as_deref()) {
+        (Some(price), None) => money(fx, price, "purchase_price", Period::Current)?,
+        (None, Some(offer)) => {
+            let target_shares = target_shares
+                .filter(|s| *s > Decimal::ZERO)
+                .ok_or_else(|| {
+                    FinanceError::out_of_range(
+                        "target_shares",
+                        Some(0.0),
+                        None,
+                        "target_shares must be positive when offer_price_per_share is given",
+                    )
+                })?;
+            let offer = money(fx, offer, "offer_price_per_share", Period::Current)?;
+            scale_money(offer, target_shares).map_err(|e| FinanceError::invalid_field("offer_price_per_share", e))?
+        }
+        _ => {
+            return Err(FinanceError::invalid_input("Provide exactly one of purchase_price or offer_price_per_share")
+                .with_field("purchase_price"))
+        }
+    };
+
+    if acquirer_shares <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range("acquirer_shares", Some(0.0), None, "Acquirer shares must be positive"));
+    }
+    if purchase_price <= Decimal::ZERO {
+        let field = if params.purchase_price.is_some() { "purchase_price" } else { "offer_price_per_share" };
+        return Err(FinanceError::out_of_range(field, Some(0.0), None, "Purchase price must be positive"));
+    }
+
+    let cash_pct = rate(units, "cash_pct", params.cash_pct.as_deref(), 0.0)?;
+    let stock_pct = rate(units, "stock_pct", params.stock_pct.as_deref(), 0.0)?;
+    let debt_pct = rate(units, "debt_pct", params.debt_pct.as_deref(), 0.0)?;
+    let mix_total = cash_pct + stock_pct + debt_pct;
+    if (mix_total - Decimal::ONE).abs() > MIX_TOLERANCE {
+        return Err(FinanceError::sum_mismatch(format!(
+            "Consideration mix must sum to 1.0 (cash_pct + stock_pct + debt_pct = {:.4})",
+            mix_total
+        ))
+        .with_field("cash_pct"));
+    }
+    let debt_rate = rate(units, "debt_interest_rate", params.debt_interest_rate.as_deref(), 0.0)?;
+    let cash_rate = rate(units, "cash_interest_rate", params.cash_interest_rate.as_deref(), 0.0)?;
+    let tax_rate = rate(units, "tax_rate", params.tax_rate.as_deref(), DEFAULT_TAX_RATE)?;
+    if tax_rate >= Decimal::ONE {
+        return Err(FinanceError::out_of_range("tax_rate", Some(0.0), Some(1.0), "tax_rate must be below 1"));
+    }
+
+    let cash = purchase_price * cash_pct;
+    let stock = purchase_price * stock_pct;
+    let debt = purchase_price * debt_pct;
+    let new_shares = if stock > Decimal::ZERO {
+        let price = share_price
+            .filter(|p| *p > Decimal::ZERO)
+            .ok_or_else(|| {
+                FinanceError::out_of_range(
+                    "acquirer_share_price",
+                    Some(0.0),
+                    None,
+                    "A positive acquirer_share_price is required when stock_pct > 0",
+                )
+            })?;
+        stock.checked_div(price).ok_or_else(|| too_large("acquirer_share_price"))?
+    } else {
+        Decimal::ZERO
+    };
+
+    let after_tax = Decimal::ONE - tax_rate;
+    let forgone_interest = cash * cash_rate * after_tax;
+    let new_interest = debt * debt_rate * after_tax;
+    let after_tax_synergies = synergies * after_tax;
+    let pro_forma_net_income =
+        acquirer_net_income + target_net_income + after_tax_synergies - forgone_interest - new_interest;
+    let pro_forma_shares = acquirer_shares + new_shares;
+
+    let standalone_eps = acquirer_net_income.checked_div(acquirer_shares).ok_or_else(|| too_large("acquirer_shares"))?;
+    let pro_forma_eps =
+        pro_forma_net_income.checked_div(pro_forma_shares).ok_or_else(|| too_large("acquirer_shares"))?;
+    if standalone_eps <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range(
+            "acquirer_net_income",
+            Some(0.0),
+            None,
+            "Acquirer standalone net income must be positive to measure accretion",
+        ));
+    }
+    let accretion = decimal_to_f64(pro_forma_eps) / decimal_to_f64(standalone_eps) - 1.0;
+
+    // Pre-tax synergies S such that pro-forma EPS equals standalone EPS
+    let required_net_income =
+        scale_money(standalone_eps, pro_forma_shares).map_err(|e| FinanceError::invalid_field("acquirer_shares", e))?;
+    let net_income_before_synergies = pro_forma_net_income - after_tax_synergies;
+    let breakeven_synergies = ((required_net_income - net_income_before_synergies) / after_tax).max(Decimal::ZERO);
+
+    let deal_impact = if accretion.abs() < BREAKEVEN_BAND {
+        "Breakeven"
+    } else if accretion > 0.0 {
+        "Accretive"
+    } else {
+        "Dilutive"
+    };
+
+    let acquirer_pe = share_price.map(|p| round_ratio(decimal_to_f64(p) / decimal_to_f64(standalone_eps), 2));
+    let purchase_pe = (target_net_income > Decimal::ZERO)
+        .then(|| round_ratio(decimal_to_f64(purchase_price) / decimal_to_f64(target_net_income), 2));
+
+    let mut interpretation = format!(
+        "The deal is {} with EPS moving from {:.2} to {:.2} ({:+.2}%).",
+        deal_impact.to_lowercase(),
+        standalone_eps,
+        pro_forma_eps,
+        accretion * 100.0
+    );
+    if breakeven_synergies > Decimal::ZERO {
+        interpretation.push_str(&format!(
+            " Pre-tax synergies of {:.2} are needed to avoid dilution versus {:.2} assumed.",
+            breakeven_synergies, synergies
+        ));
+    } else {
+        interpretation.push_str(" It is accretive even without synergies.");
+    }
+    if let (Some(acq), Some(paid)) = (acquirer_pe, purchase_pe)
+        && stock > Decimal::ZERO
+        && paid > acq
+    {
+        interpretation.push_str(&format!(
+            " Paying {:.1}x earnings with stock valued at {:.1}x is inherently dilutive.",
+            paid, acq
+        ));
+    }
+
+    Ok(AccretionDilutionResponse {
+        purchase_price: rounding.round(purchase_price),
+        consideration: ConsiderationMix {
+            cash: rounding.round(cash),
+            stock: rounding.round(stock),
+            debt: rounding.round(debt),
+            new_shares_issued: round_ratio(decimal_to_f64(new_shares), 2),
+        },
+        net_income_bridge: ProFormaNetIncome {
+            acquirer_net_income: rounding.round(acquirer_net_income),
+            target_net_income: rounding.round(target_net_income),
+            after_tax_synergies: rounding.round(after_tax_synergies),
+            forgone_interest_on_cash: rounding.round(-forgone_interest),
+            interest_on_new_debt: rounding.round(-new_interest),
+            pro_forma_net_income: rounding.round(pro_forma_net_income),
+        },
+        standalone_eps: rounding.round_price(standalone_eps),
+        pro_forma_eps: rounding.round_price(pro_forma_eps),
+        pro_forma_shares: round_ratio(decimal_to_f64(pro_forma_shares), 2),
+        accretion_dilution: round_ratio(accretion, 4),
+        accretion_dilution_pct: round_ratio(accretion * 100.0, 2),
+        deal_impact: deal_impact.to_string(),
+        breakeven_synergies: rounding.round(breakeven_synergies),
+        acquirer_pe,
+        purchase_pe,
+        acquirer_ownership_pct: round_ratio(decimal_to_f64(acquirer_shares / pro_forma_shares) * 100.0, 2),
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// EPS accretion/dilution of an acquisition (`calculate_accretion_dilution`)
+pub struct AccretionDilution;
+
+impl Calculation for AccretionDilution {
+    const NAME: &'static str = "calculate_accretion_dilution";
+    const DESCRIPTION: &'static str = "Analyze whether an acquisition is accretive or dilutive to the acquirer's EPS. Takes acquirer and target net income and share counts, acquirer share price, purchase price (total or per target share), consideration mix (cash/stock/debt summing to 1), debt and cash interest rates, pre-tax synergies and tax rate. Returns standalone and pro-forma EPS, accretion/dilution % with deal impact (Accretive, Dilutive, Breakeven), the net income bridge, new shares issued, pro-forma ownership, acquirer and purchase P/E, and the breakeven synergies needed to avoid dilution. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "accretion_dilution_pct";
+    const BAND_FIELD: Option<&'static str> = Some("deal_impact");
+
+    type Params = AccretionDilutionParams;
+    type Response = AccretionDilutionResponse;
+
+    fn calculate(params: AccretionDilutionParams) -> Result<AccretionDilutionResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = calculate_accretion_dilution_internal(&params, &mut fx, &mut units)
+            .map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_accretion_dilution_stock_deal() {
+        let params: AccretionDilutionParams = serde_json::from_value(serde_json::json!({
+            "acquirer_net_income": 1000,
+            "acquirer_shares": 500,
+            "acquirer_share_price": "40",
+            "target_net_income": 200,
+            "purchase_price": 4000,
+            "stock_pct": 1.0,
+            "tax_rate": 0.25
+        }))
+        .unwrap();
+
+        let response = AccretionDilution::calculate(params).unwrap();
+
+        // 100 new shares; EPS 2.00 -> 1200 / 600 = 2.00
+        assert_eq!(response.consideration.new_shares_issued, 100.0);
+        assert_eq!(response.standalone_eps, Decimal::TWO);
+        assert_eq!(response.pro_forma_eps, Decimal::TWO);
+        assert_eq!(response.deal_impact, "Breakeven");
+        assert_eq!(response.breakeven_synergies, Decimal::ZERO);
+        assert_eq!(response.acquirer_pe, Some(20.0));
+        assert_eq!(response.purchase_pe, Some(20.0));
+    }
+
+    #[test]
+    fn test_calculate_accretion_dilution_breakeven_synergies() {
+        let params: AccretionDilutionParams = serde_json::from_value(serde_json::json!({
+            "acquirer_net_income": 1000,
+            "acquirer_shares": 500,
+            "acquirer_share_price": 40,
+            "target_net_income": 100,
+            "purchase_price": 4000,
+            "cash_pct": 0.5,
+            "debt_pct": 0.5,
+            "cash_interest_rate": 0.02,
+            "debt_interest_rate": 0.06,
+            "synergies": 20,
+            "tax_rate": 0.25
+        }))
+        .unwrap();
+
+        let response = AccretionDilution::calculate(params).unwrap();
+
+        // Financing cost after tax: 2000 x 2% x 0.75 + 2000 x 6% x 0.75 = 30 + 90 = 120
+        // Pro-forma net income: 1000 + 100 + 15 - 120 = 995 -> EPS 1.99
+        assert_eq!(response.net_income_bridge.pro_forma_net_income, Decimal::new(995, 0));
+        assert_eq!(response.pro_forma_eps, Decimal::new(199, 2));
+        assert_eq!(response.accretion_dilution_pct, -0.5);
+        assert_eq!(response.deal_impact, "Dilutive");
+        // Without synergies net income is 980; the missing 20 after tax is 26.67 pre-tax
+        assert_eq!(response.breakeven_synergies, Decimal::new(2667, 2));
+        assert_eq!(response.acquirer_ownership_pct, 100.0);
+    }
+
+    #[test]
+    fn test_calculate_accretion_dilution_mix_must_sum_to_one() {
+        let params: AccretionDilutionParams = serde_json::from_value(serde_json::json!({
+            "acquirer_net_income": 1000,
+            "acquirer_shares": 500,
+            "target_net_income": 100,
+            "purchase_price": 4000,
+            "cash_pct": 0.5,
+            "debt_pct": 0.3
+        }))
+        .unwrap();
+
+        let error = AccretionDilution::calculate(params).unwrap_err();
+        assert!(error.message.contains("Consideration mix must sum to 1.0"));
+    }
+    #[test]
+    fn test_calculate_accretion_dilution_share_counts_with_suffixes() {
+        let params: AccretionDilutionParams = serde_json::from_value(serde_json::json!({
+            "acquirer_net_income": "1,000M",
+            "acquirer_shares": "500M",
+            "acquirer_share_price": 40,
+            "target_net_income": "200M",
+            "target_shares": "80M",
+            "offer_price_per_share": 50,
+            "stock_pct": 1.0
+        }))
+        .unwrap();
+
+        let response = AccretionDilution::calculate(params).unwrap();
+
+        // 80M x 50 = 4,000M paid in stock at 40 -> 100M new shares
+        assert_eq!(response.consideration.new_shares_issued, 100_000_000.0);
+        assert_eq!(response.standalone_eps, Decimal::TWO);
+        assert_eq!(response.pro_forma_eps, Decimal::TWO);
+    }
+}
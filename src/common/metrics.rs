@@ -1,13 +1,16 @@
 use once_cell::sync::Lazy;
-use prometheus::{Counter, Gauge, Histogram, HistogramOpts, Opts, Registry};
+use prometheus::{Counter, CounterVec, Gauge, Histogram, HistogramOpts, Opts, Registry};
 
-pub static METRICS: Lazy<FinanceMetrics> = Lazy::new(|| FinanceMetrics::new());
+use super::error::ErrorCode;
+
+pub static METRICS: Lazy<FinanceMetrics> = Lazy::new(FinanceMetrics::new);
 
 pub struct FinanceMetrics {
     #[allow(dead_code)] // Used internally by gather() method
     pub registry: Registry,
     pub requests_total: Counter,
     pub errors_total: Counter,
+    pub errors_by_code: CounterVec,
     pub request_duration: Histogram,
     pub active_requests: Gauge,
 }
@@ -30,6 +33,14 @@ impl FinanceMetrics {
             )
         ).unwrap();
 
+        let errors_by_code = CounterVec::new(
+            Opts::new(
+                "finance_errors_by_code_total",
+                "Total number of errors in finance engine calculations by error code"
+            ),
+            &["code"]
+        ).unwrap();
+
         let request_duration = Histogram::with_opts(
             HistogramOpts::new(
                 "finance_request_duration_seconds",
@@ -47,6 +58,7 @@ impl FinanceMetrics {
 
         registry.register(Box::new(requests_total.clone())).unwrap();
         registry.register(Box::new(errors_total.clone())).unwrap();
+        registry.register(Box::new(errors_by_code.clone())).unwrap();
         registry.register(Box::new(request_duration.clone())).unwrap();
         registry.register(Box::new(active_requests.clone())).unwrap();
 
@@ -54,6 +66,7 @@ impl FinanceMetrics {
             registry,
             requests_total,
             errors_total,
+            errors_by_code,
             request_duration,
             active_requests,
         }
@@ -83,6 +96,12 @@ impl RequestTimer {
     }
 }
 
+impl Default for RequestTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Drop for RequestTimer {
     fn drop(&mut self) {
         if let Some(timer) = self.timer.take() {
@@ -97,7 +116,8 @@ pub fn increment_requests() {
     METRICS.requests_total.inc();
 }
 
-/// Helper function to increment error counter
-pub fn increment_errors() {
+/// Helper function to increment the error counters, overall and for the error's code
+pub fn increment_errors(code: ErrorCode) {
     METRICS.errors_total.inc();
+    METRICS.errors_by_code.with_label_values(&[code.as_str()]).inc();
 }
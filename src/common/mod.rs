@@ -1,2 +1,27 @@
+pub mod batch;
+pub mod benchmark;
+pub mod business_metrics;
+pub mod calculation;
+pub mod cap_table;
+pub mod covenant;
+pub mod credit_rating;
+pub mod currency;
+pub mod customer;
+pub mod dividend;
+pub mod earnings_quality;
+pub mod error;
+pub mod extraction;
+#[cfg(feature = "mcp")]
 pub mod finance_engine;
+pub mod inflation;
+pub mod json_path;
+pub mod lbo;
+pub mod merger;
+#[cfg(feature = "mcp")]
 pub mod metrics;
+pub mod money;
+pub mod parsing;
+pub mod scenario;
+pub mod simulation;
+pub mod unit_economics;
+pub mod variance;
@@ -0,0 +1,206 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Decimal places used for monetary amounts when the request does not set `decimal_places`
+pub const DEFAULT_MONEY_DECIMAL_PLACES: u32 = 2;
+
+/// Upper bound on `decimal_places`, well inside the 28 digits a `Decimal` carries
+pub const MAX_MONEY_DECIMAL_PLACES: u32 = 10;
+
+/// Per-unit prices (share prices, EPS, projected dividends) keep at least this many decimal places,
+/// since a price rounded to cents misstates values across millions of shares
+pub const MIN_PRICE_DECIMAL_PLACES: u32 = 4;
+
+/// Largest accepted monetary magnitude (10^18), leaving headroom for sums and weighted sums
+const MAX_MONEY_MAGNITUDE: i64 = 1_000_000_000_000_000_000;
+
+// =================== DATA STRUCTURES ===================
+
+/// How monetary amounts are rounded to `decimal_places`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// Ties go to the even digit (banker's rounding), the finance default
+    #[default]
+    HalfEven,
+    /// Ties go away from zero
+    HalfUp,
+    /// Ties go toward zero
+    HalfDown,
+    /// Truncate toward zero
+    Down,
+    /// Round away from zero
+    Up,
+    /// Round toward negative infinity
+    Floor,
+    /// Round toward positive infinity
+    Ceiling,
+}
+
+impl RoundingMode {
+    fn strategy(self) -> RoundingStrategy {
+        match self {
+            RoundingMode::HalfEven => RoundingStrategy::MidpointNearestEven,
+            RoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            RoundingMode::HalfDown => RoundingStrategy::MidpointTowardZero,
+            RoundingMode::Down => RoundingStrategy::ToZero,
+            RoundingMode::Up => RoundingStrategy::AwayFromZero,
+            RoundingMode::Floor => RoundingStrategy::ToNegativeInfinity,
+            RoundingMode::Ceiling => RoundingStrategy::ToPositiveInfinity,
+        }
+    }
+}
+
+/// Rounding policy for monetary outputs of a single request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MoneyRounding {
+    pub mode: RoundingMode,
+    pub decimal_places: u32,
+}
+
+impl Default for MoneyRounding {
+    fn default() -> Self {
+        Self {
+            mode: RoundingMode::default(),
+            decimal_places: DEFAULT_MONEY_DECIMAL_PLACES,
+        }
+    }
+}
+
+// =================== ARITHMETIC ===================
+
+impl MoneyRounding {
+    /// Build a policy from optional request settings
+    pub fn new(mode: Option<RoundingMode>, decimal_places: Option<u32>) -> Result<Self, String> {
+        let decimal_places = decimal_places.unwrap_or(DEFAULT_MONEY_DECIMAL_PLACES);
+        if decimal_places > MAX_MONEY_DECIMAL_PLACES {
+            return Err(format!("decimal_places must be at most {}", MAX_MONEY_DECIMAL_PLACES));
+        }
+        Ok(Self {
+            mode: mode.unwrap_or_default(),
+            decimal_places,
+        })
+    }
+
+    /// Round an amount to the policy's decimal places
+    pub fn round(&self, value: Decimal) -> Decimal {
+        value.round_dp_with_strategy(self.decimal_places, self.mode.strategy())
+    }
+
+    /// Round a per-unit price with the policy's mode, keeping at least `MIN_PRICE_DECIMAL_PLACES`
+    pub fn round_price(&self, value: Decimal) -> Decimal {
+        value.round_dp_with_strategy(self.decimal_places.max(MIN_PRICE_DECIMAL_PLACES), self.mode.strategy())
+    }
+}
+
+/// Reject amounts too large to sum safely as decimals
+pub fn check_money_range(value: Decimal) -> Result<Decimal, String> {
+    if value.abs() > Decimal::from(MAX_MONEY_MAGNITUDE) {
+        return Err("amount exceeds the supported magnitude of 1e18".to_string());
+    }
+    Ok(value)
+}
+
+/// Multiply an amount by a rate or multiple (an EBITDA multiple, an exchange ratio), rejecting
+/// results beyond the supported magnitude
+pub fn scale_money(amount: Decimal, factor: Decimal) -> Result<Decimal, String> {
+    amount
+        .checked_mul(factor)
+        .ok_or_else(|| "amount exceeds the supported magnitude of 1e18".to_string())
+        .and_then(check_money_range)
+}
+
+/// Round a ratio, percentage, multiple or share count for output. Monetary amounts go through
+/// [`MoneyRounding`] instead, so the request's rounding mode and decimal places apply to them.
+pub fn round_ratio(value: f64, decimal_places: i32) -> f64 {
+    let scale = 10f64.powi(decimal_places);
+    (value * scale).round() / scale
+}
+
+/// Convert a float (an FX rate, or volume x price) into a decimal via its shortest representation,
+/// so 1.08 becomes exactly 1.08 rather than its binary approximation
+pub fn decimal_from_f64(value: f64) -> Result<Decimal, String> {
+    if !value.is_finite() {
+        return Err(format!("{} is not a finite number", value));
+    }
+    let text = value.to_string();
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&format!("{:e}", value)))
+        .map_err(|_| format!("{} is outside the supported decimal range", text))
+}
+
+/// Convert a decimal to a float for ratio arithmetic
+pub fn decimal_to_f64(value: Decimal) -> f64 {
+    use rust_decimal::prelude::ToPrimitive;
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// Percentage shares of `total` rounded to `decimal_places` that sum exactly to 100.
+///
+/// Shares are truncated and the leftover units go to the largest remainders (Hamilton's method),
+/// so a breakdown never shows 99.9% or 100.1% from independent rounding.
+pub fn allocate_percentages(parts: &[Decimal], total: Decimal, decimal_places: u32) -> Vec<Decimal> {
+    if parts.is_empty() || total.is_zero() || parts.iter().any(|p| p.is_sign_negative()) {
+        return parts
+            .iter()
+            .map(|p| {
+                if total.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    (p / total * Decimal::ONE_HUNDRED).round_dp(decimal_places)
+                }
+            })
+            .collect();
+    }
+
+    let unit = Decimal::new(1, decimal_places);
+    let exact: Vec<Decimal> = parts.iter().map(|p| p / total * Decimal::ONE_HUNDRED).collect();
+    let mut shares: Vec<Decimal> = exact
+        .iter()
+        .map(|e| e.round_dp_with_strategy(decimal_places, RoundingStrategy::ToZero))
+        .collect();
+
+    let allocated: Decimal = shares.iter().sum();
+    let mut leftover = ((Decimal::ONE_HUNDRED - allocated) / unit).round();
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|&a, &b| (exact[b] - shares[b]).cmp(&(exact[a] - shares[a])).then(a.cmp(&b)));
+    for i in order.into_iter().cycle() {
+        if leftover <= Decimal::ZERO {
+            break;
+        }
+        shares[i] += unit;
+        leftover -= Decimal::ONE;
+    }
+    shares
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_money_rounding_modes_and_share_allocation() {
+        let half_even = MoneyRounding::new(None, None).unwrap();
+        assert_eq!(half_even.round(Decimal::new(2345, 3)), Decimal::new(234, 2));
+        assert_eq!(half_even.round(Decimal::new(2355, 3)), Decimal::new(236, 2));
+        let half_up = MoneyRounding::new(Some(RoundingMode::HalfUp), None).unwrap();
+        assert_eq!(half_up.round(Decimal::new(2345, 3)), Decimal::new(235, 2));
+        let floor = MoneyRounding::new(Some(RoundingMode::Floor), Some(0)).unwrap();
+        assert_eq!(floor.round(Decimal::new(-15, 1)), Decimal::new(-2, 0));
+        assert!(MoneyRounding::new(None, Some(11)).is_err());
+
+        // Prices keep four decimals, or more when decimal_places asks for them
+        assert_eq!(half_up.round_price(Decimal::new(833335, 6)), Decimal::new(8333, 4));
+        let fine = MoneyRounding::new(None, Some(6)).unwrap();
+        assert_eq!(fine.round_price(Decimal::new(8333335, 7)), Decimal::new(833334, 6));
+        assert_eq!(scale_money(Decimal::new(500, 0), Decimal::new(7, 2)), Ok(Decimal::new(3500, 2)));
+        assert!(scale_money(Decimal::from(MAX_MONEY_MAGNITUDE), Decimal::TEN).is_err());
+        assert_eq!(round_ratio(55.5555, 2), 55.56);
+        assert_eq!(round_ratio(-0.105649, 4), -0.1056);
+
+        // Thirds truncate to 33.3 each; the leftover 0.1 goes to the first largest remainder
+        let shares = allocate_percentages(&[Decimal::ONE; 3], Decimal::new(3, 0), 1);
+        assert_eq!(shares, vec![Decimal::new(334, 1), Decimal::new(333, 1), Decimal::new(333, 1)]);
+    }
+}
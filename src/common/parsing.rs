@@ -0,0 +1,699 @@
+use serde::{Deserialize, Deserializer, de};
+use rust_decimal::Decimal;
+use std::fmt;
+
+// =================== PARSING UTILITIES ===================
+
+/// Sanitize user input for safe inclusion in error messages
+pub(crate) fn sanitize_for_error_message(input: &str) -> String {
+    let truncated = if input.chars().count() > 50 { 
+        format!("{}...", input.chars().take(47).collect::<String>())
+    } else { 
+        input.to_string() 
+    };
+    
+    truncated
+        .chars()
+        .map(|c| match c {
+            '\n' | '\r' | '\t' => ' ',
+            '"' | '\'' | '`' => '?',
+            '\\' => '?',
+            '<' | '>' => '?',
+            c if c.is_ascii_graphic() || c == ' ' => c,
+            _ => '?'
+        })
+        .collect()
+}
+
+/// Validate input length and format for security
+fn validate_input_security(input: &str, field_name: &str) -> Result<(), String> {
+    if input.len() > 100 {
+        return Err(format!("Invalid {}: input too long (max 100 characters)", field_name));
+    }
+    
+    if input.contains('\0') {
+        return Err(format!("Invalid {}: input contains null bytes", field_name));
+    }
+    
+    let control_char_count = input.chars().filter(|c| c.is_control()).count();
+    if control_char_count > 2 {
+        return Err(format!("Invalid {}: input contains too many control characters", field_name));
+    }
+    
+    Ok(())
+}
+
+/// Magnitude suffixes recognized after a number (case-insensitive), longest first, as powers of ten
+pub(crate) const MAGNITUDE_SUFFIXES: [(&str, u32); 19] = [
+    ("trillions", 12),
+    ("trillion", 12),
+    ("billions", 9),
+    ("billion", 9),
+    ("millions", 6),
+    ("million", 6),
+    ("thousands", 3),
+    ("thousand", 3),
+    ("mrd", 9),
+    ("bio", 9),
+    ("mio", 6),
+    ("bn", 9),
+    ("mm", 6),
+    ("mn", 6),
+    ("tn", 12),
+    ("b", 9),
+    ("m", 6),
+    ("k", 3),
+    ("t", 12),
+];
+
+/// Percent notations recognized after a number (case-insensitive), longest first
+const PERCENT_SUFFIXES: [(&str, PercentNotation); 5] = [
+    ("percent", PercentNotation::Percent),
+    ("pct", PercentNotation::Percent),
+    ("%", PercentNotation::Percent),
+    ("bps", PercentNotation::BasisPoints),
+    ("bp", PercentNotation::BasisPoints),
+];
+
+/// Ratios above this magnitude written without a unit are probably percentages ("9" for 9%)
+const PLAUSIBLE_RATIO_MAX: f64 = 1.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PercentNotation {
+    Percent,
+    BasisPoints,
+}
+
+/// What a numeric field holds, which decides how "%", "pct" and "bps" are read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberUnit {
+    /// A fraction such as a growth rate: "9%", "9 pct" and "900bps" all become 0.09
+    Ratio,
+    /// A value on a 0-100 scale: "9%" stays 9 and "900bps" becomes 9
+    Percentage,
+    /// A monetary amount or count: percent notation is rejected
+    Amount,
+}
+
+/// A unit-aware number with how it was interpreted and whether it looks implausible for its field
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ParsedNumber {
+    pub value: f64,
+    pub note: Option<String>,
+    pub warning: Option<String>,
+}
+
+/// Languages whose numbers use a decimal comma ("1.234,56" or "1 234,56")
+const DECIMAL_COMMA_LANGUAGES: [&str; 29] = [
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "fi", "fr", "hr", "hu", "id", "it", "lt",
+    "lv", "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "tr", "uk",
+];
+
+/// Languages whose numbers use a decimal point ("1,234.56")
+const DECIMAL_POINT_LANGUAGES: [&str; 9] = ["en", "he", "hi", "ja", "ko", "ms", "th", "zh", "ga"];
+
+/// Regional variants that use a decimal point although their language uses a comma
+const DECIMAL_POINT_REGIONS: [&str; 5] = ["de-ch", "de-li", "it-ch", "es-mx", "es-us"];
+
+/// Decimal separator convention selected by a `locale` hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NumberLocale {
+    /// "1,234.56" (English, Japanese, Chinese; Swiss "1'234.56")
+    DecimalPoint,
+    /// "1.234,56" or "1 234,56" (most of continental Europe)
+    DecimalComma,
+}
+
+impl NumberLocale {
+    /// Resolve a locale tag such as "es", "de-DE" or "en_GB"
+    pub fn from_tag(tag: &str) -> Result<Self, String> {
+        let tag = tag.trim().to_lowercase().replace('_', "-");
+        let language = tag.split('-').next().unwrap_or_default();
+        if DECIMAL_POINT_REGIONS.contains(&tag.as_str()) || DECIMAL_POINT_LANGUAGES.contains(&language) {
+            Ok(NumberLocale::DecimalPoint)
+        } else if DECIMAL_COMMA_LANGUAGES.contains(&language) {
+            Ok(NumberLocale::DecimalComma)
+        } else {
+            Err(format!("Unsupported locale '{}'", sanitize_for_error_message(&tag)))
+        }
+    }
+
+    /// Resolve an optional request hint; no hint means auto-detection
+    pub fn from_option(tag: Option<&str>) -> Result<Option<Self>, String> {
+        tag.map(Self::from_tag).transpose()
+    }
+}
+
+/// Financial number text reduced to plain digits, a sign and a power-of-ten magnitude
+struct NormalizedNumber {
+    digits: String,
+    negative: bool,
+    exponent: u32,
+    percent: Option<PercentNotation>,
+    /// Whether the input needed interpretation beyond stripping symbols and thousands separators
+    interpreted: bool,
+    sanitized: String,
+}
+
+/// Normalize the notations found in financial reports: currency symbols, thousands separators,
+/// magnitude suffixes ("1.2B", "12.5 bn", "3,4 Mio"), parenthesized negatives ("(3,200)"),
+/// trailing minus signs ("3200-"), unicode minus signs, percent notation ("9%", "900bps")
+/// and locale digit grouping ("1.234,56", "1 234,56", "1'234.56")
+fn normalize_number(s: &str, locale: Option<NumberLocale>) -> Result<NormalizedNumber, String> {
+    let trimmed = s.trim();
+
+    validate_input_security(trimmed, "number")?;
+
+    if trimmed.is_empty() {
+        return Err("Empty string cannot be parsed as number".to_string());
+    }
+
+    let sanitized = sanitize_for_error_message(trimmed);
+    let mut interpreted = false;
+
+    let mut rest = trimmed
+        .replace(['$', '€', '£', '¥'], "")
+        .replace(['\u{2212}', '\u{2013}'], "-")
+        .trim()
+        .to_string();
+    if trimmed.contains(['\u{2212}', '\u{2013}']) {
+        interpreted = true;
+    }
+
+    // Sign: parentheses, leading or trailing minus; more than one is ambiguous
+    let mut signs = 0;
+    if rest.starts_with('(') && rest.ends_with(')') && rest.len() > 2 {
+        rest = rest[1..rest.len() - 1].trim().to_string();
+        signs += 1;
+        interpreted = true;
+    }
+    if let Some(stripped) = rest.strip_suffix('-') {
+        rest = stripped.trim_end().to_string();
+        signs += 1;
+        interpreted = true;
+    }
+    if let Some(stripped) = rest.strip_prefix('-') {
+        rest = stripped.trim_start().to_string();
+        signs += 1;
+    } else if let Some(stripped) = rest.strip_prefix('+') {
+        rest = stripped.trim_start().to_string();
+    }
+    if signs > 1 {
+        return Err(format!("Conflicting signs in '{}'", sanitized));
+    }
+
+    // Percent notation, then magnitude suffix, each only when it follows a digit or whitespace
+    let mut percent = None;
+    let lower = rest.to_lowercase();
+    for (suffix, notation) in PERCENT_SUFFIXES.iter() {
+        if let Some(number) = lower.strip_suffix(suffix)
+            && number.ends_with(|c: char| c.is_ascii_digit() || c.is_whitespace())
+        {
+            rest = number.trim_end().to_string();
+            percent = Some(*notation);
+            break;
+        }
+    }
+
+    let mut exponent = 0;
+    let lower = rest.trim_end_matches('.').to_lowercase();
+    for (suffix, power) in MAGNITUDE_SUFFIXES.iter() {
+        if let Some(number) = lower.strip_suffix(suffix)
+            && number.ends_with(|c: char| c.is_ascii_digit() || c.is_whitespace())
+        {
+            rest = number.trim_end().to_string();
+            exponent = *power;
+            interpreted = true;
+            break;
+        }
+    }
+
+    let digits = match resolve_separators(&rest, locale) {
+        Some((digits, detected_comma)) => {
+            interpreted |= detected_comma;
+            digits
+        }
+        None => return Err(format!("Ambiguous digit grouping in '{}'", sanitized)),
+    };
+
+    Ok(NormalizedNumber {
+        digits,
+        negative: signs == 1,
+        exponent,
+        percent,
+        interpreted,
+        sanitized,
+    })
+}
+
+/// Rewrite grouped digits with a plain decimal point, taking the decimal separator from the
+/// locale hint or, without one, from the grouping: the last of '.' and ',' is the decimal
+/// separator when both appear, a repeated one groups thousands, and a single comma is decimal
+/// unless exactly three digits follow ("1,234", but "3,4" and "0,075").
+///
+/// Returns the digits and whether a decimal comma was auto-detected, or `None` when groups
+/// after the first are not three digits long
+fn resolve_separators(text: &str, locale: Option<NumberLocale>) -> Option<(String, bool)> {
+    // Spaces and apostrophes group digits only between two digits
+    let chars: Vec<char> = text.chars().collect();
+    let text: String = chars
+        .iter()
+        .enumerate()
+        .filter(|&(i, c)| {
+            let groups = matches!(c, ' ' | '\u{a0}' | '\u{202f}' | '\'' | '\u{2019}');
+            let between_digits = i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit());
+            !(groups && between_digits)
+        })
+        .map(|(_, c)| *c)
+        .collect();
+
+    let commas = text.matches(',').count();
+    let points = text.matches('.').count();
+    let decimal = match locale {
+        Some(NumberLocale::DecimalPoint) => '.',
+        Some(NumberLocale::DecimalComma) => ',',
+        None if commas > 0 && points > 0 && text.rfind(',') > text.rfind('.') => ',',
+        None if commas > 0 && points > 0 => '.',
+        None if commas == 1 => {
+            let (whole, fraction) = text.split_once(',').unwrap_or_default();
+            if fraction.len() != 3 || whole == "0" { ',' } else { '.' }
+        }
+        None if points > 1 => ',',
+        None => '.',
+    };
+    let grouping = if decimal == '.' { ',' } else { '.' };
+
+    let (whole, fraction) = match text.split_once(decimal) {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (text.as_str(), None),
+    };
+    if fraction.is_some_and(|f| f.contains([decimal, grouping])) {
+        return None;
+    }
+    let groups: Vec<&str> = whole.split(grouping).collect();
+    if groups.len() > 1 && (groups[0].is_empty() || groups[1..].iter().any(|g| g.len() != 3)) {
+        return None;
+    }
+
+    let mut digits = groups.concat();
+    if let Some(fraction) = fraction {
+        digits.push('.');
+        digits.push_str(fraction);
+    }
+    let detected_comma = locale.is_none() && decimal == ',' && (commas > 0 || points > 0);
+    Some((digits, detected_comma))
+}
+
+/// Parse a field that declares its unit, converting percent notation to that unit and warning
+/// when a bare number looks like the wrong one ("9" for a growth rate, "0.85" on a 0-100 scale)
+pub(crate) fn parse_f64_as(
+    s: &str,
+    unit: NumberUnit,
+    locale: Option<NumberLocale>,
+) -> Result<ParsedNumber, String> {
+    let number = normalize_number(s, locale)?;
+    let sanitized = &number.sanitized;
+
+    let written = match number.digits.parse::<f64>() {
+        Ok(value) => {
+            let magnitude = value * 10f64.powi(number.exponent as i32);
+            if number.negative { -magnitude } else { magnitude }
+        }
+        Err(_) => return Err(format!("Cannot parse '{}' as a number", sanitized)),
+    };
+    if written.is_infinite() || written.is_nan() {
+        return Err(format!("Invalid number: '{}'", sanitized));
+    }
+
+    let value = match (number.percent, unit) {
+        (Some(_), NumberUnit::Amount) => {
+            return Err(format!("'{}' is a percentage, not an amount", sanitized));
+        }
+        (Some(PercentNotation::Percent), NumberUnit::Ratio) => written / 100.0,
+        (Some(PercentNotation::BasisPoints), NumberUnit::Ratio) => written / 10_000.0,
+        (Some(PercentNotation::BasisPoints), NumberUnit::Percentage) => written / 100.0,
+        _ => written,
+    };
+
+    let scaled = number.percent.is_some() && value != written;
+    let note = (number.interpreted || scaled).then(|| format!("interpreted '{}' as {}", sanitized, value));
+    let warning = match (number.percent, unit) {
+        (None, NumberUnit::Ratio) if value.abs() > PLAUSIBLE_RATIO_MAX => Some(format!(
+            "'{}' was read as a ratio ({}%); write '{}%' if it is a percentage",
+            sanitized,
+            as_percent(value),
+            value
+        )),
+        (None, NumberUnit::Percentage) if value != 0.0 && value.abs() < 1.0 => Some(format!(
+            "'{}' was read on a 0-100 scale; write '{}' if it is a ratio",
+            sanitized,
+            as_percent(value)
+        )),
+        _ => None,
+    };
+
+    Ok(ParsedNumber { value, note, warning })
+}
+
+/// A ratio as a percentage, without binary noise in messages (0.85 -> 85, not 85.00000000000001)
+fn as_percent(ratio: f64) -> f64 {
+    (ratio * 100.0 * 1e6).round() / 1e6
+}
+
+/// Collects the notes and warnings from the unit-aware fields of one request
+#[derive(Debug, Default)]
+pub struct FieldParser {
+    locale: Option<NumberLocale>,
+    notes: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl FieldParser {
+    /// Parser for a request's optional `locale` hint
+    pub fn new(locale: Option<&str>) -> Result<Self, String> {
+        Ok(Self {
+            locale: NumberLocale::from_option(locale)?,
+            ..Default::default()
+        })
+    }
+
+    /// Parse a field in its declared unit, recording how it was read
+    pub fn parse(&mut self, value: &str, field: &str, unit: NumberUnit) -> Result<f64, String> {
+        let parsed = parse_f64_as(value, unit, self.locale)?;
+        if let Some(note) = parsed.note {
+            self.notes.push(format!("{}: {}", field, note));
+        }
+        if let Some(warning) = parsed.warning {
+            self.warnings.push(format!("{}: {}", field, warning));
+        }
+        Ok(parsed.value)
+    }
+
+    pub fn take_notes(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.notes)
+    }
+
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.warnings)
+    }
+}
+
+/// Parse an amount, returning a note when suffixes or accounting notation were interpreted
+pub(crate) fn parse_f64_with_note(s: &str, locale: Option<NumberLocale>) -> Result<(f64, Option<String>), String> {
+    parse_f64_as(s, NumberUnit::Amount, locale).map(|parsed| (parsed.value, parsed.note))
+}
+
+/// Parse a string to f64 for values without a declared unit; percent notation keeps its
+/// written scale ("12%" is 12)
+pub(crate) fn parse_f64_from_string(s: &str) -> Result<f64, String> {
+    parse_f64_as(s, NumberUnit::Percentage, None).map(|parsed| parsed.value)
+}
+
+/// Parse a value without a declared unit like `parse_f64_from_string`, also returning whether it
+/// was written in percent notation so a replacement value can be written the same way
+pub(crate) fn parse_f64_keeping_percent(s: &str, locale: Option<NumberLocale>) -> Result<(f64, bool), String> {
+    let percent = normalize_number(s, locale)?.percent.is_some();
+    parse_f64_as(s, NumberUnit::Percentage, locale).map(|parsed| (parsed.value, percent))
+}
+
+/// Parse a string to an exact decimal, accepting the same inputs as `parse_f64_from_string`
+pub(crate) fn parse_decimal_with_note(
+    s: &str,
+    locale: Option<NumberLocale>,
+) -> Result<(Decimal, Option<String>), String> {
+    let number = normalize_number(s, locale)?;
+    if number.percent.is_some() {
+        return Err(format!("'{}' is a percentage, not an amount", number.sanitized));
+    }
+    let invalid = || format!("Cannot parse '{}' as a number", number.sanitized);
+
+    let value = number
+        .digits
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(&number.digits))
+        .map_err(|_| invalid())?;
+    let magnitude = value
+        .checked_mul(Decimal::from(10u64.pow(number.exponent)))
+        .ok_or_else(invalid)?;
+    let value = if number.negative { -magnitude } else { magnitude };
+    let note = number
+        .interpreted
+        .then(|| format!("interpreted '{}' as {}", number.sanitized, value.normalize()));
+    Ok((value, note))
+}
+
+// =================== CUSTOM DESERIALIZERS ===================
+
+/// Custom deserializer that accepts both f64 numbers and strings
+pub(crate) fn deserialize_flexible_f64<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct FlexibleF64Visitor;
+
+    impl<'de> de::Visitor<'de> for FlexibleF64Visitor {
+        type Value = String;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a number or a string representing a number")
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value.to_string())
+        }
+
+        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value)
+        }
+    }
+
+    deserializer.deserialize_any(FlexibleF64Visitor)
+}
+
+/// Custom deserializer for optional values that accepts both f64 numbers and strings
+pub(crate) fn deserialize_flexible_f64_opt<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Flexible(#[serde(deserialize_with = "deserialize_flexible_f64")] String);
+
+    Option::<Flexible>::deserialize(deserializer).map(|v| v.map(|Flexible(s)| s))
+}
+
+/// Custom deserializer for lists that accepts both f64 numbers and strings as elements
+pub(crate) fn deserialize_flexible_f64_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Flexible(#[serde(deserialize_with = "deserialize_flexible_f64")] String);
+
+    Vec::<Flexible>::deserialize(deserializer).map(|v| v.into_iter().map(|Flexible(s)| s).collect())
+}
+
+/// Custom deserializer for optional boolean that accepts both boolean and string representations
+pub(crate) fn deserialize_bool_flexible_opt<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::{self, Visitor};
+    use std::fmt;
+
+    struct BoolVisitor;
+
+    impl<'de> Visitor<'de> for BoolVisitor {
+        type Value = Option<bool>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a boolean or a string representing a boolean")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+
+        fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            deserializer.deserialize_any(InnerBoolVisitor).map(Some)
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Some(value))
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            match value.to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(Some(true)),
+                "false" | "0" | "no" => Ok(Some(false)),
+                _ => Err(de::Error::custom(format!(
+                    "invalid boolean string: {}",
+                    value
+                ))),
+            }
+        }
+
+        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            self.visit_str(&value)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(None)
+        }
+    }
+
+    struct InnerBoolVisitor;
+
+    impl<'de> Visitor<'de> for InnerBoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a boolean or a string representing a boolean")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(value)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            match value.to_lowercase().as_str() {
+                "true" | "1" | "yes" => Ok(true),
+                "false" | "0" | "no" => Ok(false),
+                _ => Err(de::Error::custom(format!(
+                    "invalid boolean string: {}",
+                    value
+                ))),
+            }
+        }
+
+        fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            self.visit_str(&value)
+        }
+    }
+
+    deserializer.deserialize_option(BoolVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_declared_units() {
+        assert_eq!(parse_f64_as("9 pct", NumberUnit::Ratio, None).unwrap().value, 0.09);
+        assert_eq!(parse_f64_as("900bps", NumberUnit::Ratio, None).unwrap().value, 0.09);
+        assert_eq!(parse_f64_as("(2.5%)", NumberUnit::Ratio, None).unwrap().value, -0.025);
+        assert_eq!(parse_f64_as("900bps", NumberUnit::Percentage, None).unwrap().value, 9.0);
+        assert_eq!(parse_f64_as("9%", NumberUnit::Percentage, None).unwrap().value, 9.0);
+        assert!(parse_f64_as("9%", NumberUnit::Amount, None).is_err());
+        assert!(parse_f64_with_note("12%", None).is_err());
+
+        let bare = parse_f64_as("9", NumberUnit::Ratio, None).unwrap();
+        assert_eq!(bare.value, 9.0);
+        assert_eq!(bare.warning.unwrap(), "'9' was read as a ratio (900%); write '9%' if it is a percentage");
+        assert_eq!(parse_f64_as("0.09", NumberUnit::Ratio, None).unwrap().warning, None);
+    }
+
+    #[test]
+    fn test_parse_magnitude_suffixes_and_accounting_notation() {
+        assert_eq!(parse_f64_from_string("450M").unwrap(), 450_000_000.0);
+        assert_eq!(parse_f64_from_string("12.5 bn").unwrap(), 12_500_000_000.0);
+        assert_eq!(parse_f64_from_string("(3,200)").unwrap(), -3200.0);
+        assert_eq!(parse_f64_from_string("3,200-").unwrap(), -3200.0);
+        assert_eq!(parse_f64_from_string("\u{2212}3,200").unwrap(), -3200.0);
+        assert_eq!(parse_f64_from_string("1,234").unwrap(), 1234.0);
+        assert!(parse_f64_from_string("-(3,200)").is_err());
+        assert!(parse_f64_from_string("12 apples").is_err());
+
+        let (value, note) = parse_decimal_with_note("3,4 Mio", None).unwrap();
+        assert_eq!(value, Decimal::new(3_400_000, 0));
+        assert_eq!(note.unwrap(), "interpreted '3,4 Mio' as 3400000");
+        assert_eq!(parse_f64_with_note("1200", None).unwrap().1, None);
+    }
+
+    #[test]
+    fn test_parse_locale_digit_grouping() {
+        assert_eq!(parse_f64_from_string("1.234,56").unwrap(), 1234.56);
+        assert_eq!(parse_f64_from_string("1,234.56").unwrap(), 1234.56);
+        assert_eq!(parse_f64_from_string("1.234.567").unwrap(), 1_234_567.0);
+        assert_eq!(parse_f64_from_string("1 234,56").unwrap(), 1234.56);
+        assert_eq!(parse_f64_from_string("1'234.56").unwrap(), 1234.56);
+        assert!(parse_f64_from_string("1,23,456").is_err());
+
+        let (value, note) = parse_f64_with_note("1.234,56", None).unwrap();
+        assert_eq!(value, 1234.56);
+        assert_eq!(note.unwrap(), "interpreted '1.234,56' as 1234.56");
+
+        let comma = NumberLocale::from_tag("es_ES").unwrap();
+        assert_eq!(comma, NumberLocale::DecimalComma);
+        assert_eq!(parse_f64_with_note("1.234", Some(comma)).unwrap(), (1234.0, None));
+        assert!(parse_f64_with_note("1.5", Some(comma)).is_err());
+        assert_eq!(parse_f64_with_note("1.234", None).unwrap().0, 1.234);
+        assert_eq!(NumberLocale::from_tag("de-CH").unwrap(), NumberLocale::DecimalPoint);
+        assert!(NumberLocale::from_tag("xx").is_err());
+    }
+
+    #[test]
+    fn test_error_message_truncates_multibyte_input() {
+        // 56 characters in 66 bytes, with a two-byte 'é' straddling byte 47
+        let input = format!("1{}{}", "0".repeat(45), "é".repeat(10));
+        let error = parse_f64_from_string(&input).unwrap_err();
+        assert!(error.contains(&format!("'1{}?...'", "0".repeat(45))));
+        assert_eq!(sanitize_for_error_message("\u{2212}3\u{202f}200"), "?3?200");
+    }
+}
@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::error::FinanceError;
+use super::json_path::{get_number_at_path, numeric_leaf_paths, output_number, set_number_at_path};
+
+const DEFAULT_SHOCK_PCT: f64 = 0.10;
+const THRESHOLD_SEARCH_STEPS: usize = 40;
+const THRESHOLD_BISECTION_STEPS: usize = 60;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: analyze_scenarios
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct NamedScenario {
+    #[schemars(description = "Scenario name (e.g., 'bull', 'bear')")]
+    pub name: String,
+    #[schemars(description = "Input values to override, keyed by input path (e.g., {'revenue_growth': 0.05}), in the notation of the base argument: an input given as '9%' is overridden with 5 for 5%")]
+    pub overrides: HashMap<String, f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SensitivitySpec {
+    #[schemars(description = "Input paths to shock (default: every numeric input in base arguments)")]
+    #[serde(default)]
+    pub inputs: Option<Vec<String>>,
+    #[schemars(description = "Relative shock applied up and down as decimal (default: 0.10 for ±10%). Inputs at zero are shocked by the same absolute amount")]
+    #[serde(default)]
+    pub shock_pct: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ScenarioAnalysisParams {
+    #[schemars(description = "Name of the calculation to analyze (e.g., 'calculate_company_health_score')")]
+    pub function_name: String,
+    #[schemars(description = "Base arguments for the calculation, exactly as they would be passed to the tool")]
+    pub base_arguments: serde_json::Value,
+    #[schemars(description = "Named scenarios with input overrides, evaluated side by side with the base case")]
+    #[serde(default)]
+    pub scenarios: Option<Vec<NamedScenario>>,
+    #[schemars(description = "One-at-a-time ±% shocks for tornado ranking and band threshold search")]
+    #[serde(default)]
+    pub sensitivity: Option<SensitivitySpec>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ScenarioResult {
+    #[schemars(description = "Scenario name")]
+    pub name: String,
+    #[schemars(description = "Input overrides applied on top of the base arguments")]
+    pub overrides: HashMap<String, f64>,
+    #[schemars(description = "Headline metric value, absent if the scenario failed validation")]
+    pub primary_value: Option<f64>,
+    #[schemars(description = "Change in the headline metric versus the base case")]
+    pub delta_vs_base: Option<f64>,
+    #[schemars(description = "Risk/rating band for the scenario")]
+    pub band: Option<String>,
+    #[schemars(description = "Full calculation response for the scenario")]
+    pub result: Option<serde_json::Value>,
+    #[schemars(description = "Validation error if the calculation rejected the scenario")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct TornadoEntry {
+    #[schemars(description = "Input path")]
+    pub input: String,
+    #[schemars(description = "Input value in the base case, as written ('9%' is 9)")]
+    pub base_value: f64,
+    #[schemars(description = "Input value after the downward shock")]
+    pub low_input: f64,
+    #[schemars(description = "Input value after the upward shock")]
+    pub high_input: f64,
+    #[schemars(description = "Headline metric with the downward shock")]
+    pub metric_at_low: Option<f64>,
+    #[schemars(description = "Headline metric with the upward shock")]
+    pub metric_at_high: Option<f64>,
+    #[schemars(description = "Absolute range of the headline metric across both shocks")]
+    pub swing: f64,
+    #[schemars(description = "Influence rank, 1 being the most influential input")]
+    pub rank: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BandThreshold {
+    #[schemars(description = "Input path")]
+    pub input: String,
+    #[schemars(description = "Input value in the base case")]
+    pub base_value: f64,
+    #[schemars(description = "Direction of the input move: increase or decrease")]
+    pub direction: String,
+    #[schemars(description = "Input value at which the band flips")]
+    pub threshold_value: f64,
+    #[schemars(description = "Relative distance from the base value as percentage")]
+    pub change_pct: Option<f64>,
+    #[schemars(description = "Band in the base case")]
+    pub band_from: String,
+    #[schemars(description = "Band reached past the threshold")]
+    pub band_to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ScenarioAnalysisResponse {
+    #[schemars(description = "Calculation that was analyzed")]
+    pub function_name: String,
+    #[schemars(description = "Headline output of the calculation")]
+    pub primary_metric: String,
+    #[schemars(description = "Output field holding the risk/rating band, if the calculation has one")]
+    pub band_field: Option<String>,
+    #[schemars(description = "Base case result")]
+    pub base: ScenarioResult,
+    #[schemars(description = "Named scenario results, side by side with the base case")]
+    pub scenarios: Vec<ScenarioResult>,
+    #[schemars(description = "Inputs ranked by influence on the headline metric (tornado chart)")]
+    pub tornado: Vec<TornadoEntry>,
+    #[schemars(description = "Input values at which the risk/rating band flips, nearest first per input")]
+    pub band_thresholds: Vec<BandThreshold>,
+}
+
+// =================== ANALYSIS ===================
+
+/// Headline metric and band extracted from one calculation run
+struct Evaluation {
+    primary_value: Option<f64>,
+    band: Option<String>,
+    result: serde_json::Value,
+}
+
+struct Evaluator<'a, F> {
+    base_arguments: &'a serde_json::Value,
+    primary_metric: &'a str,
+    band_field: Option<&'a str>,
+    run: F,
+}
+
+impl<F> Evaluator<'_, F>
+where
+    F: Fn(serde_json::Value) -> Result<serde_json::Value, FinanceError>,
+{
+    fn evaluate(&self, overrides: &[(&str, f64)]) -> Result<Evaluation, FinanceError> {
+        let mut arguments = self.base_arguments.clone();
+        for (path, value) in overrides.iter() {
+            set_number_at_path(&mut arguments, path, *value)
+                .map_err(|e| FinanceError::invalid_input(e).with_field(&format!("base_arguments.{}", path)))?;
+        }
+
+        let result = (self.run)(arguments)?;
+        Ok(Evaluation {
+            primary_value: result.get(self.primary_metric).and_then(output_number),
+            band: self
+                .band_field
+                .and_then(|f| result.get(f))
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            result,
+        })
+    }
+
+    fn band_at(&self, path: &str, value: f64) -> Option<String> {
+        self.evaluate(&[(path, value)]).ok().and_then(|e| e.band)
+    }
+
+    /// Walk away from the base value in growing steps until the band changes,
+    /// then bisect the bracket to locate the flip point
+    fn find_threshold(&self, path: &str, base_value: f64, base_band: &str, direction: f64) -> Option<(f64, String)> {
+        let scale = if base_value != 0.0 { base_value.abs() } else { 1.0 };
+        let mut step = scale * 0.01;
+        let mut same = base_value;
+
+        for _ in 0..THRESHOLD_SEARCH_STEPS {
+            let candidate = base_value + direction * step;
+            // A rejected input means the calculation's valid range ends before any flip
+            let band = self.band_at(path, candidate)?;
+            if band != base_band {
+                let (mut lo, mut hi, mut flipped) = (same, candidate, band);
+                for _ in 0..THRESHOLD_BISECTION_STEPS {
+                    if (hi - lo).abs() <= scale * 1e-9 {
+                        break;
+                    }
+                    let mid = (lo + hi) / 2.0;
+                    match self.band_at(path, mid) {
+                        Some(b) if b == base_band => lo = mid,
+                        Some(b) => {
+                            hi = mid;
+                            flipped = b;
+                        }
+                        None => break,
+                    }
+                }
+                return Some((hi, flipped));
+            }
+            same = candidate;
+            step *= 2.0;
+        }
+
+        None
+    }
+}
+
+fn scenario_result(
+    name: &str,
+    overrides: HashMap<String, f64>,
+    evaluation: Result<Evaluation, FinanceError>,
+    base_value: Option<f64>,
+) -> ScenarioResult {
+    match evaluation {
+        Ok(e) => ScenarioResult {
+            name: name.to_string(),
+            overrides,
+            delta_vs_base: e.primary_value.zip(base_value).map(|(v, b)| v - b),
+            primary_value: e.primary_value,
+            band: e.band,
+            result: Some(e.result),
+            error: None,
+        },
+        Err(error) => ScenarioResult {
+            name: name.to_string(),
+            overrides,
+            primary_value: None,
+            delta_vs_base: None,
+            band: None,
+            result: None,
+            error: Some(error.message),
+        },
+    }
+}
+
+/// Evaluate named scenarios and one-at-a-time sensitivities around a base case.
+///
+/// `run` evaluates the calculation for one set of arguments and returns its JSON response.
+/// `primary_metric` and `band_field` name the headline numeric output and the rating band.
+pub fn analyze_scenarios_internal<F>(
+    params: ScenarioAnalysisParams,
+    primary_metric: &str,
+    band_field: Option<&str>,
+    run: F,
+) -> Result<ScenarioAnalysisResponse, FinanceError>
+where
+    F: Fn(serde_json::Value) -> Result<serde_json::Value, FinanceError>,
+{
+    if params.scenarios.is_none() && params.sensitivity.is_none() {
+        return Err(FinanceError::invalid_input("Provide named scenarios, sensitivity shocks, or both"));
+    }
+
+    let evaluator = Evaluator {
+        base_arguments: &params.base_arguments,
+        primary_metric,
+        band_field,
+        run,
+    };
+
+    let base_evaluation = evaluator
+        .evaluate(&[])
+        .map_err(|e| e.context("Base case failed"))?;
+    let base_value = base_evaluation.primary_value;
+    let base_band = base_evaluation.band.clone();
+    let base = scenario_result("base", HashMap::new(), Ok(base_evaluation), base_value);
+
+    // Named scenarios
+    let mut scenarios = Vec::new();
+    for scenario in params.scenarios.unwrap_or_default() {
+        let mut overrides: Vec<(&str, f64)> = scenario.overrides.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        overrides.sort_by(|a, b| a.0.cmp(b.0));
+        let evaluation = evaluator.evaluate(&overrides);
+        scenarios.push(scenario_result(&scenario.name, scenario.overrides.clone(), evaluation, base_value));
+    }
+
+    let mut tornado = Vec::new();
+    let mut band_thresholds = Vec::new();
+
+    if let Some(sensitivity) = params.sensitivity {
+        let shock = sensitivity.shock_pct.unwrap_or(DEFAULT_SHOCK_PCT);
+        if !(shock > 0.0 && shock.is_finite()) {
+            return Err(FinanceError::out_of_range(
+                "sensitivity.shock_pct",
+                Some(0.0),
+                None,
+                "shock_pct must be a positive number",
+            ));
+        }
+
+        let inputs = sensitivity
+            .inputs
+            .unwrap_or_else(|| numeric_leaf_paths(&params.base_arguments));
+        if inputs.is_empty() {
+            return Err(FinanceError::insufficient_data("No numeric inputs found to shock").with_field("sensitivity.inputs"));
+        }
+
+        for input in inputs.iter() {
+            let value = get_number_at_path(&params.base_arguments, input)
+                .map_err(|e| FinanceError::invalid_input(e).with_field("sensitivity.inputs"))?;
+            let delta = if value != 0.0 { value.abs() * shock } else { shock };
+            let (low_input, high_input) = (value - delta, value + delta);
+
+            let metric_at_low = evaluator.evaluate(&[(input, low_input)]).ok().and_then(|e| e.primary_value);
+            let metric_at_high = evaluator.evaluate(&[(input, high_input)]).ok().and_then(|e| e.primary_value);
+
+            let observed: Vec<f64> = [metric_at_low, base_value, metric_at_high].into_iter().flatten().collect();
+            let swing = observed.iter().cloned().fold(f64::MIN, f64::max)
+                - observed.iter().cloned().fold(f64::MAX, f64::min);
+
+            tornado.push(TornadoEntry {
+                input: input.clone(),
+                base_value: value,
+                low_input,
+                high_input,
+                metric_at_low,
+                metric_at_high,
+                swing: if observed.is_empty() { 0.0 } else { swing },
+                rank: 0,
+            });
+
+            if let Some(band_from) = base_band.as_deref() {
+                for (direction, label) in [(1.0, "increase"), (-1.0, "decrease")] {
+                    if let Some((threshold_value, band_to)) = evaluator.find_threshold(input, value, band_from, direction) {
+                        band_thresholds.push(BandThreshold {
+                            input: input.clone(),
+                            base_value: value,
+                            direction: label.to_string(),
+                            threshold_value,
+                            change_pct: (value != 0.0).then(|| (threshold_value - value) / value.abs() * 100.0),
+                            band_from: band_from.to_string(),
+                            band_to,
+                        });
+                    }
+                }
+            }
+        }
+
+        tornado.sort_by(|a, b| b.swing.partial_cmp(&a.swing).unwrap());
+        for (i, entry) in tornado.iter_mut().enumerate() {
+            entry.rank = i + 1;
+        }
+    }
+
+    Ok(ScenarioAnalysisResponse {
+        function_name: params.function_name,
+        primary_metric: primary_metric.to_string(),
+        band_field: band_field.map(|f| f.to_string()),
+        base,
+        scenarios,
+        tornado,
+        band_thresholds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::calculation::{calculation_output_fields, run_calculation_internal};
+
+    /// Analyze a calculation by name, as the analyze_scenarios tool does
+    fn analyze(params: ScenarioAnalysisParams) -> Result<ScenarioAnalysisResponse, FinanceError> {
+        let (primary_metric, band_field) = calculation_output_fields(&params.function_name).unwrap();
+        let function_name = params.function_name.clone();
+        analyze_scenarios_internal(params, primary_metric, band_field, |arguments| {
+            run_calculation_internal(&function_name, arguments)
+        })
+    }
+
+    #[test]
+    fn test_analyze_scenarios_named_and_sensitivity() {
+        let params: ScenarioAnalysisParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_company_health_score",
+            "base_arguments": {
+                "revenue_growth": "0.09",
+                "sla_compliance": "0.985",
+                "customer_satisfaction": "89.0"
+            },
+            "scenarios": [
+                {"name": "bull", "overrides": {"revenue_growth": 0.15}},
+                {"name": "bear", "overrides": {"revenue_growth": 0.05}}
+            ],
+            "sensitivity": {"shock_pct": 0.10}
+        })).unwrap();
+
+        let response = analyze(params).unwrap();
+
+        assert_eq!(response.base.band.as_deref(), Some("LOW"));
+        assert_eq!(response.scenarios.len(), 2);
+        // bull: 100*0.40 + 34.475 + 22.25 = 96.725
+        assert!((response.scenarios[0].primary_value.unwrap() - 96.725).abs() < 0.01);
+        assert!(response.scenarios[0].delta_vs_base.unwrap() > 0.0);
+        assert_eq!(response.scenarios[1].band.as_deref(), Some("MEDIUM"));
+
+        // Tornado covers all three inputs, ranked by swing
+        assert_eq!(response.tornado.len(), 3);
+        assert_eq!(response.tornado[0].rank, 1);
+        assert!(response.tornado[0].swing >= response.tornado[2].swing);
+
+        // Score drops below 80 once the revenue component falls under
+        // (80 - 34.475 - 22.25) / 0.40 = 58.1875 points, i.e. 0.0872813 growth
+        let threshold = response
+            .band_thresholds
+            .iter()
+            .find(|t| t.input == "revenue_growth" && t.direction == "decrease")
+            .unwrap();
+        assert!((threshold.threshold_value - 0.0872813).abs() < 1e-5);
+        assert_eq!(threshold.band_to, "MEDIUM");
+    }
+
+    #[test]
+    fn test_analyze_scenarios_keeps_percent_notation() {
+        let params: ScenarioAnalysisParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_company_health_score",
+            "base_arguments": {
+                "revenue_growth": "9%",
+                "sla_compliance": "0.985",
+                "customer_satisfaction": "89.0"
+            },
+            "scenarios": [{"name": "bull", "overrides": {"revenue_growth": 15.0}}],
+            "sensitivity": {"inputs": ["revenue_growth"], "shock_pct": 0.10}
+        })).unwrap();
+
+        let response = analyze(params).unwrap();
+
+        // "9%" is shocked to "8.1%" and "9.9%", not to ratios of 8.1 and 9.9
+        assert!((response.base.primary_value.unwrap() - 80.725).abs() < 1e-9);
+        let revenue = &response.tornado[0];
+        assert_eq!(revenue.base_value, 9.0);
+        assert!((revenue.metric_at_low.unwrap() - 78.325).abs() < 1e-9);
+        assert!((revenue.metric_at_high.unwrap() - 83.125).abs() < 1e-9);
+        assert!((response.scenarios[0].primary_value.unwrap() - 96.725).abs() < 1e-9);
+
+        let threshold = response.band_thresholds.iter().find(|t| t.direction == "decrease").unwrap();
+        assert!((threshold.threshold_value - 8.72813).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_analyze_scenarios_keeps_currency_tags() {
+        let params: ScenarioAnalysisParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_organic_growth",
+            "base_arguments": {
+                "revenue_prior": "EUR 100",
+                "revenue_current": "EUR 110",
+                "reporting_currency": "USD",
+                "fx_rates": {"EUR": 1.2},
+                "prior_fx_rates": {"EUR": 1.2}
+            },
+            "sensitivity": {"inputs": ["revenue_current"], "shock_pct": 0.10}
+        })).unwrap();
+
+        let response = analyze(params).unwrap();
+
+        // "EUR 110" is shocked to "EUR 99" and "EUR 121", still converted at 1.2
+        let current = &response.tornado[0];
+        assert_eq!(current.base_value, 110.0);
+        assert!((current.metric_at_low.unwrap() - -0.01).abs() < 1e-9);
+        assert!((current.metric_at_high.unwrap() - 0.21).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_analyze_scenarios_reads_decimal_primary_metric() {
+        let params: ScenarioAnalysisParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_budget_variance",
+            "base_arguments": {
+                "line_items": [
+                    {"name": "Sales", "line_type": "revenue", "budget": 1000, "actual": 1100},
+                    {"name": "Rent", "line_type": "opex", "budget": 200, "actual": 250}
+                ]
+            },
+            "scenarios": [{"name": "overspend", "overrides": {"line_items.1.actual": 400}}]
+        })).unwrap();
+
+        let response = analyze(params).unwrap();
+
+        // operating_income_variance serializes as the exact string "50.00"
+        assert_eq!(response.base.primary_value, Some(50.0));
+        assert_eq!(response.scenarios[0].primary_value, Some(-100.0));
+        assert_eq!(response.scenarios[0].delta_vs_base, Some(-150.0));
+    }
+
+    #[test]
+    fn test_analyze_scenarios_requires_scenarios_or_sensitivity() {
+        let params: ScenarioAnalysisParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_organic_growth",
+            "base_arguments": {"revenue_prior": 48.7, "revenue_current": 53.0}
+        })).unwrap();
+
+        let error = analyze(params).unwrap_err();
+        assert!(error.message.contains("Provide named scenarios, sensitivity shocks, or both"));
+    }
+}
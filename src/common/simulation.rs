@@ -0,0 +1,445 @@
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::error::FinanceError;
+use super::json_path::{output_number, set_number_at_path};
+
+const DEFAULT_ITERATIONS: usize = 10_000;
+const MAX_ITERATIONS: usize = 100_000;
+const DEFAULT_HISTOGRAM_BINS: usize = 20;
+const MAX_HISTOGRAM_BINS: usize = 100;
+const MAX_ERROR_SAMPLES: usize = 5;
+const PERCENTILES: [(&str, f64); 7] = [
+    ("p5", 0.05),
+    ("p10", 0.10),
+    ("p25", 0.25),
+    ("p50", 0.50),
+    ("p75", 0.75),
+    ("p90", 0.90),
+    ("p95", 0.95),
+];
+
+// =================== DATA STRUCTURES ===================
+
+// Function: simulate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Distribution {
+    /// Normal distribution defined by mean and standard deviation
+    Normal {
+        #[schemars(description = "Mean of the distribution")]
+        mean: f64,
+        #[schemars(description = "Standard deviation (must be non-negative)")]
+        std_dev: f64,
+    },
+    /// Triangular distribution defined by minimum, most likely and maximum values
+    Triangular {
+        #[schemars(description = "Minimum value")]
+        min: f64,
+        #[schemars(description = "Most likely value")]
+        mode: f64,
+        #[schemars(description = "Maximum value")]
+        max: f64,
+    },
+    /// Uniform distribution between minimum and maximum
+    Uniform {
+        #[schemars(description = "Minimum value")]
+        min: f64,
+        #[schemars(description = "Maximum value")]
+        max: f64,
+    },
+    /// Lognormal distribution defined by the parameters of the underlying normal
+    Lognormal {
+        #[schemars(description = "Mean of the underlying normal (ln of the median)")]
+        mu: f64,
+        #[schemars(description = "Standard deviation of the underlying normal (must be non-negative)")]
+        sigma: f64,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SimulationParams {
+    #[schemars(description = "Name of the calculation to simulate (e.g., 'calculate_company_health_score')")]
+    pub function_name: String,
+    #[schemars(description = "Base arguments for the calculation, exactly as they would be passed to the tool")]
+    pub base_arguments: serde_json::Value,
+    #[schemars(description = "Probability distribution per numeric input, keyed by input path (e.g., 'revenue_growth', 'revenues.0', 'segments.cloud.revenue'). Samples are written in the notation of the base argument: an input given as '9%' is sampled in percent")]
+    pub distributions: HashMap<String, Distribution>,
+    #[schemars(description = "Number of Monte Carlo iterations (default: 10000, max: 100000)")]
+    #[serde(default)]
+    pub iterations: Option<usize>,
+    #[schemars(description = "Random seed for reproducible results (random if omitted)")]
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[schemars(description = "Number of histogram bins per output (default: 20, max: 100)")]
+    #[serde(default)]
+    pub histogram_bins: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct HistogramBin {
+    #[schemars(description = "Inclusive lower bound of the bin")]
+    pub lower: f64,
+    #[schemars(description = "Upper bound of the bin (inclusive for the last bin)")]
+    pub upper: f64,
+    #[schemars(description = "Number of iterations falling in the bin")]
+    pub count: usize,
+    #[schemars(description = "Share of successful iterations falling in the bin")]
+    pub frequency: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct OutputDistribution {
+    #[schemars(description = "Mean of the simulated output")]
+    pub mean: f64,
+    #[schemars(description = "Standard deviation of the simulated output")]
+    pub std_dev: f64,
+    #[schemars(description = "Minimum simulated value")]
+    pub min: f64,
+    #[schemars(description = "Maximum simulated value")]
+    pub max: f64,
+    #[schemars(description = "Percentiles p5, p10, p25, p50, p75, p90 and p95")]
+    pub percentiles: HashMap<String, f64>,
+    #[schemars(description = "Histogram of simulated values")]
+    pub histogram: Vec<HistogramBin>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct SimulationResponse {
+    #[schemars(description = "Calculation that was simulated")]
+    pub function_name: String,
+    #[schemars(description = "Number of iterations requested")]
+    pub iterations: usize,
+    #[schemars(description = "Iterations where the calculation succeeded")]
+    pub successful_iterations: usize,
+    #[schemars(description = "Iterations rejected by the calculation's validation")]
+    pub failed_iterations: usize,
+    #[schemars(description = "Seed used, to reproduce this simulation")]
+    pub seed: u64,
+    #[schemars(description = "Headline output of the calculation")]
+    pub primary_metric: String,
+    #[schemars(description = "Distribution of every numeric output of the calculation")]
+    pub outputs: HashMap<String, OutputDistribution>,
+    #[schemars(description = "Output field holding the risk/rating band, if the calculation has one")]
+    pub band_field: Option<String>,
+    #[schemars(description = "Probability of each risk/rating band across successful iterations")]
+    pub band_probabilities: HashMap<String, f64>,
+    #[schemars(description = "Sample of distinct validation errors from failed iterations")]
+    pub error_samples: Vec<String>,
+}
+
+// =================== SAMPLING ===================
+
+impl Distribution {
+    /// Validate distribution parameters
+    fn validate(&self, input: &str) -> Result<(), FinanceError> {
+        let valid = match *self {
+            Distribution::Normal { mean, std_dev } => mean.is_finite() && std_dev.is_finite() && std_dev >= 0.0,
+            Distribution::Triangular { min, mode, max } => min <= mode && mode <= max && min.is_finite() && max.is_finite(),
+            Distribution::Uniform { min, max } => min <= max && min.is_finite() && max.is_finite(),
+            Distribution::Lognormal { mu, sigma } => mu.is_finite() && sigma.is_finite() && sigma >= 0.0,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(FinanceError::invalid_input(format!("Invalid distribution parameters for '{}'", input))
+                .with_field(&format!("distributions.{}", input)))
+        }
+    }
+
+    /// Draw one sample using inverse transform or Box-Muller sampling
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match *self {
+            Distribution::Normal { mean, std_dev } => mean + std_dev * standard_normal(rng),
+            Distribution::Triangular { min, mode, max } => {
+                if max == min {
+                    return min;
+                }
+                let u: f64 = rng.random();
+                let split = (mode - min) / (max - min);
+                if u < split {
+                    min + (u * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+                }
+            }
+            Distribution::Uniform { min, max } => min + rng.random::<f64>() * (max - min),
+            Distribution::Lognormal { mu, sigma } => (mu + sigma * standard_normal(rng)).exp(),
+        }
+    }
+}
+
+/// Standard normal variate via the Box-Muller transform
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    // Shift u1 into (0, 1] so ln() stays finite
+    let u1: f64 = 1.0 - rng.random::<f64>();
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// =================== STATISTICS ===================
+
+/// Linear-interpolated percentile of an ascending-sorted slice
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+fn summarize(mut values: Vec<f64>, bins: usize) -> OutputDistribution {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let min = values[0];
+    let max = values[values.len() - 1];
+
+    let percentiles = PERCENTILES
+        .iter()
+        .map(|(name, p)| (name.to_string(), percentile(&values, *p)))
+        .collect();
+
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0usize; bins];
+    for v in values.iter() {
+        let index = if width > 0.0 {
+            (((v - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[index] += 1;
+    }
+
+    let histogram = counts
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| width > 0.0 || *i == 0)
+        .map(|(i, count)| HistogramBin {
+            lower: min + width * i as f64,
+            upper: if width > 0.0 { min + width * (i + 1) as f64 } else { max },
+            count,
+            frequency: count as f64 / n,
+        })
+        .collect();
+
+    OutputDistribution {
+        mean,
+        std_dev: variance.sqrt(),
+        min,
+        max,
+        percentiles,
+        histogram,
+    }
+}
+
+// =================== SIMULATION ===================
+
+/// Run a Monte Carlo simulation over a calculation.
+///
+/// `run` evaluates the calculation for one set of arguments and returns its JSON response.
+/// `primary_metric` and `band_field` name the headline numeric output and the rating band.
+pub fn simulate_internal<F>(
+    params: SimulationParams,
+    primary_metric: &str,
+    band_field: Option<&str>,
+    run: F,
+) -> Result<SimulationResponse, FinanceError>
+where
+    F: Fn(serde_json::Value) -> Result<serde_json::Value, FinanceError>,
+{
+    let iterations = params.iterations.unwrap_or(DEFAULT_ITERATIONS);
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return Err(FinanceError::out_of_range(
+            "iterations",
+            Some(1.0),
+            Some(MAX_ITERATIONS as f64),
+            format!("iterations must be between 1 and {}", MAX_ITERATIONS),
+        ));
+    }
+
+    let bins = params.histogram_bins.unwrap_or(DEFAULT_HISTOGRAM_BINS);
+    if bins == 0 || bins > MAX_HISTOGRAM_BINS {
+        return Err(FinanceError::out_of_range(
+            "histogram_bins",
+            Some(1.0),
+            Some(MAX_HISTOGRAM_BINS as f64),
+            format!("histogram_bins must be between 1 and {}", MAX_HISTOGRAM_BINS),
+        ));
+    }
+
+    if params.distributions.is_empty() {
+        return Err(FinanceError::insufficient_data("At least one input distribution is required").with_field("distributions"));
+    }
+
+    // Validate every distribution and path once before sampling
+    let mut inputs: Vec<(&String, &Distribution)> = params.distributions.iter().collect();
+    inputs.sort_by(|a, b| a.0.cmp(b.0));
+    let path_error = |path: &str, e: String| FinanceError::invalid_input(e).with_field(&format!("distributions.{}", path));
+    let mut probe = params.base_arguments.clone();
+    for (path, distribution) in inputs.iter() {
+        distribution.validate(path)?;
+        set_number_at_path(&mut probe, path, 0.0).map_err(|e| path_error(path, e))?;
+    }
+
+    let seed = params.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut samples: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut band_counts: HashMap<String, usize> = HashMap::new();
+    let mut error_samples: Vec<String> = Vec::new();
+    let mut successful_iterations = 0;
+
+    for _ in 0..iterations {
+        let mut arguments = params.base_arguments.clone();
+        for (path, distribution) in inputs.iter() {
+            set_number_at_path(&mut arguments, path, distribution.sample(&mut rng)).map_err(|e| path_error(path, e))?;
+        }
+
+        match run(arguments) {
+            Ok(serde_json::Value::Object(result)) => {
+                successful_iterations += 1;
+                for (field, value) in result.iter() {
+                    if let Some(number) = output_number(value) {
+                        samples.entry(field.clone()).or_default().push(number);
+                    }
+                }
+                if let Some(band) = band_field.and_then(|f| result.get(f)).and_then(|v| v.as_str()) {
+                    *band_counts.entry(band.to_string()).or_default() += 1;
+                }
+            }
+            Ok(_) => return Err(FinanceError::internal("Calculation did not return an object")),
+            Err(e) => {
+                if error_samples.len() < MAX_ERROR_SAMPLES && !error_samples.contains(&e.message) {
+                    error_samples.push(e.message);
+                }
+            }
+        }
+    }
+
+    if successful_iterations == 0 {
+        return Err(FinanceError::invalid_input(format!(
+            "All {} iterations failed validation: {}",
+            iterations,
+            error_samples.join("; ")
+        )));
+    }
+
+    let outputs = samples
+        .into_iter()
+        .map(|(field, values)| (field, summarize(values, bins)))
+        .collect();
+
+    let band_probabilities = band_counts
+        .into_iter()
+        .map(|(band, count)| (band, count as f64 / successful_iterations as f64))
+        .collect();
+
+    Ok(SimulationResponse {
+        function_name: params.function_name,
+        iterations,
+        successful_iterations,
+        failed_iterations: iterations - successful_iterations,
+        seed,
+        primary_metric: primary_metric.to_string(),
+        outputs,
+        band_field: band_field.map(|f| f.to_string()),
+        band_probabilities,
+        error_samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::calculation::{calculation_output_fields, run_calculation_internal};
+
+    /// Simulate a calculation by name, as the simulate tool does
+    fn simulate(params: SimulationParams) -> Result<SimulationResponse, FinanceError> {
+        let (primary_metric, band_field) = calculation_output_fields(&params.function_name).unwrap();
+        let function_name = params.function_name.clone();
+        simulate_internal(params, primary_metric, band_field, |arguments| {
+            run_calculation_internal(&function_name, arguments)
+        })
+    }
+
+    fn health_score_simulation_params(seed: u64) -> SimulationParams {
+        serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_company_health_score",
+            "base_arguments": {
+                "revenue_growth": "0.09",
+                "sla_compliance": "0.985",
+                "customer_satisfaction": "89.0"
+            },
+            "distributions": {
+                "revenue_growth": {"type": "normal", "mean": 0.09, "std_dev": 0.03},
+                "customer_satisfaction": {"type": "triangular", "min": 80.0, "mode": 89.0, "max": 95.0}
+            },
+            "iterations": 2000,
+            "seed": seed
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_simulate_company_health_score() {
+        let response = simulate(health_score_simulation_params(7)).unwrap();
+
+        assert_eq!(response.iterations, 2000);
+        assert_eq!(response.successful_iterations, 2000);
+        assert_eq!(response.primary_metric, "overall_score");
+        assert_eq!(response.band_field.as_deref(), Some("risk_level"));
+
+        // Deterministic score is 80.725, so the median should sit close to it
+        let overall = &response.outputs["overall_score"];
+        assert!((overall.percentiles["p50"] - 80.7).abs() < 1.5);
+        assert!(overall.percentiles["p5"] < overall.percentiles["p95"]);
+        assert_eq!(overall.histogram.iter().map(|b| b.count).sum::<usize>(), 2000);
+
+        // Scores straddle the 80-point LOW/MEDIUM boundary
+        let total: f64 = response.band_probabilities.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(response.band_probabilities["LOW"] > 0.0);
+        assert!(response.band_probabilities["MEDIUM"] > 0.0);
+    }
+
+    #[test]
+    fn test_simulate_is_reproducible_with_seed() {
+        let first = simulate(health_score_simulation_params(42)).unwrap();
+        let second = simulate(health_score_simulation_params(42)).unwrap();
+        assert_eq!(first.outputs["overall_score"], second.outputs["overall_score"]);
+    }
+
+    #[test]
+    fn test_simulate_counts_failed_iterations() {
+        let mut params = health_score_simulation_params(1);
+        // SLA compliance above 1.0 is rejected by the calculation
+        params.distributions.insert(
+            "sla_compliance".to_string(),
+            Distribution::Uniform { min: 0.9, max: 1.1 },
+        );
+
+        let response = simulate(params).unwrap();
+
+        assert!(response.failed_iterations > 0);
+        assert_eq!(response.failed_iterations + response.successful_iterations, 2000);
+        assert!(response.error_samples[0].contains("SLA compliance must be between"));
+    }
+
+    #[test]
+    fn test_simulate_invalid_input_path() {
+        let params: SimulationParams = serde_json::from_value(serde_json::json!({
+            "function_name": "calculate_hhi_and_diversification",
+            "base_arguments": {"revenues": [15.0, 25.0]},
+            "distributions": {"revenues.5": {"type": "uniform", "min": 1.0, "max": 2.0}}
+        })).unwrap();
+
+        let error = simulate(params).unwrap_err();
+        assert!(error.message.contains("not found in base arguments"));
+    }
+}
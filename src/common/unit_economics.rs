@@ -0,0 +1,371 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::calculation::{converter, fx_failure, field, field_parser, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::money::{allocate_percentages, decimal_from_f64, decimal_to_f64, round_ratio};
+use super::parsing::{deserialize_flexible_f64, deserialize_flexible_f64_opt, sanitize_for_error_message, FieldParser, NumberUnit};
+
+const THIN_CONTRIBUTION_MARGIN: f64 = 0.10;
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_unit_economics
+/// Segment data with costs; a superset of `PortfolioSegmentData`, so the same segment map
+/// can be passed to `calculate_portfolio_momentum` and `calculate_unit_economics`
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ProductLineData {
+    #[serde(deserialize_with = "deserialize_flexible_f64")]
+    #[schemars(description = "Product line revenue (number or currency-tagged amount, e.g., 'EUR 12.5')")]
+    pub revenue: String,
+    #[schemars(description = "Year-over-year growth rate as a ratio (optional, e.g., 0.20, '20%' or '2000bps')")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub growth_rate: Option<String>,
+    #[schemars(description = "Cost of goods sold (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub cogs: Option<String>,
+    #[schemars(description = "Other variable costs such as commissions, shipping or payment fees (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub variable_costs: Option<String>,
+    #[schemars(description = "Fixed costs allocated to the line (default: 0)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub allocated_fixed_costs: Option<String>,
+    #[schemars(description = "Units sold, for per-unit economics (optional)")]
+    #[serde(default)]
+    pub units: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnitEconomicsParams {
+    #[schemars(description = "Dictionary of product line names to revenue, costs and optional growth rate")]
+    pub segments: HashMap<String, ProductLineData>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct ProductLineEconomics {
+    #[schemars(description = "Rank by contribution, 1 = largest")]
+    pub rank: usize,
+    #[schemars(description = "Product line name")]
+    pub name: String,
+    #[schemars(description = "Revenue", with = "String")]
+    pub revenue: Decimal,
+    #[schemars(description = "Revenue as percentage of total")]
+    pub revenue_share_pct: f64,
+    #[schemars(description = "Revenue - COGS", with = "String")]
+    pub gross_profit: Decimal,
+    #[schemars(description = "Gross profit / revenue as percentage")]
+    pub gross_margin_pct: f64,
+    #[schemars(description = "Gross profit - variable costs", with = "String")]
+    pub contribution: Decimal,
+    #[schemars(description = "Contribution / revenue as percentage")]
+    pub contribution_margin_pct: f64,
+    #[schemars(description = "Line contribution as percentage of total contribution")]
+    pub contribution_share_pct: f64,
+    #[schemars(description = "Contribution - allocated fixed costs", with = "String")]
+    pub operating_profit: Decimal,
+    #[schemars(description = "Operating profit / revenue as percentage")]
+    pub operating_margin_pct: f64,
+    #[schemars(description = "Contribution per unit, when units are given", with = "Option<String>")]
+    pub contribution_per_unit: Option<Decimal>,
+    #[schemars(description = "Growth rate as percentage, when given")]
+    pub growth_pct: Option<f64>,
+    #[schemars(description = "Status: Profitable, Covers Variable Costs, or Negative Contribution")]
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct UnitEconomicsResponse {
+    #[schemars(description = "Product lines ranked by contribution")]
+    pub lines: Vec<ProductLineEconomics>,
+    #[schemars(description = "Total revenue", with = "String")]
+    pub total_revenue: Decimal,
+    #[schemars(description = "Total gross profit", with = "String")]
+    pub total_gross_profit: Decimal,
+    #[schemars(description = "Blended gross margin as percentage")]
+    pub gross_margin_pct: f64,
+    #[schemars(description = "Total contribution", with = "String")]
+    pub total_contribution: Decimal,
+    #[schemars(description = "Blended contribution margin as percentage")]
+    pub contribution_margin_pct: f64,
+    #[schemars(description = "Total operating profit after allocated fixed costs", with = "String")]
+    pub total_operating_profit: Decimal,
+    #[schemars(description = "Share of revenue from lines with non-negative operating profit as percentage")]
+    pub profitable_revenue_share_pct: f64,
+    #[schemars(description = "Lines whose contribution is negative")]
+    pub negative_contribution_lines: Vec<String>,
+    #[schemars(description = "Warnings about loss-making and thin-margin lines")]
+    pub unit_economics_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of product line economics")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 20 for a 20% growth rate)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+fn pct(part: Decimal, whole: Decimal) -> f64 {
+    if !whole.is_zero() { round_ratio(decimal_to_f64(part / whole) * 100.0, 2) } else { 0.0 }
+}
+
+struct LineFigures {
+    name: String,
+    revenue: Decimal,
+    gross_profit: Decimal,
+    contribution: Decimal,
+    operating_profit: Decimal,
+    units: Option<f64>,
+    growth_rate: Option<f64>,
+}
+
+/// Gross and contribution margin per product line, ranked by contribution
+pub fn calculate_unit_economics_internal(
+    params: &UnitEconomicsParams,
+    fx: &mut CurrencyConverter,
+    units: &mut FieldParser,
+) -> Result<UnitEconomicsResponse, FinanceError> {
+    if params.segments.is_empty() {
+        return Err(FinanceError::insufficient_data("Segments cannot be empty").with_field("segments"));
+    }
+
+    let rounding = fx.rounding();
+    let mut figures = Vec::with_capacity(params.segments.len());
+    for (name, data) in params.segments.iter() {
+        let mut amount = |value: Option<&str>, field: &str| -> Result<Decimal, FinanceError> {
+            let path = format!("segments.{}.{}", name, field);
+            let value = value
+                .map(|v| fx.parse_money(v, &path, Period::Current).map_err(|e| FinanceError::invalid_field(&path, e)))
+                .transpose()?
+                .unwrap_or_default();
+            if value < Decimal::ZERO {
+                return Err(FinanceError::out_of_range(&path, Some(0.0), None, format!("{} cannot be negative", path)));
+            }
+            Ok(value)
+        };
+        let revenue = amount(Some(&data.revenue), "revenue")?;
+        let cogs = amount(data.cogs.as_deref(), "cogs")?;
+        let variable_costs = amount(data.variable_costs.as_deref(), "variable_costs")?;
+        let fixed_costs = amount(data.allocated_fixed_costs.as_deref(), "allocated_fixed_costs")?;
+        if data.units.is_some_and(|u| u <= 0.0) {
+            let path = format!("segments.{}.units", name);
+            return Err(FinanceError::out_of_range(
+                &path,
+                Some(0.0),
+                None,
+                format!("{} must be positive", sanitize_for_error_message(&path)),
+            ));
+        }
+
+        let growth_rate = data
+            .growth_rate
+            .as_deref()
+            .map(|v| field(units, v, &format!("segments.{}.growth_rate", name), NumberUnit::Ratio))
+            .transpose()?;
+
+        let gross_profit = revenue - cogs;
+        let contribution = gross_profit - variable_costs;
+        figures.push(LineFigures {
+            name: name.clone(),
+            revenue,
+            gross_profit,
+            contribution,
+            operating_profit: contribution - fixed_costs,
+            units: data.units,
+            growth_rate,
+        });
+    }
+
+    let total_revenue: Decimal = figures.iter().map(|f| f.revenue).sum();
+    if total_revenue.is_zero() {
+        return Err(FinanceError::out_of_range("segments", None, None, "Total revenue cannot be zero"));
+    }
+    let total_gross_profit: Decimal = figures.iter().map(|f| f.gross_profit).sum();
+    let total_contribution: Decimal = figures.iter().map(|f| f.contribution).sum();
+    let total_operating_profit: Decimal = figures.iter().map(|f| f.operating_profit).sum();
+    let profitable_revenue: Decimal =
+        figures.iter().filter(|f| f.operating_profit >= Decimal::ZERO).map(|f| f.revenue).sum();
+
+    figures.sort_by(|a, b| b.contribution.cmp(&a.contribution).then_with(|| a.name.cmp(&b.name)));
+    let revenues: Vec<Decimal> = figures.iter().map(|f| f.revenue).collect();
+    let revenue_shares = allocate_percentages(&revenues, total_revenue, 2);
+
+    let mut unit_economics_issues = Vec::new();
+    let mut negative_contribution_lines = Vec::new();
+    let mut lines = Vec::with_capacity(figures.len());
+    for (i, f) in figures.iter().enumerate() {
+        let status = if f.contribution < Decimal::ZERO {
+            negative_contribution_lines.push(f.name.clone());
+            unit_economics_issues.push(format!(
+                "{} has negative contribution of {:.2}: each sale deepens the loss",
+                f.name, f.contribution
+            ));
+            if f.growth_rate.is_some_and(|g| g > 0.0) {
+                unit_economics_issues.push(format!("{} is growing while losing money on every sale", f.name));
+            }
+            "Negative Contribution"
+        } else if f.operating_profit < Decimal::ZERO {
+            unit_economics_issues.push(format!(
+                "{} covers variable costs but not its allocated fixed costs ({:.2} operating loss)",
+                f.name, f.operating_profit
+            ));
+            "Covers Variable Costs"
+        } else {
+            "Profitable"
+        };
+        if f.contribution >= Decimal::ZERO
+            && f.revenue > Decimal::ZERO
+            && decimal_to_f64(f.contribution / f.revenue) < THIN_CONTRIBUTION_MARGIN
+        {
+            unit_economics_issues.push(format!(
+                "{} contribution margin is below {:.0}%",
+                f.name,
+                THIN_CONTRIBUTION_MARGIN * 100.0
+            ));
+        }
+
+        lines.push(ProductLineEconomics {
+            rank: i + 1,
+            name: f.name.clone(),
+            revenue: rounding.round(f.revenue),
+            revenue_share_pct: decimal_to_f64(revenue_shares[i]),
+            gross_profit: rounding.round(f.gross_profit),
+            gross_margin_pct: pct(f.gross_profit, f.revenue),
+            contribution: rounding.round(f.contribution),
+            contribution_margin_pct: pct(f.contribution, f.revenue),
+            contribution_share_pct: pct(f.contribution, total_contribution),
+            operating_profit: rounding.round(f.operating_profit),
+            operating_margin_pct: pct(f.operating_profit, f.revenue),
+            contribution_per_unit: f
+                .units
+                .and_then(|u| decimal_from_f64(u).ok())
+                .map(|u| rounding.round(f.contribution / u)),
+            growth_pct: f.growth_rate.map(|g| round_ratio(g * 100.0, 2)),
+            status: status.to_string(),
+        });
+    }
+
+    let mut interpretation = format!(
+        "Blended contribution margin is {:.1}% on {:.2} of revenue; {:.1}% of revenue comes from lines that cover their allocated costs. {} contributes most.",
+        decimal_to_f64(total_contribution / total_revenue) * 100.0,
+        rounding.round(total_revenue),
+        decimal_to_f64(profitable_revenue / total_revenue) * 100.0,
+        figures[0].name
+    );
+    if !negative_contribution_lines.is_empty() {
+        interpretation.push_str(&format!(
+            " Negative contribution: {}.",
+            negative_contribution_lines.join(", ")
+        ));
+    }
+
+    Ok(UnitEconomicsResponse {
+        lines,
+        total_revenue: rounding.round(total_revenue),
+        total_gross_profit: rounding.round(total_gross_profit),
+        gross_margin_pct: pct(total_gross_profit, total_revenue),
+        total_contribution: rounding.round(total_contribution),
+        contribution_margin_pct: pct(total_contribution, total_revenue),
+        total_operating_profit: rounding.round(total_operating_profit),
+        profitable_revenue_share_pct: pct(profitable_revenue, total_revenue),
+        negative_contribution_lines,
+        unit_economics_issues,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Unit economics and contribution margin per product line (`calculate_unit_economics`)
+pub struct UnitEconomics;
+
+impl Calculation for UnitEconomics {
+    const NAME: &'static str = "calculate_unit_economics";
+    const DESCRIPTION: &'static str = "Calculate unit economics and contribution margin per product line. Takes a dictionary of product lines (the calculate_portfolio_momentum segment shape) with revenue, COGS, variable costs, allocated fixed costs, and optional units and growth rate. Returns per line gross profit and margin, contribution and contribution margin, operating profit after allocated fixed costs, contribution per unit, rank by contribution and status (Profitable, Covers Variable Costs, Negative Contribution), plus blended margins, the share of revenue from profitable lines, negative-contribution lines and unit economics issues. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "contribution_margin_pct";
+    const BAND_FIELD: Option<&'static str> = None;
+
+    type Params = UnitEconomicsParams;
+    type Response = UnitEconomicsResponse;
+
+    fn calculate(params: UnitEconomicsParams) -> Result<UnitEconomicsResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let mut result = calculate_unit_economics_internal(&params, &mut fx, &mut units)
+            .map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_unit_economics() {
+        let params: UnitEconomicsParams = serde_json::from_value(serde_json::json!({
+            "segments": {
+                "services": {"revenue": 30, "cogs": 25, "variable_costs": 10, "allocated_fixed_costs": 5, "growth_rate": 0.20},
+                "subscription": {"revenue": "100", "cogs": 20, "variable_costs": 10, "allocated_fixed_costs": 30, "units": 10},
+                "hardware": {"revenue": 50, "cogs": 40, "variable_costs": 5, "allocated_fixed_costs": 10}
+            }
+        }))
+        .unwrap();
+
+        let response = UnitEconomics::calculate(params).unwrap();
+
+        let ranking: Vec<&str> = response.lines.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(ranking, vec!["subscription", "hardware", "services"]);
+
+        let subscription = &response.lines[0];
+        assert_eq!(subscription.gross_margin_pct, 80.0);
+        assert_eq!(subscription.contribution_margin_pct, 70.0);
+        assert_eq!(subscription.contribution_per_unit, Some(Decimal::new(7, 0)));
+        assert_eq!(subscription.status, "Profitable");
+        assert_eq!(response.lines[1].status, "Covers Variable Costs");
+        assert_eq!(response.lines[2].status, "Negative Contribution");
+
+        // Contribution 70 + 5 - 5 on revenue 180; only subscription covers its fixed costs
+        assert_eq!(response.total_contribution, Decimal::new(70, 0));
+        assert_eq!(response.contribution_margin_pct, 38.89);
+        assert_eq!(response.profitable_revenue_share_pct, 55.56);
+        assert_eq!(response.negative_contribution_lines, vec!["services".to_string()]);
+        assert!(response.unit_economics_issues.iter().any(|i| i.contains("services is growing")));
+    }
+
+    #[test]
+    fn test_calculate_unit_economics_growth_rate_units() {
+        let params: UnitEconomicsParams = serde_json::from_value(serde_json::json!({
+            "segments": {
+                "services": {"revenue": 30, "cogs": 10, "growth_rate": "20%"},
+                "hardware": {"revenue": 50, "cogs": 40, "growth_rate": 15}
+            }
+        }))
+        .unwrap();
+
+        let response = UnitEconomics::calculate(params).unwrap();
+
+        let growth: HashMap<&str, Option<f64>> = response.lines.iter().map(|l| (l.name.as_str(), l.growth_pct)).collect();
+        assert_eq!(growth["services"], Some(20.0));
+        assert_eq!(growth["hardware"], Some(1500.0));
+        assert!(response.parsing_notes.iter().any(|n| n.starts_with("segments.services.growth_rate")));
+        assert_eq!(response.warnings.len(), 1);
+        assert!(response.warnings[0].starts_with("segments.hardware.growth_rate"));
+    }
+}
@@ -0,0 +1,666 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::calculation::{converter, field_parser, fx_failure, rate_or, Calculation};
+use super::error::FinanceError;
+use super::currency::{CurrencyConversion, CurrencyConverter, CurrencyOptions, Period};
+use super::parsing::deserialize_flexible_f64_opt;
+use super::money::{check_money_range, decimal_from_f64, decimal_to_f64, round_ratio, MoneyRounding};
+
+const DEFAULT_MATERIALITY_PCT: f64 = 0.05;
+const PRICE_CONSISTENCY_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+
+// =================== DATA STRUCTURES ===================
+
+// Function: calculate_budget_variance
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct VarianceLineItem {
+    #[schemars(description = "Line item name (e.g., 'Product A', 'Marketing')")]
+    pub name: String,
+    #[schemars(description = "Line type: revenue, cogs, or opex")]
+    pub line_type: String,
+    #[schemars(description = "Operating expense category (e.g., 'sales_marketing', 'r_and_d', 'g_and_a')")]
+    #[serde(default)]
+    pub category: Option<String>,
+    #[schemars(description = "Budgeted amount, number or currency-tagged (optional when budget_volume and budget_price are given)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub budget: Option<String>,
+    #[schemars(description = "Actual amount, number or currency-tagged (optional when actual_volume and actual_price are given)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub actual: Option<String>,
+    #[schemars(description = "Budgeted units sold (revenue lines, enables price/volume/mix split)")]
+    #[serde(default)]
+    pub budget_volume: Option<f64>,
+    #[schemars(description = "Actual units sold (revenue lines, enables price/volume/mix split)")]
+    #[serde(default)]
+    pub actual_volume: Option<f64>,
+    #[schemars(description = "Budgeted price per unit, number or currency-tagged (derived from budget / budget_volume if omitted)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub budget_price: Option<String>,
+    #[schemars(description = "Actual price per unit, number or currency-tagged (derived from actual / actual_volume if omitted)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub actual_price: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BudgetVarianceParams {
+    #[schemars(description = "Budget and actual figures per line item")]
+    pub line_items: Vec<VarianceLineItem>,
+    #[schemars(description = "Variance share of budget above which a line is flagged as material, as a ratio (e.g., 0.05 or '5%', default: 0.05)")]
+    #[serde(default, deserialize_with = "deserialize_flexible_f64_opt")]
+    pub materiality_threshold: Option<String>,
+    #[serde(flatten)]
+    pub currency: CurrencyOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct VarianceLineResult {
+    #[schemars(description = "Line item name")]
+    pub name: String,
+    #[schemars(description = "Line type: revenue, cogs, or opex")]
+    pub line_type: String,
+    #[schemars(description = "Operating expense category, if any")]
+    pub category: Option<String>,
+    #[schemars(description = "Budgeted amount", with = "String")]
+    pub budget: Decimal,
+    #[schemars(description = "Actual amount", with = "String")]
+    pub actual: Decimal,
+    #[schemars(description = "Actual minus budget", with = "String")]
+    pub variance: Decimal,
+    #[schemars(description = "Variance as percentage of budget (absent when budget is zero)")]
+    pub variance_pct: Option<f64>,
+    #[schemars(description = "Favorable, Unfavorable, or On budget given the line type")]
+    pub status: String,
+    #[schemars(description = "Whether the variance exceeds the materiality threshold")]
+    pub material: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct VarianceSummary {
+    #[schemars(description = "Budgeted total", with = "String")]
+    pub budget: Decimal,
+    #[schemars(description = "Actual total", with = "String")]
+    pub actual: Decimal,
+    #[schemars(description = "Actual minus budget", with = "String")]
+    pub variance: Decimal,
+    #[schemars(description = "Variance as percentage of budget (absent when budget is zero)")]
+    pub variance_pct: Option<f64>,
+    #[schemars(description = "Favorable, Unfavorable, or On budget")]
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RevenueEffects {
+    #[schemars(description = "Variance from selling at different prices: (actual price - budget price) x actual volume", with = "String")]
+    pub price_effect: Decimal,
+    #[schemars(description = "Variance from selling more or fewer total units at budget mix and prices", with = "String")]
+    pub volume_effect: Decimal,
+    #[schemars(description = "Variance from shifting volume between higher and lower priced lines", with = "String")]
+    pub mix_effect: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct RevenueVarianceDecomposition {
+    #[schemars(description = "Total price, volume and mix effects (sum to the total revenue variance)")]
+    pub total: RevenueEffects,
+    #[schemars(description = "Price, volume and mix effects per revenue line")]
+    pub by_line: HashMap<String, RevenueEffects>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, schemars::JsonSchema)]
+pub struct BudgetVarianceResponse {
+    #[schemars(description = "Variance per line item")]
+    pub line_items: Vec<VarianceLineResult>,
+    #[schemars(description = "Total revenue variance")]
+    pub revenue: VarianceSummary,
+    #[schemars(description = "Total cost of goods sold variance")]
+    pub cogs: VarianceSummary,
+    #[schemars(description = "Gross profit variance (revenue - COGS)")]
+    pub gross_profit: VarianceSummary,
+    #[schemars(description = "Total operating expense variance")]
+    pub opex: VarianceSummary,
+    #[schemars(description = "Operating expense variance per category")]
+    pub opex_by_category: HashMap<String, VarianceSummary>,
+    #[schemars(description = "Operating income variance (gross profit - opex)")]
+    pub operating_income: VarianceSummary,
+    #[schemars(description = "Operating income variance (actual - budget)", with = "String")]
+    pub operating_income_variance: Decimal,
+    #[schemars(description = "Overall status based on operating income: Favorable, Unfavorable, or On budget")]
+    pub overall_status: String,
+    #[schemars(description = "Price, volume and mix split of the revenue variance, when volumes are supplied for every revenue line")]
+    pub revenue_decomposition: Option<RevenueVarianceDecomposition>,
+    #[schemars(description = "Material unfavorable variances requiring attention")]
+    pub variance_issues: Vec<String>,
+    #[schemars(description = "Plain language summary of the variance analysis")]
+    pub interpretation: String,
+    #[schemars(description = "Currency conversion applied to currency-tagged amounts, if any")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency_conversion: Option<CurrencyConversion>,
+    #[schemars(description = "How values written as percentages, basis points, with suffixes or in accounting notation were interpreted")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parsing_notes: Vec<String>,
+    #[schemars(description = "Inputs whose magnitude looks implausible for their unit (e.g., 5 for a 5% threshold)")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+// =================== CALCULATION ===================
+
+#[derive(Clone, Copy, PartialEq)]
+enum LineType {
+    Revenue,
+    Cogs,
+    Opex,
+}
+
+impl LineType {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "revenue" => Ok(LineType::Revenue),
+            "cogs" => Ok(LineType::Cogs),
+            "opex" => Ok(LineType::Opex),
+            other => Err(format!("Invalid line_type '{}': must be revenue, cogs, or opex", other)),
+        }
+    }
+
+    /// Revenue above budget is favorable; costs above budget are unfavorable
+    fn status(self, variance: Decimal) -> &'static str {
+        status_for(if self == LineType::Revenue { variance } else { -variance })
+    }
+}
+
+/// Status of a variance already signed so that positive means better than budget
+fn status_for(signed_variance: Decimal) -> &'static str {
+    if signed_variance.is_zero() {
+        "On budget"
+    } else if signed_variance.is_sign_positive() {
+        "Favorable"
+    } else {
+        "Unfavorable"
+    }
+}
+
+fn variance_pct(variance: Decimal, budget: Decimal) -> Option<f64> {
+    (!budget.is_zero()).then(|| round_ratio(decimal_to_f64(variance / budget.abs()) * 100.0, 2))
+}
+
+fn summary(budget: Decimal, actual: Decimal, higher_is_better: bool, rounding: MoneyRounding) -> VarianceSummary {
+    let variance = actual - budget;
+    VarianceSummary {
+        budget: rounding.round(budget),
+        actual: rounding.round(actual),
+        variance: rounding.round(variance),
+        variance_pct: variance_pct(variance, budget),
+        status: status_for(if higher_is_better { variance } else { -variance }).to_string(),
+    }
+}
+
+/// Volume x price as an exact decimal amount
+fn volume_times_price(volume: f64, price: Decimal) -> Result<Decimal, String> {
+    decimal_from_f64(volume)?
+        .checked_mul(price)
+        .ok_or_else(|| "amount exceeds the supported magnitude of 1e18".to_string())
+        .and_then(check_money_range)
+}
+
+/// Resolve an amount in reporting currency from either the explicit value or volume x price.
+///
+/// Unit prices are tagged and converted like amounts; returns the amount and the converted
+/// unit price, when one was given.
+fn resolve_amount(
+    fx: &mut CurrencyConverter,
+    name: &str,
+    label: &str,
+    amount: Option<&str>,
+    volume: Option<f64>,
+    price: Option<&str>,
+    period: Period,
+) -> Result<(Decimal, Option<Decimal>), FinanceError> {
+    let path = |field: &str| format!("line_items.{}.{}", name, field);
+    let mut parse = |value: &str, field: &str| {
+        fx.parse_money(value, &format!("{}.{}", name, field), period).map_err(|e| {
+            FinanceError::invalid_input(format!("Line '{}': invalid {}: {}", name, field, e)).with_field(&path(field))
+        })
+    };
+    let amount = amount.map(|a| parse(a, label)).transpose()?;
+    let price = price.map(|p| parse(p, &format!("{}_price", label))).transpose()?;
+    let times_price = |v: f64, p: Decimal| {
+        volume_times_price(v, p).map_err(|e| {
+            FinanceError::invalid_input(format!("Line '{}': invalid {} volume x price: {}", name, label, e))
+                .with_field(&path(label))
+        })
+    };
+
+    let resolved = match (amount, volume, price) {
+        (Some(a), Some(v), Some(p)) => {
+            let implied = times_price(v, p)?;
+            if (implied - a).abs() > PRICE_CONSISTENCY_TOLERANCE * a.abs().max(Decimal::ONE) {
+                return Err(FinanceError::sum_mismatch(format!(
+                    "Line '{}': {} amount {} is inconsistent with volume x price ({})",
+                    name, label, a, implied
+                ))
+                .with_field(&path(label)));
+            }
+            a
+        }
+        (Some(a), _, _) => a,
+        (None, Some(v), Some(p)) => times_price(v, p)?,
+        _ => {
+            return Err(FinanceError::insufficient_data(format!(
+                "Line '{}': {} amount or both {} volume and price are required",
+                name, label, label
+            ))
+            .with_field(&path(label)));
+        }
+    };
+    Ok((resolved, price))
+}
+
+/// Revenue line resolved into reporting currency, with the FX rates used
+struct RevenueLine<'a> {
+    item: &'a VarianceLineItem,
+    budget: Decimal,
+    actual: Decimal,
+    budget_price: Option<Decimal>,
+    actual_price: Option<Decimal>,
+}
+
+/// Split the revenue variance into price, volume and mix effects.
+///
+/// With budget mix m_i = vb_i / Vb and actual total volume Va:
+///   price_i  = (pa_i - pb_i) x va_i
+///   volume_i = (Va x m_i - vb_i) x pb_i
+///   mix_i    = (va_i - Va x m_i) x pb_i
+/// so that price_i + volume_i + mix_i = actual_i - budget_i.
+fn decompose_revenue(
+    lines: &[RevenueLine],
+    rounding: MoneyRounding,
+) -> Result<Option<RevenueVarianceDecomposition>, FinanceError> {
+    let mut points = Vec::new();
+    for RevenueLine { item, budget, actual, budget_price, actual_price } in lines.iter() {
+        let (Some(vb), Some(va)) = (item.budget_volume, item.actual_volume) else {
+            return Ok(None);
+        };
+        let path = |field: &str| format!("line_items.{}.{}", item.name, field);
+        for (field, volume) in [("budget_volume", vb), ("actual_volume", va)] {
+            if volume < 0.0 {
+                return Err(FinanceError::out_of_range(
+                    &path(field),
+                    Some(0.0),
+                    None,
+                    format!("Line '{}': volumes cannot be negative", item.name),
+                ));
+            }
+        }
+        if vb == 0.0 && budget_price.is_none() {
+            return Err(FinanceError::insufficient_data(format!(
+                "Line '{}': budget_price is required when budget_volume is zero",
+                item.name
+            ))
+            .with_field(&path("budget_price")));
+        }
+        if va == 0.0 && actual_price.is_none() {
+            return Err(FinanceError::insufficient_data(format!(
+                "Line '{}': actual_price is required when actual_volume is zero",
+                item.name
+            ))
+            .with_field(&path("actual_price")));
+        }
+        let price = |price: Option<Decimal>, amount: Decimal, volume: f64, field: &str| match price {
+            Some(p) => Ok(p),
+            None => decimal_from_f64(volume).map(|v| amount / v).map_err(|e| FinanceError::invalid_field(&path(field), e)),
+        };
+        let pb = price(*budget_price, *budget, vb, "budget_price")?;
+        let pa = price(*actual_price, *actual, va, "actual_price")?;
+        let volume = |v: f64, field: &str| decimal_from_f64(v).map_err(|e| FinanceError::invalid_field(&path(field), e));
+        points.push((item.name.clone(), volume(vb, "budget_volume")?, volume(va, "actual_volume")?, pb, pa));
+    }
+
+    let total_budget_volume: Decimal = points.iter().map(|p| p.1).sum();
+    let total_actual_volume: Decimal = points.iter().map(|p| p.2).sum();
+    if total_budget_volume <= Decimal::ZERO {
+        return Err(FinanceError::out_of_range(
+            "line_items",
+            Some(0.0),
+            None,
+            "Total budget volume must be positive for the price/volume/mix split",
+        ));
+    }
+
+    let effect = |a: Decimal, b: Decimal| {
+        a.checked_mul(b).ok_or_else(|| {
+            FinanceError::out_of_range(
+                "line_items",
+                None,
+                None,
+                "Price, volume and mix effects exceed the supported magnitude of 1e18",
+            )
+        })
+    };
+    let mut total = RevenueEffects {
+        price_effect: Decimal::ZERO,
+        volume_effect: Decimal::ZERO,
+        mix_effect: Decimal::ZERO,
+    };
+    let mut by_line = HashMap::new();
+
+    for (name, vb, va, pb, pa) in points.into_iter() {
+        let at_budget_mix = effect(total_actual_volume, vb / total_budget_volume)?;
+        let price_effect = effect(pa - pb, va)?;
+        let volume_effect = effect(at_budget_mix - vb, pb)?;
+        let mix_effect = effect(va - at_budget_mix, pb)?;
+
+        total.price_effect += price_effect;
+        total.volume_effect += volume_effect;
+        total.mix_effect += mix_effect;
+        by_line.insert(name, RevenueEffects {
+            price_effect: rounding.round(price_effect),
+            volume_effect: rounding.round(volume_effect),
+            mix_effect: rounding.round(mix_effect),
+        });
+    }
+
+    Ok(Some(RevenueVarianceDecomposition {
+        total: RevenueEffects {
+            price_effect: rounding.round(total.price_effect),
+            volume_effect: rounding.round(total.volume_effect),
+            mix_effect: rounding.round(total.mix_effect),
+        },
+        by_line,
+    }))
+}
+
+/// Calculate budget versus actual variances.
+///
+/// Budget amounts are converted at prior-period (budget) rates and actuals at current rates;
+/// with constant currency the actuals are restated at budget rates.
+pub fn calculate_budget_variance_internal(
+    line_items: Vec<VarianceLineItem>,
+    materiality: f64,
+    fx: &mut CurrencyConverter,
+) -> Result<BudgetVarianceResponse, FinanceError> {
+    if line_items.is_empty() {
+        return Err(FinanceError::insufficient_data("Line items cannot be empty").with_field("line_items"));
+    }
+
+    if !(0.0..=1.0).contains(&materiality) {
+        return Err(FinanceError::out_of_range(
+            "materiality_threshold",
+            Some(0.0),
+            Some(1.0),
+            "materiality_threshold must be between 0.0 and 1.0",
+        ));
+    }
+
+    let rounding = fx.rounding();
+    let zero = (Decimal::ZERO, Decimal::ZERO);
+    let mut results = Vec::new();
+    let mut revenue_lines = Vec::new();
+    let (mut revenue, mut cogs, mut opex) = (zero, zero, zero);
+    let mut opex_categories: HashMap<String, (Decimal, Decimal)> = HashMap::new();
+    let mut variance_issues = Vec::new();
+
+    for item in line_items.iter() {
+        let line_type = LineType::parse(&item.line_type)
+            .map_err(|e| FinanceError::invalid_input(e).with_field(&format!("line_items.{}.line_type", item.name)))?;
+        let (budget, budget_price) = resolve_amount(
+            fx,
+            &item.name,
+            "budget",
+            item.budget.as_deref(),
+            item.budget_volume,
+            item.budget_price.as_deref(),
+            Period::Prior,
+        )?;
+        let (actual, actual_price) = resolve_amount(
+            fx,
+            &item.name,
+            "actual",
+            item.actual.as_deref(),
+            item.actual_volume,
+            item.actual_price.as_deref(),
+            Period::Current,
+        )?;
+        if budget.is_sign_negative() || actual.is_sign_negative() {
+            return Err(FinanceError::out_of_range(
+                &format!("line_items.{}", item.name),
+                Some(0.0),
+                None,
+                format!("Line '{}': amounts cannot be negative", item.name),
+            ));
+        }
+
+        let variance = actual - budget;
+        let status = line_type.status(variance);
+        let material = if !budget.is_zero() {
+            decimal_to_f64(variance.abs() / budget.abs()) > materiality
+        } else {
+            !variance.is_zero()
+        };
+
+        if material && status == "Unfavorable" {
+            variance_issues.push(match variance_pct(variance, budget) {
+                Some(pct) => format!("{} ({}) is unfavorable by {:.2} ({:+.1}% vs budget)", item.name, item.line_type, variance.abs(), pct),
+                None => format!("{} ({}) is unfavorable by {:.2} against a zero budget", item.name, item.line_type, variance.abs()),
+            });
+        }
+
+        let totals = match line_type {
+            LineType::Revenue => {
+                revenue_lines.push(RevenueLine { item, budget, actual, budget_price, actual_price });
+                &mut revenue
+            }
+            LineType::Cogs => &mut cogs,
+            LineType::Opex => {
+                let category = item.category.clone().unwrap_or_else(|| "uncategorized".to_string());
+                let entry = opex_categories.entry(category).or_insert(zero);
+                entry.0 += budget;
+                entry.1 += actual;
+                &mut opex
+            }
+        };
+        totals.0 += budget;
+        totals.1 += actual;
+
+        results.push(VarianceLineResult {
+            name: item.name.clone(),
+            line_type: item.line_type.trim().to_lowercase(),
+            category: item.category.clone(),
+            budget: rounding.round(budget),
+            actual: rounding.round(actual),
+            variance: rounding.round(variance),
+            variance_pct: variance_pct(variance, budget),
+            status: status.to_string(),
+            material,
+        });
+    }
+
+    let revenue_decomposition = if revenue_lines.is_empty() {
+        None
+    } else {
+        decompose_revenue(&revenue_lines, rounding)?
+    };
+
+    let gross_profit = (revenue.0 - cogs.0, revenue.1 - cogs.1);
+    let operating_income = (gross_profit.0 - opex.0, gross_profit.1 - opex.1);
+    let operating_income_summary = summary(operating_income.0, operating_income.1, true, rounding);
+
+    let interpretation = format!(
+        "Operating income is {} budget by {:.2}{}: revenue {} by {:.2}, COGS {} by {:.2}, operating expenses {} by {:.2}.",
+        if operating_income.1 >= operating_income.0 { "above" } else { "below" },
+        (operating_income.1 - operating_income.0).abs(),
+        operating_income_summary
+            .variance_pct
+            .map(|p| format!(" ({:+.1}%)", p))
+            .unwrap_or_default(),
+        if revenue.1 >= revenue.0 { "beat budget" } else { "missed budget" },
+        (revenue.1 - revenue.0).abs(),
+        if cogs.1 <= cogs.0 { "came in under budget" } else { "overran budget" },
+        (cogs.1 - cogs.0).abs(),
+        if opex.1 <= opex.0 { "came in under budget" } else { "overran budget" },
+        (opex.1 - opex.0).abs(),
+    );
+
+    Ok(BudgetVarianceResponse {
+        line_items: results,
+        revenue: summary(revenue.0, revenue.1, true, rounding),
+        cogs: summary(cogs.0, cogs.1, false, rounding),
+        gross_profit: summary(gross_profit.0, gross_profit.1, true, rounding),
+        opex: summary(opex.0, opex.1, false, rounding),
+        opex_by_category: opex_categories
+            .into_iter()
+            .map(|(category, (budget, actual))| (category, summary(budget, actual, false, rounding)))
+            .collect(),
+        operating_income_variance: operating_income_summary.variance,
+        overall_status: operating_income_summary.status.clone(),
+        operating_income: operating_income_summary,
+        revenue_decomposition,
+        variance_issues,
+        interpretation,
+        currency_conversion: None,
+        parsing_notes: Vec::new(),
+        warnings: Vec::new(),
+    })
+}
+
+// =================== CALCULATION ===================
+
+/// Budget versus actual variance analysis (`calculate_budget_variance`)
+pub struct BudgetVariance;
+
+impl Calculation for BudgetVariance {
+    const NAME: &'static str = "calculate_budget_variance";
+    const DESCRIPTION: &'static str = "Analyze budget versus actual variances for FP&A. Takes budget and actual line items typed as revenue, cogs or opex (with optional opex category and optional volume and price). Returns absolute and percentage variance per line flagged Favorable/Unfavorable by line type (revenue above budget is favorable, costs above budget are unfavorable), totals for revenue, COGS, gross profit, opex by category and operating income, and a price/volume/mix split of the revenue variance when volumes are supplied. Use ALWAYS FIRST function `get_metrics_from_vector_store` to get the financial context to extract metrics from.";
+    const PRIMARY_METRIC: &'static str = "operating_income_variance";
+    const BAND_FIELD: Option<&'static str> = Some("overall_status");
+
+    type Params = BudgetVarianceParams;
+    type Response = BudgetVarianceResponse;
+
+    fn calculate(params: BudgetVarianceParams) -> Result<BudgetVarianceResponse, FinanceError> {
+        let mut fx = converter(&params.currency)?;
+        let mut units = field_parser(params.currency.locale.as_deref())?;
+        let materiality = rate_or(
+            &mut units,
+            params.materiality_threshold.as_deref(),
+            "materiality_threshold",
+            DEFAULT_MATERIALITY_PCT,
+        )?;
+        let mut result = calculate_budget_variance_internal(params.line_items, materiality, &mut fx)
+            .map_err(|e| fx_failure(&fx, e))?;
+        result.parsing_notes = fx.take_parsing_notes();
+        result.parsing_notes.extend(units.take_notes());
+        result.warnings = units.take_warnings();
+        result.currency_conversion = fx.into_summary();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_budget_variance() {
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [
+                {"name": "Basic", "line_type": "revenue", "budget": 1000.0, "actual": 1320.0,
+                 "budget_volume": 100.0, "actual_volume": 120.0},
+                {"name": "Premium", "line_type": "revenue", "budget": 3000.0, "actual": 2520.0,
+                 "budget_volume": 100.0, "actual_volume": 90.0},
+                {"name": "Materials", "line_type": "cogs", "budget": 1500.0, "actual": 1450.0},
+                {"name": "Campaigns", "line_type": "opex", "category": "sales_marketing", "budget": 800.0, "actual": 950.0},
+                {"name": "Salaries", "line_type": "opex", "category": "g_and_a", "budget": 600.0, "actual": 600.0}
+            ]
+        })).unwrap();
+
+        let response = BudgetVariance::calculate(params).unwrap();
+
+        // Revenue 4000 -> 3840, COGS 1500 -> 1450, opex 1400 -> 1550
+        assert_eq!(response.revenue.variance, Decimal::new(-160, 0));
+        assert_eq!(response.revenue.status, "Unfavorable");
+        assert_eq!(response.cogs.status, "Favorable");
+        assert_eq!(response.opex_by_category["g_and_a"].status, "On budget");
+        assert_eq!(response.operating_income_variance, Decimal::new(-260, 0));
+        assert_eq!(response.overall_status, "Unfavorable");
+        assert_eq!(response.line_items[3].variance_pct, Some(18.75));
+        assert!(response.line_items[3].material);
+        assert!(response.variance_issues.iter().any(|i| i.contains("Campaigns")));
+
+        // Budget prices 10 and 30, actual prices 11 and 28, total volume 200 -> 210
+        // price = 1*120 - 2*90 = -60; volume = 10 units x avg price 20 = 200; mix = -300
+        let effects = &response.revenue_decomposition.unwrap().total;
+        assert_eq!(effects.price_effect, Decimal::new(-60, 0));
+        assert_eq!(effects.volume_effect, Decimal::new(200, 0));
+        assert_eq!(effects.mix_effect, Decimal::new(-300, 0));
+        assert_eq!(effects.price_effect + effects.volume_effect + effects.mix_effect, response.revenue.variance);
+    }
+
+    #[test]
+    fn test_calculate_budget_variance_invalid_line_type() {
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [{"name": "Capex", "line_type": "capex", "budget": 100.0, "actual": 90.0}]
+        })).unwrap();
+
+        let error = BudgetVariance::calculate(params).unwrap_err();
+        assert!(error.message.contains("Invalid line_type"));
+        assert_eq!(error.field.as_deref(), Some("line_items.Capex.line_type"));
+    }
+
+    #[test]
+    fn test_calculate_budget_variance_converts_volume_times_price_lines() {
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [
+                {"name": "Widgets", "line_type": "revenue", "budget_volume": 100, "budget_price": "EUR 10",
+                 "actual_volume": 110, "actual_price": "EUR 10"}
+            ],
+            "reporting_currency": "USD",
+            "fx_rates": {"EUR": 1.1},
+            "prior_fx_rates": {"EUR": 1.0}
+        })).unwrap();
+
+        let response = BudgetVariance::calculate(params).unwrap();
+
+        // Budget 1000 EUR at 1.0, actual 1100 EUR at 1.1; the unit price moved only through FX
+        assert_eq!(response.revenue.budget, Decimal::new(1000, 0));
+        assert_eq!(response.revenue.actual, Decimal::new(1210, 0));
+        let effects = &response.revenue_decomposition.unwrap().total;
+        assert_eq!(effects.price_effect, Decimal::new(110, 0));
+        assert_eq!(effects.volume_effect, Decimal::new(100, 0));
+        assert_eq!(effects.price_effect + effects.volume_effect + effects.mix_effect, response.revenue.variance);
+
+        // An untagged price next to tagged amounts is ambiguous without a reporting currency
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [
+                {"name": "Widgets", "line_type": "revenue", "budget": "EUR 1000", "actual": "EUR 1100"},
+                {"name": "Gadgets", "line_type": "revenue", "budget_volume": 10, "budget_price": 5,
+                 "actual_volume": 10, "actual_price": 5}
+            ]
+        })).unwrap();
+
+        let error = BudgetVariance::calculate(params).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("line_items.Gadgets.budget_price"));
+        assert!(error.message.contains("mix currency-tagged and untagged values"));
+    }
+
+    #[test]
+    fn test_calculate_budget_variance_materiality_threshold_units() {
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [{"name": "Campaigns", "line_type": "opex", "budget": 800.0, "actual": 950.0}],
+            "materiality_threshold": "20%"
+        })).unwrap();
+
+        let response = BudgetVariance::calculate(params).unwrap();
+        assert!(!response.line_items[0].material);
+        assert!(response.parsing_notes.iter().any(|n| n.starts_with("materiality_threshold")));
+
+        let params: BudgetVarianceParams = serde_json::from_value(serde_json::json!({
+            "line_items": [{"name": "Campaigns", "line_type": "opex", "budget": 800.0, "actual": 950.0}],
+            "materiality_threshold": 5
+        })).unwrap();
+
+        let error = BudgetVariance::calculate(params).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("materiality_threshold"));
+    }
+}
@@ -0,0 +1,45 @@
+//! Finance Engine calculation library.
+//!
+//! The financial calculations behind the Finance Engine MCP server, usable from any Rust
+//! service without MCP. Each calculation lives in its own module under [`common`] with
+//! typed parameters, a typed response and a `*_internal` function that computes one from
+//! the other:
+//!
+//! ```
+//! use finance_engine::common::business_metrics::calculate_company_health_score_internal;
+//!
+//! // Revenue growth and SLA compliance as ratios, customer satisfaction on a 0-100 scale
+//! let health = calculate_company_health_score_internal(0.09, 0.985, 89.0).unwrap();
+//! assert_eq!(health.risk_level, "LOW");
+//! ```
+//!
+//! Parameters take numbers as strings in the same notations the MCP tools accept
+//! (`"12.5M"`, `"(3.2%)"`, `"EUR 1.234,5"`), so requests can be forwarded unchanged.
+//! Every calculation implements [`Calculation`], which parses those parameters and returns
+//! the response, and [`run_calculation`] runs any calculation by tool name from its JSON
+//! arguments:
+//!
+//! ```
+//! use serde_json::json;
+//!
+//! let result = finance_engine::run_calculation(
+//!     "calculate_hhi_and_diversification",
+//!     json!({ "revenues": ["500", "300", "200"] }),
+//! )
+//! .unwrap();
+//! assert_eq!(result["hhi"], json!(0.38));
+//! ```
+//!
+//! Every calculation fails with a [`FinanceError`] carrying a stable [`ErrorCode`].
+//!
+//! The default `mcp` feature adds the MCP server adapters (`common::finance_engine`,
+//! `common::metrics`) used by the `stdio_server` and `mcp_server` binaries. Depend on the
+//! crate with `default-features = false` to get the calculations alone.
+
+pub mod common;
+
+pub use common::calculation::{
+    calculation_input_fields, calculation_names, calculation_output_fields, run_calculation_internal as run_calculation,
+    Calculation,
+};
+pub use common::error::{AllowedRange, ErrorCode, FinanceError};
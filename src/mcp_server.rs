@@ -6,8 +6,7 @@ use tracing_subscriber::{
     util::SubscriberInitExt,
     {self},
 };
-mod common;
-use common::{finance_engine::FinanceEngine, metrics};
+use finance_engine::common::{finance_engine::FinanceEngine, metrics};
 use axum::{
     response::IntoResponse,
     http::{StatusCode, Request, header},
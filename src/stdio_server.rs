@@ -3,8 +3,7 @@ use anyhow::Result;
 use rmcp::{ServiceExt, transport::stdio};
 use tracing_subscriber::{self, EnvFilter};
 
-mod common;
-use common::finance_engine::FinanceEngine;
+use finance_engine::common::finance_engine::FinanceEngine;
 
 #[tokio::main]
 async fn main() -> Result<()> {